@@ -1,10 +1,87 @@
-use crate::*;
+use super::file_system::FileSystem;
+use super::template::TemplateError;
+use crate::parsing;
+use crate::parsing::table_compression::CompressionOptions;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-pub struct CodeGeneratorData<'a> {
-    pub table: parsing::parse_table::Table<'a>,
-    pub project_name: &'a String,
+//how generators compress tables. expected and error messages are by the state
+//error_messages::error_state finds, a default reduction would make the generated parser reduce
+//first and find the error in a later state
+pub const COMPRESSION: CompressionOptions = CompressionOptions {
+    default_reductions: false,
+};
+
+pub struct CodeGeneratorData {
+    pub table: parsing::parse_table::Table,
+    pub project_name: String,
+    //hand written syntax error messages by state, see parsing::error_messages
+    pub messages: BTreeMap<usize, String>,
+}
+
+pub enum CodegenError {
+    //path that couldn't be written and the underlying error
+    Io(PathBuf, std::io::Error),
+    //path that the file system refuses to hold (absolute, contains .., etc)
+    InvalidPath(PathBuf),
+    //template file that failed to parse or render
+    Template(PathBuf, TemplateError),
+    //the generator needs an LL(1) grammar, one message per conflict
+    NotLL1(Vec<String>),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodegenError::Io(path, e) => write!(f, "couldn't write {}: {}", path.display(), e),
+            CodegenError::InvalidPath(path) => write!(f, "invalid output path {}", path.display()),
+            CodegenError::Template(path, e) => write!(f, "template {}, {}", path.display(), e),
+            CodegenError::NotLL1(conflicts) => {
+                write!(f, "grammar is not LL(1)")?;
+                for conflict in conflicts {
+                    write!(f, "\n  {}", conflict)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+//list of files a generator created, relative to the root of the file system it wrote to
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct GeneratedFiles {
+    pub files: Vec<PathBuf>,
+}
+
+impl GeneratedFiles {
+    pub fn new() -> GeneratedFiles {
+        GeneratedFiles { files: Vec::new() }
+    }
+    //writes through the file system and records the path
+    pub fn write(
+        &mut self,
+        fs: &mut dyn FileSystem,
+        path: &Path,
+        contents: &[u8],
+    ) -> Result<(), CodegenError> {
+        fs.write_file(path, contents)?;
+        self.files.push(path.to_path_buf());
+        Ok(())
+    }
 }
 
 pub trait CodeGenerator {
-    fn generate(mut self, data: &CodeGeneratorData, dest_folder: &std::path::Path);
+    //writes the generated project into fs, paths are relative to the root of fs
+    fn generate(
+        &mut self,
+        data: &CodeGeneratorData,
+        fs: &mut dyn FileSystem,
+    ) -> Result<GeneratedFiles, CodegenError>;
 }
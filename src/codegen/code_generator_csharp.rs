@@ -1,36 +1,98 @@
-use super::code_generator;
-use std::fs::File;
-use std::io::Write;
+use super::code_generator::{
+    CodeGenerator, CodeGeneratorData, CodegenError, GeneratedFiles, COMPRESSION,
+};
+use super::code_generator_template::quote;
+use super::file_system::FileSystem;
+use crate::parsing::table_compression::compress;
 use std::path::Path;
 
-const parser_class_name: &str = "Parser";
-const program_class_name: &str = "Program";
+const PARSER_CLASS_NAME: &str = "Parser";
+const PROGRAM_CLASS_NAME: &str = "Program";
 
-pub struct CodeGeneratorCSharp {
-    program: File,
-    parser: File,
-}
+#[derive(Default)]
+pub struct CodeGeneratorCSharp {}
 
-impl code_generator::CodeGenerator for CodeGeneratorCSharp {
-    fn generate(mut self, data: &code_generator::CodeGeneratorData, dest_folder: &Path) {
-        generate_csproj(
-            dest_folder
-                .join(data.project_name)
-                .join(".csproj")
-                .as_path(),
-        );
+impl CodeGenerator for CodeGeneratorCSharp {
+    fn generate(
+        &mut self,
+        data: &CodeGeneratorData,
+        fs: &mut dyn FileSystem,
+    ) -> Result<GeneratedFiles, CodegenError> {
+        let mut files = GeneratedFiles::new();
+        files.write(
+            fs,
+            Path::new(&format!("{}.csproj", data.project_name)),
+            generate_csproj().as_bytes(),
+        )?;
+        files.write(
+            fs,
+            Path::new(&format!("{}.cs", PROGRAM_CLASS_NAME)),
+            generate_program(&data.project_name).as_bytes(),
+        )?;
+        files.write(
+            fs,
+            Path::new(&format!("{}.cs", PARSER_CLASS_NAME)),
+            generate_parser(data).as_bytes(),
+        )?;
+        Ok(files)
     }
 }
 
-fn generate_csproj(path: &Path) {
-    let mut file = match File::create(path) {
-        Err(e) => panic!("couldn't create file {}, error: {}", path.display(), e),
-        Ok(f) => f,
-    };
-    match file.write_all(b"<Project Sdk=\"Microsoft.NET.Sdk\">\n\t<PropertyGroup>\n\t\t<OutputType>Exe</OutputType>\n\t\t<TargetFramework>netcoreapp3.1</TargetFramework>\n\t</PropertyGroup>\n</Project>") {
-            Err(e) => panic!("couldn't write to file {}, error: {}", path.display(), e),
-            _ => ()
-        };
+pub(crate) fn generate_csproj() -> String {
+    String::from("<Project Sdk=\"Microsoft.NET.Sdk\">\n\t<PropertyGroup>\n\t\t<OutputType>Exe</OutputType>\n\t\t<TargetFramework>netcoreapp3.1</TargetFramework>\n\t</PropertyGroup>\n</Project>")
+}
+
+fn generate_program(project_name: &str) -> String {
+    format!(
+        "namespace {}\n{{\n\tclass {}\n\t{{\n\t\tstatic void Main(string[] args)\n\t\t{{\n\t\t\tvar parser = new {}();\n\t\t}}\n\t}}\n}}\n",
+        project_name, PROGRAM_CLASS_NAME, PARSER_CLASS_NAME
+    )
 }
 
-fn generate_program(path: &Path) {}
+//the parse table is embedded as comb vectors, see parsing::table_compression for the encoding
+fn generate_parser(data: &CodeGeneratorData) -> String {
+    let compressed = compress(&data.table, &COMPRESSION);
+    let mut arrays = String::new();
+    let mut array = |name: &str, values: &[usize]| {
+        let values: Vec<String> = values.iter().map(|x| x.to_string()).collect();
+        arrays.push_str(&format!(
+            "\t\tstatic readonly int[] {} = {{ {} }};\n",
+            name,
+            values.join(", ")
+        ));
+    };
+    array("DefaultReduction", &compressed.encoded_default_reductions());
+    array("ActionRow", &compressed.actions.row);
+    array("ActionBase", &compressed.actions.base);
+    array("ActionNext", &compressed.actions.encoded_next());
+    array("ActionCheck", &compressed.actions.encoded_check());
+    array("GotoRow", &compressed.gotos.row);
+    array("GotoBase", &compressed.gotos.base);
+    array("GotoNext", &compressed.gotos.encoded_next());
+    array("GotoCheck", &compressed.gotos.encoded_check());
+    let expected: Vec<String> = (0..data.table.rows.len())
+        .map(|state| quote(&data.table.cfg.describe_list(&data.table.expected(state))))
+        .collect();
+    arrays.push_str(&format!(
+        "\t\t//what each state expects, for error messages\n\t\tpublic static readonly string[] Expected = {{ {} }};\n",
+        expected.join(", ")
+    ));
+    let messages: Vec<String> = (0..data.table.rows.len())
+        .map(|state| match data.messages.get(&state) {
+            Some(message) => quote(message),
+            None => String::from("null"),
+        })
+        .collect();
+    arrays.push_str(&format!(
+        "\t\t//hand written message for an error in each state, or null\n\t\tpublic static readonly string[] Messages = {{ {} }};\n",
+        messages.join(", ")
+    ));
+    format!(
+        "namespace {}\n{{\n\tclass {}\n\t{{\n\t\tpublic const int StateCount = {};\n\t\t//column of <eof> in the action table\n\t\tpublic const int EofColumn = {};\n{}\n\t\t//0 is an error, otherwise the low 2 bits are the kind (1 shift, 2 reduce, 3 goto, 0 accept) and the rest is the target\n\t\tpublic static int Action(int state, int column)\n\t\t{{\n\t\t\tint row = ActionRow[state];\n\t\t\tint slot = ActionBase[row] + column;\n\t\t\tif (slot < ActionCheck.Length && ActionCheck[slot] == row + 1)\n\t\t\t\treturn ActionNext[slot];\n\t\t\treturn DefaultReduction[state] == 0 ? 0 : 4 * (DefaultReduction[state] - 1) + 2;\n\t\t}}\n\n\t\tpublic static int Goto(int state, int nonterminal)\n\t\t{{\n\t\t\tint row = GotoRow[state];\n\t\t\tint slot = GotoBase[row] + nonterminal;\n\t\t\tif (slot < GotoCheck.Length && GotoCheck[slot] == row + 1)\n\t\t\t\treturn GotoNext[slot] / 4;\n\t\t\treturn -1;\n\t\t}}\n\t}}\n}}\n",
+        data.project_name,
+        PARSER_CLASS_NAME,
+        data.table.rows.len(),
+        compressed.terminal_count,
+        arrays
+    )
+}
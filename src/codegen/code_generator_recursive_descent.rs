@@ -0,0 +1,362 @@
+//C# recursive descent parser for LL(1) grammars, one method per nonterminal that switches on the next token
+//meant to be read and edited by hand, unlike the table driven parser from code_generator_csharp
+
+use super::code_generator::{CodeGenerator, CodeGeneratorData, CodegenError, GeneratedFiles};
+use super::code_generator_csharp::generate_csproj;
+use super::code_generator_template::{ident, quote, unique};
+use super::file_system::FileSystem;
+use crate::parsing::grammar::{Symbol, CFG};
+use crate::parsing::ll1_generator::{self, LL1Table};
+use std::path::Path;
+
+const PARSER_CLASS_NAME: &str = "Parser";
+const PROGRAM_CLASS_NAME: &str = "Program";
+//TokenKind member for the end of input
+const EOF_KIND: &str = "EndOfInput";
+
+#[derive(Default)]
+pub struct CodeGeneratorRecursiveDescent {}
+
+impl CodeGenerator for CodeGeneratorRecursiveDescent {
+    fn generate(
+        &mut self,
+        data: &CodeGeneratorData,
+        fs: &mut dyn FileSystem,
+    ) -> Result<GeneratedFiles, CodegenError> {
+        let cfg = &data.table.cfg;
+        let table = ll1_generator::generate(cfg).map_err(|conflicts| {
+            CodegenError::NotLL1(
+                conflicts
+                    .iter()
+                    .map(|c| ll1_generator::conflict_message(cfg, c))
+                    .collect(),
+            )
+        })?;
+        let mut files = GeneratedFiles::new();
+        files.write(
+            fs,
+            Path::new(&format!("{}.csproj", data.project_name)),
+            generate_csproj().as_bytes(),
+        )?;
+        files.write(
+            fs,
+            Path::new(&format!("{}.cs", PROGRAM_CLASS_NAME)),
+            generate_program(&data.project_name).as_bytes(),
+        )?;
+        files.write(
+            fs,
+            Path::new(&format!("{}.cs", PARSER_CLASS_NAME)),
+            generate_parser(&data.project_name, &table).as_bytes(),
+        )?;
+        Ok(files)
+    }
+}
+
+//collects lines at an indent level, tabs like the rest of the generated C#
+struct Writer {
+    out: String,
+    indent: usize,
+}
+
+impl Writer {
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            for _ in 0..self.indent {
+                self.out.push('\t');
+            }
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+    fn open(&mut self) {
+        self.line("{");
+        self.indent += 1;
+    }
+    fn close(&mut self, after: &str) {
+        self.indent -= 1;
+        self.line(&format!("}}{}", after));
+    }
+}
+
+//C# reserved words, an identifier spelled like one needs an @ in front
+const KEYWORDS: &str =
+    "abstract as base bool break byte case catch char checked class const continue decimal \
+    default delegate do double else enum event explicit extern false finally fixed float for \
+    foreach goto if implicit in int interface internal is lock long namespace new null \
+    object operator out override params private protected public readonly ref return sbyte \
+    sealed short sizeof stackalloc static string struct switch this throw true try typeof \
+    uint ulong unchecked unsafe ushort using virtual void volatile while";
+
+//C# names for the symbols, TokenKind members by terminal and parse methods by nonterminal
+struct Names {
+    kinds: Vec<String>,
+    methods: Vec<String>,
+}
+
+impl Names {
+    fn new(cfg: &CFG) -> Names {
+        let kinds = unique(cfg.terminal_symbols.iter().map(ident), &[EOF_KIND]);
+        let methods = (1..cfg.nonterminal_symbols.len())
+            .map(|x| format!("Parse{}", ident(&cfg.nonterminal_symbols[x])));
+        let mut methods = unique(methods, &["Parse", PARSER_CLASS_NAME]);
+        methods.insert(0, String::from("Parse"));
+        Names {
+            kinds: kinds
+                .into_iter()
+                .map(|x| match KEYWORDS.split_whitespace().any(|k| k == x) {
+                    true => format!("@{}", x),
+                    false => x,
+                })
+                .collect(),
+            methods,
+        }
+    }
+
+    fn kind(&self, symbol: &Symbol) -> String {
+        match symbol {
+            Symbol::Terminal(x) => format!("TokenKind.{}", self.kinds[*x]),
+            _ => format!("TokenKind.{}", EOF_KIND),
+        }
+    }
+}
+
+fn generate_program(project_name: &str) -> String {
+    let mut w = Writer {
+        out: String::new(),
+        indent: 0,
+    };
+    w.line("using System;");
+    w.line("using System.Collections.Generic;");
+    w.line("");
+    w.line(&format!("namespace {}", project_name));
+    w.open();
+    w.line(&format!("class {}", PROGRAM_CLASS_NAME));
+    w.open();
+    w.line("//reads whitespace separated terminal names from stdin and prints the parse tree");
+    w.line("static void Main(string[] args)");
+    w.open();
+    w.line("try");
+    w.open();
+    w.line("var tokens = new List<Token>();");
+    w.line("var lines = Console.In.ReadToEnd().Split('\\n');");
+    w.line("for (int line = 0; line < lines.Length; line++)");
+    w.open();
+    w.line("int column = 0;");
+    w.line("foreach (var word in lines[line].Split(' ', '\\t', '\\r'))");
+    w.open();
+    w.line("column += word.Length + 1;");
+    w.line("if (word.Length == 0)");
+    w.line("\tcontinue;");
+    w.line(&format!(
+        "tokens.Add(new Token({}.KindOf(word), word, line + 1, column - word.Length));",
+        PARSER_CLASS_NAME
+    ));
+    w.close("");
+    w.close("");
+    w.line(&format!(
+        "Console.WriteLine(new {}(tokens).Parse());",
+        PARSER_CLASS_NAME
+    ));
+    w.close("");
+    w.line("catch (ParseException e)");
+    w.open();
+    w.line("Console.Error.WriteLine(e.Message);");
+    w.line("Environment.Exit(1);");
+    w.close("");
+    w.close("");
+    w.close("");
+    w.close("");
+    w.out
+}
+
+fn generate_parser(project_name: &str, table: &LL1Table) -> String {
+    let cfg = &table.cfg;
+    let names = Names::new(cfg);
+    let mut w = Writer {
+        out: String::new(),
+        indent: 0,
+    };
+    w.line("using System;");
+    w.line("using System.Collections.Generic;");
+    w.line("");
+    w.line(&format!("namespace {}", project_name));
+    w.open();
+
+    w.line("public enum TokenKind");
+    w.open();
+    w.line(&format!("{},", EOF_KIND));
+    for kind in &names.kinds {
+        w.line(&format!("{},", kind));
+    }
+    w.close("");
+    w.line("");
+
+    w.line("public class Token");
+    w.open();
+    w.line("public readonly TokenKind Kind;");
+    w.line("public readonly string Text;");
+    w.line("public readonly int Line, Column;");
+    w.line("public Token(TokenKind kind, string text, int line, int column)");
+    w.open();
+    w.line("Kind = kind;");
+    w.line("Text = text;");
+    w.line("Line = line;");
+    w.line("Column = column;");
+    w.close("");
+    w.line("public override string ToString() => Text;");
+    w.close("");
+    w.line("");
+
+    w.line("public class Node");
+    w.open();
+    w.line("public readonly string Name;");
+    w.line("//Token or Node for each symbol on the right hand side, <empty> has none");
+    w.line("public readonly List<object> Children = new List<object>();");
+    w.line("public Node(string name)");
+    w.open();
+    w.line("Name = name;");
+    w.close("");
+    w.line("public override string ToString() => Children.Count == 0 ? \"(\" + Name + \")\" : \"(\" + Name + \" \" + string.Join(\" \", Children) + \")\";");
+    w.close("");
+    w.line("");
+
+    w.line("public class ParseException : Exception");
+    w.open();
+    w.line("public readonly Token Found;");
+    w.line("public ParseException(string message, Token found) : base(message)");
+    w.open();
+    w.line("Found = found;");
+    w.close("");
+    w.close("");
+    w.line("");
+
+    w.line(&format!("public class {}", PARSER_CLASS_NAME));
+    w.open();
+    w.line(
+        "static readonly Dictionary<string, TokenKind> Kinds = new Dictionary<string, TokenKind>",
+    );
+    w.open();
+    for (name, kind) in cfg.terminal_symbols.iter().zip(&names.kinds) {
+        w.line(&format!("{{ {}, TokenKind.{} }},", quote(name), kind));
+        //the . is optional in input, like parsing::parse_tree::tokenize
+        if let Some(bare) = name.strip_prefix('.') {
+            if cfg.terminal_symbols.get(bare).is_none() {
+                w.line(&format!("{{ {}, TokenKind.{} }},", quote(bare), kind));
+            }
+        }
+    }
+    w.close(";");
+    w.line("");
+    w.line("//indexed by TokenKind, for error messages");
+    let mut descriptions = vec![quote(&cfg.describe(&Symbol::EOF()))];
+    descriptions.extend(
+        (0..cfg.terminal_symbols.len()).map(|x| quote(&cfg.describe(&Symbol::Terminal(x)))),
+    );
+    w.line(&format!(
+        "static readonly string[] Names = {{ {} }};",
+        descriptions.join(", ")
+    ));
+    w.line("");
+    w.line("public static TokenKind KindOf(string text)");
+    w.open();
+    w.line("if (Kinds.TryGetValue(text, out var kind))");
+    w.line("\treturn kind;");
+    w.line("throw new ParseException(\"unknown token \" + text, null);");
+    w.close("");
+    w.line("");
+    w.line("readonly IList<Token> tokens;");
+    w.line("int pos;");
+    w.line("");
+    w.line(&format!(
+        "public {}(IList<Token> tokens)",
+        PARSER_CLASS_NAME
+    ));
+    w.open();
+    w.line("this.tokens = tokens;");
+    w.close("");
+    w.line("");
+    w.line("Token Peek()");
+    w.open();
+    w.line("if (pos < tokens.Count)");
+    w.line("\treturn tokens[pos];");
+    w.line("var last = tokens.Count == 0 ? null : tokens[tokens.Count - 1];");
+    w.line(&format!(
+        "return last == null ? new Token(TokenKind.{0}, \"\", 1, 1) : new Token(TokenKind.{0}, \"\", last.Line, last.Column + last.Text.Length);",
+        EOF_KIND
+    ));
+    w.close("");
+    w.line("");
+    w.line("Token Expect(TokenKind kind)");
+    w.open();
+    w.line("var token = Peek();");
+    w.line("if (token.Kind != kind)");
+    w.line("\tthrow Error(kind);");
+    w.line("pos++;");
+    w.line("return token;");
+    w.close("");
+    w.line("");
+    w.line("ParseException Error(params TokenKind[] expected)");
+    w.open();
+    w.line("var token = Peek();");
+    w.line("var names = Array.ConvertAll(expected, kind => Names[(int)kind]);");
+    w.line("//a, b or c");
+    w.line("var list = names.Length == 1 ? names[0] : string.Join(\", \", names, 0, names.Length - 1) + \" or \" + names[names.Length - 1];");
+    w.line("return new ParseException(\"expected \" + list + \", found \" + Names[(int)token.Kind] + \" at \" + token.Line + \":\" + token.Column, token);");
+    w.close("");
+
+    for (nonterminal, row) in table.rows.iter().enumerate() {
+        w.line("");
+        for production in &cfg.productions[nonterminal] {
+            w.line(&format!("//{}", cfg.production_str(production)));
+        }
+        w.line(&format!("public Node {}()", names.methods[nonterminal]));
+        w.open();
+        w.line(&format!(
+            "var node = new Node({});",
+            quote(&cfg.nonterminal_symbols[nonterminal])
+        ));
+        w.line("switch (Peek().Kind)");
+        w.open();
+        let mut expected: Vec<Symbol> = row.keys().copied().collect();
+        expected.sort();
+        for production in &cfg.productions[nonterminal] {
+            let id = cfg.production_id(production);
+            let mut lookaheads: Vec<Symbol> = row
+                .iter()
+                .filter(|(_, p)| **p == id)
+                .map(|(s, _)| *s)
+                .collect();
+            if lookaheads.is_empty() {
+                continue;
+            }
+            lookaheads.sort();
+            w.line(&format!("//{}", cfg.production_str(production)));
+            for lookahead in lookaheads {
+                w.line(&format!("case {}:", names.kind(&lookahead)));
+            }
+            w.indent += 1;
+            for symbol in &production.rhs {
+                match symbol {
+                    Symbol::Nonterminal(x) => {
+                        w.line(&format!("node.Children.Add({}());", names.methods[*x]))
+                    }
+                    Symbol::Empty() => {}
+                    _ => w.line(&format!(
+                        "node.Children.Add(Expect({}));",
+                        names.kind(symbol)
+                    )),
+                }
+            }
+            w.line("return node;");
+            w.indent -= 1;
+        }
+        w.line("default:");
+        let expected: Vec<String> = expected.iter().map(|x| names.kind(x)).collect();
+        w.line(&format!("\tthrow Error({});", expected.join(", ")));
+        w.close("");
+        w.close("");
+    }
+    w.close("");
+    w.close("");
+    w.out
+}
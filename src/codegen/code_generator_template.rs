@@ -0,0 +1,358 @@
+//renders user supplied templates against the parse table, so new target languages don't need rust code
+//see template.rs for the template syntax and model() below for the variables available to templates
+
+use super::code_generator::{
+    CodeGenerator, CodeGeneratorData, CodegenError, GeneratedFiles, COMPRESSION,
+};
+use super::file_system::FileSystem;
+use super::template::{Template, TemplateError, Value};
+use crate::parsing::grammar::{Symbol, CFG};
+use crate::parsing::parse_table::TableCell;
+use crate::parsing::table_compression::compress;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+//templates in a directory need this extension, it's removed from the output file name
+pub const TEMPLATE_EXTENSION: &str = "tmpl";
+
+struct OutputTemplate {
+    //where the template was loaded from, for error messages
+    source: PathBuf,
+    //the output path is a template too, so it can use {{project_name}}
+    path: Template,
+    body: Template,
+}
+
+#[derive(Default)]
+pub struct CodeGeneratorTemplate {
+    templates: Vec<OutputTemplate>,
+}
+
+impl CodeGeneratorTemplate {
+    pub fn new() -> CodeGeneratorTemplate {
+        CodeGeneratorTemplate {
+            templates: Vec::new(),
+        }
+    }
+
+    //path is relative to the output root and may contain template tags
+    pub fn add(&mut self, path: &str, source: &str) -> Result<(), CodegenError> {
+        let error = |e: TemplateError| CodegenError::Template(PathBuf::from(path), e);
+        self.templates.push(OutputTemplate {
+            source: PathBuf::from(path),
+            path: Template::parse(path).map_err(error)?,
+            body: Template::parse(source).map_err(error)?,
+        });
+        Ok(())
+    }
+
+    //loads every *.tmpl file under dir, keeping the directory layout for the output
+    pub fn from_directory(dir: &Path) -> Result<CodeGeneratorTemplate, CodegenError> {
+        let mut generator = CodeGeneratorTemplate::new();
+        let mut files = Vec::new();
+        find_templates(dir, Path::new(""), &mut files)?;
+        files.sort();
+        for relative in files {
+            let full = dir.join(&relative);
+            let source =
+                std::fs::read_to_string(&full).map_err(|e| CodegenError::Io(full.clone(), e))?;
+            let output = relative.with_extension("");
+            let error = |e: TemplateError| CodegenError::Template(full.clone(), e);
+            generator.templates.push(OutputTemplate {
+                path: Template::parse(&output.to_string_lossy()).map_err(error)?,
+                body: Template::parse(&source).map_err(error)?,
+                source: full.clone(),
+            });
+        }
+        Ok(generator)
+    }
+}
+
+fn find_templates(
+    root: &Path,
+    relative: &Path,
+    dest: &mut Vec<PathBuf>,
+) -> Result<(), CodegenError> {
+    let dir = root.join(relative);
+    let entries = std::fs::read_dir(&dir).map_err(|e| CodegenError::Io(dir.clone(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| CodegenError::Io(dir.clone(), e))?;
+        let path = relative.join(entry.file_name());
+        if entry.path().is_dir() {
+            find_templates(root, &path, dest)?;
+        } else if path.extension().map(|x| x == TEMPLATE_EXTENSION) == Some(true) {
+            dest.push(path);
+        }
+    }
+    Ok(())
+}
+
+impl CodeGenerator for CodeGeneratorTemplate {
+    fn generate(
+        &mut self,
+        data: &CodeGeneratorData,
+        fs: &mut dyn FileSystem,
+    ) -> Result<GeneratedFiles, CodegenError> {
+        let context = model(data);
+        let mut files = GeneratedFiles::new();
+        for template in &self.templates {
+            let error = |e: TemplateError| CodegenError::Template(template.source.clone(), e);
+            let path = template.path.render(&context).map_err(error)?;
+            let body = template.body.render(&context).map_err(error)?;
+            files.write(fs, Path::new(&path), body.as_bytes())?;
+        }
+        Ok(files)
+    }
+}
+
+//builds the variables visible to templates:
+//  project_name, state_count, terminal_count, nonterminal_count, production_count
+//  terminals, nonterminals: index, name, quoted, ident, description
+//  productions: index, lhs, lhs_index, lhs_ident, length, rhs (list of symbols, <empty> left out,
+//               so length is the number of states a reduction pops)
+//  states: index, cells: symbol_name, symbol_quoted, symbol_ident, symbol_kind, symbol_index,
+//                        symbol_description, action, target, is_shift, is_reduce, is_goto, is_accept
+//          expected (list of symbols), expected_message (`.+`, `.;` or end of input),
+//          message, has_message (hand written error message, see parsing::error_messages)
+//  compressed: default_reductions, action_row, action_base, action_next, action_check,
+//              goto_row, goto_base, goto_next, goto_check (lists of ints, see table_compression)
+//symbol_kind is terminal, nonterminal or eof. eof is given the index after the last terminal
+//description is the name used in error messages, see CFG::describe
+//ident is unique among the terminals and <eof>, and among the nonterminals, see unique
+pub fn model(data: &CodeGeneratorData) -> Value {
+    let cfg = &data.table.cfg;
+    let idents = Idents::new(cfg);
+    let terminals = cfg
+        .terminal_symbols
+        .iter()
+        .enumerate()
+        .map(|(index, _)| symbol_value(cfg, &idents, &Symbol::Terminal(index)))
+        .collect();
+    let nonterminals = cfg
+        .nonterminal_symbols
+        .iter()
+        .enumerate()
+        .map(|(index, _)| symbol_value(cfg, &idents, &Symbol::Nonterminal(index)))
+        .collect();
+    let mut productions = Vec::new();
+    for production in cfg.productions.iter().flatten() {
+        let lhs = &cfg.nonterminal_symbols[production.nonterminal];
+        productions.push(
+            Value::object()
+                .with("index", Value::Int(productions.len()))
+                .with("lhs", Value::Str(String::from(lhs)))
+                .with("lhs_index", Value::Int(production.nonterminal))
+                .with(
+                    "lhs_ident",
+                    Value::Str(idents.nonterminals[production.nonterminal].clone()),
+                )
+                .with("length", Value::Int(production.rhs_len()))
+                .with(
+                    "rhs",
+                    Value::List(
+                        production
+                            .rhs
+                            .iter()
+                            .filter(|s| **s != Symbol::Empty())
+                            .map(|s| symbol_value(cfg, &idents, s))
+                            .collect(),
+                    ),
+                ),
+        );
+    }
+    let mut states = Vec::new();
+    for (index, row) in data.table.rows.iter().enumerate() {
+        let mut symbols: Vec<&Symbol> = row.cells.keys().collect();
+        symbols.sort();
+        let cells = symbols
+            .into_iter()
+            .map(|symbol| {
+                let (action, target) = match row.cells[symbol] {
+                    TableCell::Shift(x) => ("shift", x),
+                    TableCell::Reduce(x) => ("reduce", x),
+                    TableCell::Goto(x) => ("goto", x),
+                    TableCell::Accept() => ("accept", 0),
+                };
+                with_symbol(Value::object(), "symbol_", cfg, &idents, symbol)
+                    .with("action", Value::Str(String::from(action)))
+                    .with("target", Value::Int(target))
+                    .with("is_shift", Value::Bool(action == "shift"))
+                    .with("is_reduce", Value::Bool(action == "reduce"))
+                    .with("is_goto", Value::Bool(action == "goto"))
+                    .with("is_accept", Value::Bool(action == "accept"))
+            })
+            .collect();
+        let expected = data.table.expected(index);
+        states.push(
+            Value::object()
+                .with("index", Value::Int(index))
+                .with("cells", Value::List(cells))
+                .with(
+                    "expected",
+                    Value::List(
+                        expected
+                            .iter()
+                            .map(|x| symbol_value(cfg, &idents, x))
+                            .collect(),
+                    ),
+                )
+                .with("expected_message", Value::Str(cfg.describe_list(&expected)))
+                .with(
+                    "message",
+                    Value::Str(data.messages.get(&index).cloned().unwrap_or_default()),
+                )
+                .with(
+                    "has_message",
+                    Value::Bool(data.messages.contains_key(&index)),
+                ),
+        );
+    }
+    Value::object()
+        .with("project_name", Value::Str(data.project_name.clone()))
+        .with("state_count", Value::Int(states.len()))
+        .with("terminal_count", Value::Int(cfg.terminal_symbols.len()))
+        .with(
+            "nonterminal_count",
+            Value::Int(cfg.nonterminal_symbols.len()),
+        )
+        .with("production_count", Value::Int(productions.len()))
+        .with("terminals", Value::List(terminals))
+        .with("nonterminals", Value::List(nonterminals))
+        .with("productions", Value::List(productions))
+        .with("states", Value::List(states))
+        .with("compressed", compressed_model(data))
+}
+
+fn compressed_model(data: &CodeGeneratorData) -> Value {
+    let compressed = compress(&data.table, &COMPRESSION);
+    let list = |values: &[usize]| Value::List(values.iter().map(|x| Value::Int(*x)).collect());
+    Value::object()
+        .with(
+            "default_reductions",
+            list(&compressed.encoded_default_reductions()),
+        )
+        .with("action_row", list(&compressed.actions.row))
+        .with("action_base", list(&compressed.actions.base))
+        .with("action_next", list(&compressed.actions.encoded_next()))
+        .with("action_check", list(&compressed.actions.encoded_check()))
+        .with("goto_row", list(&compressed.gotos.row))
+        .with("goto_base", list(&compressed.gotos.base))
+        .with("goto_next", list(&compressed.gotos.encoded_next()))
+        .with("goto_check", list(&compressed.gotos.encoded_check()))
+}
+
+fn symbol_value(cfg: &CFG, idents: &Idents, symbol: &Symbol) -> Value {
+    with_symbol(Value::object(), "", cfg, idents, symbol)
+}
+
+//adds name, quoted, ident, kind and index fields for the symbol, each key starting with prefix
+fn with_symbol(value: Value, prefix: &str, cfg: &CFG, idents: &Idents, symbol: &Symbol) -> Value {
+    let name = cfg.symbol_str(symbol);
+    let (kind, index) = match symbol {
+        Symbol::Terminal(x) => ("terminal", *x),
+        Symbol::Nonterminal(x) => ("nonterminal", *x),
+        Symbol::EOF() => ("eof", cfg.terminal_symbols.len()),
+        Symbol::Empty() => ("empty", 0),
+    };
+    value
+        .with(&format!("{}name", prefix), Value::Str(String::from(name)))
+        .with(&format!("{}quoted", prefix), Value::Str(quote(name)))
+        .with(
+            &format!("{}ident", prefix),
+            Value::Str(idents.get(cfg, symbol)),
+        )
+        .with(&format!("{}kind", prefix), Value::Str(String::from(kind)))
+        .with(&format!("{}index", prefix), Value::Int(index))
+        .with(
+            &format!("{}description", prefix),
+            Value::Str(cfg.describe(symbol)),
+        )
+}
+
+//different symbols can have the same identifier, .a' and .a_27 are both a_27
+//the later ones get a number: a_27, a_27_2
+pub fn unique<I: Iterator<Item = String>>(names: I, taken: &[&str]) -> Vec<String> {
+    let mut used: HashSet<String> = taken.iter().map(|x| String::from(*x)).collect();
+    names
+        .map(|name| {
+            let mut unique = name.clone();
+            let mut n = 2;
+            while !used.insert(unique.clone()) {
+                unique = format!("{}_{}", name, n);
+                n += 1;
+            }
+            unique
+        })
+        .collect()
+}
+
+//idents for the model, numbered with unique so two terminals or two nonterminals never share one
+//<eof> is numbered with the terminals since templates tend to put them in the same enum
+struct Idents {
+    terminals: Vec<String>,
+    nonterminals: Vec<String>,
+    eof: String,
+}
+
+impl Idents {
+    fn new(cfg: &CFG) -> Idents {
+        let eof = ident(cfg.symbol_str(&Symbol::EOF()));
+        let mut terminals = unique(
+            cfg.terminal_symbols
+                .iter()
+                .map(ident)
+                .chain(std::iter::once(eof)),
+            &[],
+        );
+        let eof = terminals.pop().unwrap();
+        Idents {
+            terminals,
+            nonterminals: unique(cfg.nonterminal_symbols.iter().map(ident), &[]),
+            eof,
+        }
+    }
+
+    fn get(&self, cfg: &CFG, symbol: &Symbol) -> String {
+        match symbol {
+            Symbol::Terminal(x) => self.terminals[*x].clone(),
+            Symbol::Nonterminal(x) => self.nonterminals[*x].clone(),
+            Symbol::EOF() => self.eof.clone(),
+            Symbol::Empty() => ident(cfg.symbol_str(symbol)),
+        }
+    }
+}
+
+//double quoted string literal, valid in most c-like languages
+pub fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+//identifier safe version of a symbol name: the terminal dot is dropped, other characters are hex escaped
+//example: .id -> id, .+ -> _2B, S' -> S_27
+pub fn ident(s: &str) -> String {
+    let s = s.strip_prefix('.').unwrap_or(s);
+    let mut out = String::new();
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            out.push(c);
+        } else {
+            out.push_str(&format!("_{:X}", c as u32));
+        }
+    }
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
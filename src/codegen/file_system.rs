@@ -0,0 +1,210 @@
+//sinks that code generators write through, so output can go to disk, memory, or an archive
+
+use super::code_generator::CodegenError;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+pub trait FileSystem {
+    //path is relative to the root of the file system, missing parent directories are created
+    fn write_file(&mut self, path: &Path, contents: &[u8]) -> Result<(), CodegenError>;
+}
+
+//only plain relative paths are allowed so a generator can't escape its output directory
+fn check_path(path: &Path) -> Result<(), CodegenError> {
+    if path.as_os_str().is_empty() {
+        return Err(CodegenError::InvalidPath(path.to_path_buf()));
+    }
+    for component in path.components() {
+        match component {
+            Component::Normal(_) | Component::CurDir => (),
+            _ => return Err(CodegenError::InvalidPath(path.to_path_buf())),
+        }
+    }
+    Ok(())
+}
+
+//writes into a real directory
+pub struct DirectoryFileSystem {
+    pub root: PathBuf,
+}
+
+impl DirectoryFileSystem {
+    pub fn new(root: &Path) -> DirectoryFileSystem {
+        DirectoryFileSystem {
+            root: root.to_path_buf(),
+        }
+    }
+}
+
+impl FileSystem for DirectoryFileSystem {
+    fn write_file(&mut self, path: &Path, contents: &[u8]) -> Result<(), CodegenError> {
+        check_path(path)?;
+        let full = self.root.join(path);
+        if let Some(parent) = full.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| CodegenError::Io(full.clone(), e))?;
+        }
+        std::fs::write(&full, contents).map_err(|e| CodegenError::Io(full.clone(), e))
+    }
+}
+
+//keeps every file in a map, used for tests and for callers that want the output as strings
+#[derive(Default)]
+pub struct MemoryFileSystem {
+    pub files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> MemoryFileSystem {
+        MemoryFileSystem {
+            files: BTreeMap::new(),
+        }
+    }
+    pub fn get_str(&self, path: &str) -> Option<&str> {
+        self.files
+            .get(Path::new(path))
+            .and_then(|x| std::str::from_utf8(x).ok())
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn write_file(&mut self, path: &Path, contents: &[u8]) -> Result<(), CodegenError> {
+        check_path(path)?;
+        self.files.insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+}
+
+//accepts and discards everything, the generator's GeneratedFiles says what would have been written
+#[derive(Default)]
+pub struct DryRunFileSystem {}
+
+impl FileSystem for DryRunFileSystem {
+    fn write_file(&mut self, path: &Path, _contents: &[u8]) -> Result<(), CodegenError> {
+        check_path(path)
+    }
+}
+
+//writes an uncompressed (stored) zip archive into dest
+//call finish once all files are written to add the central directory
+pub struct ZipFileSystem<W: Write> {
+    dest: W,
+    offset: u32,
+    //name, crc, size, offset of local header
+    entries: Vec<(String, u32, u32, u32)>,
+}
+
+impl<W: Write> ZipFileSystem<W> {
+    pub fn new(dest: W) -> ZipFileSystem<W> {
+        ZipFileSystem {
+            dest,
+            offset: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    fn emit(&mut self, bytes: &[u8], path: &Path) -> Result<(), CodegenError> {
+        self.dest
+            .write_all(bytes)
+            .map_err(|e| CodegenError::Io(path.to_path_buf(), e))?;
+        self.offset += bytes.len() as u32;
+        Ok(())
+    }
+
+    //writes the central directory and returns the underlying writer
+    pub fn finish(mut self) -> Result<W, CodegenError> {
+        let start = self.offset;
+        let entries = std::mem::take(&mut self.entries);
+        let archive = Path::new("<zip>");
+        for (name, crc, size, offset) in &entries {
+            let mut header = Vec::with_capacity(46 + name.len());
+            push_u32(&mut header, 0x0201_4b50);
+            push_u16(&mut header, 20); //version made by
+            push_u16(&mut header, 20); //version needed
+            push_u16(&mut header, 0x0800); //utf-8 names
+            push_u16(&mut header, 0); //stored
+            push_u16(&mut header, 0); //mod time
+            push_u16(&mut header, 0x21); //mod date, 1980-01-01
+            push_u32(&mut header, *crc);
+            push_u32(&mut header, *size);
+            push_u32(&mut header, *size);
+            push_u16(&mut header, name.len() as u16);
+            push_u16(&mut header, 0); //extra length
+            push_u16(&mut header, 0); //comment length
+            push_u16(&mut header, 0); //disk number
+            push_u16(&mut header, 0); //internal attributes
+            push_u32(&mut header, 0); //external attributes
+            push_u32(&mut header, *offset);
+            header.extend_from_slice(name.as_bytes());
+            self.emit(&header, archive)?;
+        }
+        let size = self.offset - start;
+        let mut end = Vec::with_capacity(22);
+        push_u32(&mut end, 0x0605_4b50);
+        push_u16(&mut end, 0);
+        push_u16(&mut end, 0);
+        push_u16(&mut end, entries.len() as u16);
+        push_u16(&mut end, entries.len() as u16);
+        push_u32(&mut end, size);
+        push_u32(&mut end, start);
+        push_u16(&mut end, 0);
+        self.emit(&end, archive)?;
+        Ok(self.dest)
+    }
+}
+
+impl<W: Write> FileSystem for ZipFileSystem<W> {
+    fn write_file(&mut self, path: &Path, contents: &[u8]) -> Result<(), CodegenError> {
+        check_path(path)?;
+        //zip always uses forward slashes
+        let name = path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(s) => Some(s.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect::<Vec<String>>()
+            .join("/");
+        let crc = crc32(contents);
+        let size = contents.len() as u32;
+        let mut header = Vec::with_capacity(30 + name.len());
+        push_u32(&mut header, 0x0403_4b50);
+        push_u16(&mut header, 20); //version needed
+        push_u16(&mut header, 0x0800); //utf-8 names
+        push_u16(&mut header, 0); //stored
+        push_u16(&mut header, 0); //mod time
+        push_u16(&mut header, 0x21); //mod date, 1980-01-01
+        push_u32(&mut header, crc);
+        push_u32(&mut header, size);
+        push_u32(&mut header, size);
+        push_u16(&mut header, name.len() as u16);
+        push_u16(&mut header, 0); //extra length
+        header.extend_from_slice(name.as_bytes());
+        let offset = self.offset;
+        self.emit(&header, path)?;
+        self.emit(contents, path)?;
+        self.entries.push((name, crc, size, offset));
+        Ok(())
+    }
+}
+
+fn push_u16(buf: &mut Vec<u8>, x: u16) {
+    buf.extend_from_slice(&x.to_le_bytes());
+}
+
+fn push_u32(buf: &mut Vec<u8>, x: u32) {
+    buf.extend_from_slice(&x.to_le_bytes());
+}
+
+//standard crc-32 (ieee 802.3), bitwise since the files we write are small
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
@@ -0,0 +1,105 @@
+//a single offline html page documenting a grammar: a railroad diagram and the productions for each
+//nonterminal, links between where nonterminals are used and defined, and a table of the terminals
+//terminals are listed with their aliases, the closest thing a grammar file has to a lexer definition
+
+use super::railroad::{diagram, escape};
+use crate::parsing::grammar::{Symbol, CFG};
+use std::collections::BTreeSet;
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: 2em auto; }
+code, svg text { font-family: monospace; font-size: 13px; }
+section { margin-bottom: 2em; }
+svg.railroad path { fill: none; stroke: #333; stroke-width: 1.5; }
+svg.railroad rect { stroke: #333; stroke-width: 1.5; }
+svg.railroad rect.terminal { fill: #e4f2e4; }
+svg.railroad rect.nonterminal { fill: #e4ecf7; }
+svg.railroad text { text-anchor: middle; }
+svg.railroad a:hover rect { fill: #c8daf2; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }";
+
+//id of a nonterminal's section, by index so any name works
+fn anchor(nonterminal: usize) -> String {
+    format!("nt-{}", nonterminal)
+}
+
+fn nonterminal_link(cfg: &CFG, nonterminal: usize) -> String {
+    format!(
+        "<a href=\"#{}\"><code>{}</code></a>",
+        anchor(nonterminal),
+        escape(&cfg.nonterminal_symbols[nonterminal])
+    )
+}
+
+//the nonterminals with a production using the symbol, sorted, leaving out S'
+fn used_by(cfg: &CFG, symbol: &Symbol) -> BTreeSet<usize> {
+    cfg.productions
+        .iter()
+        .flatten()
+        .filter(|x| x.nonterminal != 0 && x.rhs.contains(symbol))
+        .map(|x| x.nonterminal)
+        .collect()
+}
+
+fn links(cfg: &CFG, nonterminals: &BTreeSet<usize>) -> String {
+    let links: Vec<String> = nonterminals
+        .iter()
+        .map(|x| nonterminal_link(cfg, *x))
+        .collect();
+    links.join(", ")
+}
+
+pub fn render(cfg: &CFG, title: &str) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(title),
+        STYLE,
+        escape(title)
+    );
+    //S' -> S <eof>, the grammar's own start symbol is S
+    if let Some(Symbol::Nonterminal(start)) = cfg.productions[0].first().map(|x| x.rhs[0]) {
+        out.push_str(&format!(
+            "<p>Start symbol: {}</p>\n",
+            nonterminal_link(cfg, start)
+        ));
+    }
+    out.push_str("<h2>Nonterminals</h2>\n");
+    for nonterminal in 1..cfg.nonterminal_symbols.len() {
+        out.push_str(&format!(
+            "<section id=\"{}\">\n<h3><code>{}</code></h3>\n",
+            anchor(nonterminal),
+            escape(&cfg.nonterminal_symbols[nonterminal])
+        ));
+        if cfg.productions[nonterminal].is_empty() {
+            out.push_str("<p>No productions.</p>\n");
+        } else {
+            out.push_str(&diagram(cfg, nonterminal, &|x| format!("#{}", anchor(x))));
+            out.push_str("\n<ul>\n");
+            for production in &cfg.productions[nonterminal] {
+                let rhs: Vec<&str> = production.rhs.iter().map(|x| cfg.symbol_str(x)).collect();
+                out.push_str(&format!(
+                    "<li><code>{} -&gt; {}</code></li>\n",
+                    escape(&cfg.nonterminal_symbols[nonterminal]),
+                    escape(&rhs.join(" "))
+                ));
+            }
+            out.push_str("</ul>\n");
+        }
+        let users = used_by(cfg, &Symbol::Nonterminal(nonterminal));
+        if !users.is_empty() {
+            out.push_str(&format!("<p>Used by: {}</p>\n", links(cfg, &users)));
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("<h2>Terminals</h2>\n<table>\n<tr><th>Terminal</th><th>Description</th><th>Used by</th></tr>\n");
+    for terminal in 0..cfg.terminal_symbols.len() {
+        out.push_str(&format!(
+            "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+            escape(&cfg.terminal_symbols[terminal]),
+            escape(cfg.aliases.get(&terminal).map(|x| x.as_str()).unwrap_or("")),
+            links(cfg, &used_by(cfg, &Symbol::Terminal(terminal)))
+        ));
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
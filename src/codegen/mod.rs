@@ -1,2 +1,8 @@
 pub mod code_generator;
 pub mod code_generator_csharp;
+pub mod code_generator_recursive_descent;
+pub mod code_generator_template;
+pub mod file_system;
+pub mod grammar_docs;
+pub mod railroad;
+pub mod template;
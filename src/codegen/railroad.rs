@@ -0,0 +1,145 @@
+//railroad diagrams as inline svg, one track per production of a nonterminal
+//
+//  |--+-- .id ---- .:= ---- E --+--|
+//     |                         |
+//     +---------- E ------------+
+//
+//sizes are worked out from the character count, the page uses a monospace font so they fit
+
+use crate::parsing::grammar::{Symbol, CFG};
+
+const BOX_HEIGHT: usize = 24;
+const CHAR_WIDTH: usize = 8;
+//space between the text and the sides of its box
+const PADDING: usize = 10;
+//line between two boxes on a track
+const GAP: usize = 20;
+//space between tracks
+const ROW_GAP: usize = 12;
+//where the tracks start, after the start bar and the left rail
+const LEFT: usize = 40;
+const MARGIN: usize = 10;
+
+struct Node<'a> {
+    text: &'a str,
+    //target of the link for a nonterminal, None for a terminal
+    link: Option<String>,
+    width: usize,
+}
+
+//svg for one nonterminal, link gives the href for each nonterminal it uses
+pub fn diagram(cfg: &CFG, nonterminal: usize, link: &dyn Fn(usize) -> String) -> String {
+    let rows: Vec<Vec<Node>> = cfg.productions[nonterminal]
+        .iter()
+        .map(|production| {
+            production
+                .rhs
+                .iter()
+                .filter(|x| **x != Symbol::Empty())
+                .map(|symbol| {
+                    let text = cfg.symbol_str(symbol);
+                    Node {
+                        text,
+                        link: match symbol {
+                            Symbol::Nonterminal(x) => Some(link(*x)),
+                            _ => None,
+                        },
+                        width: text.chars().count() * CHAR_WIDTH + 2 * PADDING,
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let row_width = |row: &Vec<Node>| {
+        row.iter().map(|x| x.width).sum::<usize>() + GAP * row.len().saturating_sub(1)
+    };
+    let inner = rows.iter().map(row_width).max().unwrap_or(0);
+    let right = LEFT + inner;
+    let width = right + LEFT;
+    let axis = |row: usize| MARGIN + row * (BOX_HEIGHT + ROW_GAP) + BOX_HEIGHT / 2;
+    let height = MARGIN * 2 + rows.len().max(1) * (BOX_HEIGHT + ROW_GAP) - ROW_GAP;
+    let top = axis(0);
+    let mut out = format!(
+        "<svg class=\"railroad\" xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+    //start and end bars, and the lines to the first track
+    out.push_str(&format!(
+        "<path d=\"M {} {} v {} M {} {} v {}\"/>\n",
+        MARGIN,
+        top - 6,
+        12,
+        width - MARGIN,
+        top - 6,
+        12
+    ));
+    out.push_str(&format!(
+        "<path d=\"M {} {} H {} M {} {} H {}\"/>\n",
+        MARGIN,
+        top,
+        LEFT,
+        right,
+        top,
+        width - MARGIN
+    ));
+    for (index, row) in rows.iter().enumerate() {
+        let y = axis(index);
+        if index > 0 {
+            //down the left rail to the track and back up the right one
+            out.push_str(&format!(
+                "<path d=\"M {} {} a 10 10 0 0 1 10 10 V {} a 10 10 0 0 0 10 10 M {} {} a 10 10 0 0 0 10 -10 V {} a 10 10 0 0 1 10 -10\"/>\n",
+                LEFT - 20,
+                top,
+                y - 10,
+                right,
+                y,
+                top + 10,
+            ));
+        }
+        let mut x = LEFT;
+        for (position, node) in row.iter().enumerate() {
+            if position > 0 {
+                out.push_str(&format!("<path d=\"M {} {} h {}\"/>\n", x, y, GAP));
+                x += GAP;
+            }
+            out.push_str(&node_svg(node, x, y));
+            x += node.width;
+        }
+        if x < right {
+            out.push_str(&format!("<path d=\"M {} {} H {}\"/>\n", x, y, right));
+        }
+    }
+    out.push_str("</svg>");
+    out
+}
+
+fn node_svg(node: &Node, x: usize, axis: usize) -> String {
+    let (class, radius) = match node.link {
+        Some(_) => ("nonterminal", 0),
+        None => ("terminal", BOX_HEIGHT / 2),
+    };
+    let shape = format!(
+        "<rect class=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"/><text x=\"{}\" y=\"{}\">{}</text>",
+        class,
+        x,
+        axis - BOX_HEIGHT / 2,
+        node.width,
+        BOX_HEIGHT,
+        radius,
+        x + node.width / 2,
+        axis + 4,
+        escape(node.text)
+    );
+    match &node.link {
+        Some(link) => format!("<a href=\"{}\">{}</a>\n", escape(link), shape),
+        None => format!("{}\n", shape),
+    }
+}
+
+//for text and attribute values in html and svg
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,394 @@
+//a small mustache-like template language used by the template code generator
+//  {{name}}                         prints a value, dotted paths index into objects: {{production.lhs}}
+//  {{#each list}} ... {{/each}}     repeats the body for every item, fields of the item come into scope
+//                                   along with @index, @first and @last
+//  {{#if name}} ... {{else}} ... {{/if}}
+//  {{! comment }}
+//block tags that are the only thing on their line don't leave a blank line behind
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Clone)]
+pub enum Value {
+    Str(String),
+    Int(usize),
+    Bool(bool),
+    List(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    pub fn object() -> Value {
+        Value::Object(BTreeMap::new())
+    }
+    //builder style insert, does nothing if self is not an object
+    pub fn with(mut self, key: &str, value: Value) -> Value {
+        if let Value::Object(map) = &mut self {
+            map.insert(String::from(key), value);
+        }
+        self
+    }
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Str(s) => !s.is_empty(),
+            Value::Int(x) => *x != 0,
+            Value::Bool(b) => *b,
+            Value::List(l) => !l.is_empty(),
+            Value::Object(_) => true,
+        }
+    }
+}
+
+pub struct TemplateError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl fmt::Debug for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+enum Node {
+    Text(String),
+    //path, line
+    Var(String, usize),
+    //path, line, body
+    Each(String, usize, Vec<Node>),
+    //path, line, then, else
+    If(String, usize, Vec<Node>, Vec<Node>),
+}
+
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+enum Token {
+    Text(String),
+    //tag contents, line
+    Tag(String, usize),
+}
+
+fn is_block_tag(tag: &str) -> bool {
+    tag.starts_with('#') || tag.starts_with('/') || tag.starts_with('!') || tag == "else"
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut line = 1;
+    while let Some(start) = rest.find("{{") {
+        let text = &rest[..start];
+        line += text.matches('\n').count();
+        let after = &rest[start + 2..];
+        let end = match after.find("}}") {
+            Some(x) => x,
+            None => {
+                return Err(TemplateError {
+                    line,
+                    message: String::from("unclosed {{"),
+                })
+            }
+        };
+        let tag = after[..end].trim();
+        tokens.push(Token::Text(String::from(text)));
+        tokens.push(Token::Tag(String::from(tag), line));
+        line += after[..end].matches('\n').count();
+        rest = &after[end + 2..];
+    }
+    tokens.push(Token::Text(String::from(rest)));
+    strip_standalone(&mut tokens);
+    Ok(tokens)
+}
+
+//removes the indentation and newline around block tags that sit alone on a line
+fn strip_standalone(tokens: &mut [Token]) {
+    //tokens alternate text, tag, text, tag ... text
+    //find every standalone tag before changing anything, since neighbouring tags share text
+    let mut standalone = Vec::new();
+    let mut i = 1;
+    while i + 1 < tokens.len() {
+        if let (Token::Text(before), Token::Tag(tag, _), Token::Text(after)) =
+            (&tokens[i - 1], &tokens[i], &tokens[i + 1])
+        {
+            if is_block_tag(tag) {
+                let line_start = before.rfind('\n').map(|x| x + 1).unwrap_or(0);
+                //the first line of the file counts as a line start
+                let starts_line = (line_start > 0 || i == 1)
+                    && before[line_start..].chars().all(|c| c == ' ' || c == '\t');
+                let line_end = after.find('\n');
+                let ends_line = match line_end {
+                    Some(x) => after[..x]
+                        .chars()
+                        .all(|c| c == ' ' || c == '\t' || c == '\r'),
+                    None => i + 2 == tokens.len() && after.trim().is_empty(),
+                };
+                if starts_line && ends_line {
+                    standalone.push((
+                        i,
+                        line_start,
+                        line_end.map(|x| x + 1).unwrap_or(after.len()),
+                    ));
+                }
+            }
+        }
+        i += 2;
+    }
+    //trailing indentation is cut before leading newlines so the offsets stay valid
+    for (i, line_start, _) in &standalone {
+        if let Token::Text(before) = &mut tokens[i - 1] {
+            before.truncate(*line_start);
+        }
+    }
+    for (i, _, after_start) in &standalone {
+        if let Token::Text(after) = &mut tokens[i + 1] {
+            let end = (*after_start).min(after.len());
+            after.replace_range(..end, "");
+        }
+    }
+}
+
+//nodes and the closing tag that stopped parsing (if any) along with its line
+type Parsed = (Vec<Node>, Option<(String, usize)>);
+
+//parses until a closing tag
+fn parse_nodes(tokens: &mut std::vec::IntoIter<Token>) -> Result<Parsed, TemplateError> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(text))
+                }
+            }
+            Token::Tag(tag, line) => {
+                if tag.starts_with('!') {
+                    continue;
+                } else if tag.starts_with('/') || tag == "else" {
+                    return Ok((nodes, Some((tag, line))));
+                } else if let Some(block) = tag.strip_prefix('#') {
+                    let mut parts = block.split_whitespace();
+                    let kind = parts.next().unwrap_or("");
+                    let path = match (parts.next(), parts.next()) {
+                        (Some(p), None) => String::from(p),
+                        _ => {
+                            return Err(TemplateError {
+                                line,
+                                message: format!("expected one argument in {{{{{}}}}}", tag),
+                            })
+                        }
+                    };
+                    let (body, close) = parse_nodes(tokens)?;
+                    match kind {
+                        "each" => {
+                            expect_close(&close, "/each", line)?;
+                            nodes.push(Node::Each(path, line, body));
+                        }
+                        "if" => {
+                            let (else_body, close) = match &close {
+                                Some((c, _)) if c == "else" => parse_nodes(tokens)?,
+                                _ => (Vec::new(), close),
+                            };
+                            expect_close(&close, "/if", line)?;
+                            nodes.push(Node::If(path, line, body, else_body));
+                        }
+                        _ => {
+                            return Err(TemplateError {
+                                line,
+                                message: format!("unknown block #{}", kind),
+                            })
+                        }
+                    }
+                } else if tag.is_empty() {
+                    return Err(TemplateError {
+                        line,
+                        message: String::from("empty tag"),
+                    });
+                } else {
+                    nodes.push(Node::Var(tag, line));
+                }
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn expect_close(
+    close: &Option<(String, usize)>,
+    expected: &str,
+    open_line: usize,
+) -> Result<(), TemplateError> {
+    match close {
+        Some((tag, _)) if tag == expected => Ok(()),
+        Some((tag, line)) => Err(TemplateError {
+            line: *line,
+            message: format!("expected {{{{{}}}}}, found {{{{{}}}}}", expected, tag),
+        }),
+        None => Err(TemplateError {
+            line: open_line,
+            message: format!("block is never closed with {{{{{}}}}}", expected),
+        }),
+    }
+}
+
+//one level of scope while rendering, loops also know where they are in the list
+struct Scope<'a> {
+    value: &'a Value,
+    index: usize,
+    len: usize,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        let mut tokens = tokenize(source)?.into_iter();
+        let (nodes, close) = parse_nodes(&mut tokens)?;
+        match close {
+            None => Ok(Template { nodes }),
+            Some((tag, line)) => Err(TemplateError {
+                line,
+                message: format!("unexpected {{{{{}}}}}", tag),
+            }),
+        }
+    }
+
+    pub fn render(&self, context: &Value) -> Result<String, TemplateError> {
+        let mut out = String::new();
+        let mut scopes = vec![Scope {
+            value: context,
+            index: 0,
+            len: 1,
+        }];
+        render_nodes(&self.nodes, &mut scopes, &mut out)?;
+        Ok(out)
+    }
+}
+
+//the value itself, or for @index, @first and @last one made on the spot
+fn lookup<'a>(
+    path: &str,
+    scopes: &[Scope<'a>],
+    line: usize,
+) -> Result<Cow<'a, Value>, TemplateError> {
+    let not_found = || TemplateError {
+        line,
+        message: format!("unknown variable {}", path),
+    };
+    let innermost = scopes.last().ok_or_else(not_found)?;
+    let mut segments = path.split('.');
+    let first = segments.next().unwrap_or("");
+    let mut value = match first {
+        "@index" => return Ok(Cow::Owned(Value::Int(innermost.index))),
+        "@first" => return Ok(Cow::Owned(Value::Bool(innermost.index == 0))),
+        "@last" => {
+            return Ok(Cow::Owned(Value::Bool(
+                innermost.index + 1 == innermost.len,
+            )))
+        }
+        "this" => innermost.value,
+        _ => scopes
+            .iter()
+            .rev()
+            .find_map(|s| match s.value {
+                Value::Object(map) => map.get(first),
+                _ => None,
+            })
+            .ok_or_else(not_found)?,
+    };
+    for segment in segments {
+        value = match value {
+            Value::Object(map) => map.get(segment).ok_or_else(not_found)?,
+            _ => return Err(not_found()),
+        };
+    }
+    Ok(Cow::Borrowed(value))
+}
+
+fn lookup_list<'a>(
+    path: &str,
+    scopes: &[Scope<'a>],
+    line: usize,
+) -> Result<&'a Vec<Value>, TemplateError> {
+    let not_found = || TemplateError {
+        line,
+        message: format!("unknown list {}", path),
+    };
+    let mut segments = path.split('.');
+    let first = segments.next().unwrap_or("");
+    let mut value: &'a Value = match first {
+        "this" => scopes.last().ok_or_else(not_found)?.value,
+        _ => scopes
+            .iter()
+            .rev()
+            .find_map(|s| match s.value {
+                Value::Object(map) => map.get(first),
+                _ => None,
+            })
+            .ok_or_else(not_found)?,
+    };
+    for segment in segments {
+        value = match value {
+            Value::Object(map) => map.get(segment).ok_or_else(not_found)?,
+            _ => return Err(not_found()),
+        };
+    }
+    match value {
+        Value::List(l) => Ok(l),
+        _ => Err(TemplateError {
+            line,
+            message: format!("{} is not a list", path),
+        }),
+    }
+}
+
+fn render_nodes<'a>(
+    nodes: &[Node],
+    scopes: &mut Vec<Scope<'a>>,
+    out: &mut String,
+) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var(path, line) => match lookup(path, scopes, *line)?.as_ref() {
+                Value::Str(s) => out.push_str(s),
+                Value::Int(x) => out.push_str(&x.to_string()),
+                Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+                _ => {
+                    return Err(TemplateError {
+                        line: *line,
+                        message: format!("{} can't be printed", path),
+                    })
+                }
+            },
+            Node::Each(path, line, body) => {
+                let list = lookup_list(path, scopes, *line)?;
+                for (index, item) in list.iter().enumerate() {
+                    scopes.push(Scope {
+                        value: item,
+                        index,
+                        len: list.len(),
+                    });
+                    let result = render_nodes(body, scopes, out);
+                    scopes.pop();
+                    result?;
+                }
+            }
+            Node::If(path, line, then, otherwise) => {
+                if lookup(path, scopes, *line)?.truthy() {
+                    render_nodes(then, scopes, out)?;
+                } else {
+                    render_nodes(otherwise, scopes, out)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,7 @@
+#![allow(clippy::upper_case_acronyms)]
+
+pub mod codegen;
+pub mod parsing;
+#[cfg(test)]
+mod test;
+pub mod util;
@@ -1,45 +1,547 @@
-mod codegen;
-mod parsing;
-mod test;
+use lmaker::codegen::code_generator::{CodeGenerator, CodeGeneratorData};
+use lmaker::codegen::code_generator_csharp::CodeGeneratorCSharp;
+use lmaker::codegen::code_generator_recursive_descent::CodeGeneratorRecursiveDescent;
+use lmaker::codegen::code_generator_template::CodeGeneratorTemplate;
+use lmaker::codegen::file_system::DirectoryFileSystem;
+use lmaker::codegen::grammar_docs;
+use lmaker::parsing::error_messages::{MessageProblem, Messages};
+use lmaker::parsing::grammar::sentences::{sentence_text, SentenceMode, SentenceOptions};
+use lmaker::parsing::table_cache::{CacheStatus, TableCache, DEFAULT_CACHE_DIR};
+use lmaker::parsing::table_serialization::SavedTable;
+use lmaker::parsing::*;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::exit;
 
-use parsing::*;
+const USAGE: &str = "usage: lmaker <command> [options]
 
-//TODO: FIX EMPTIES
+commands:
+  info <grammar>          print the grammar with its first and follow sets
+  build <grammar>         generate the lr(1) parse table
+      -o <file>           write the table, as json if the file ends in .json, otherwise binary
+      --cache-dir <dir>   where generated tables are cached (default .lmaker-cache)
+      --no-cache          always regenerate the table
+  dot <grammar>           print the lr(1) automaton for graphviz, conflicting states are drawn in red
+      --lalr              merge states with the same items, like an lalr(1) generator does
+      --conflicts         only draw the way to each conflicting state and the states near it
+      --state <n>         only draw the way to state n and the states near it
+      --distance <n>      how many transitions away from those states to draw (default 1)
+  report <grammar>        describe the grammar and every state of its lr(1) table, like bison's .output file
+      -o <file>           write the report to a file instead of stdout
+  docs <grammar>          write an html page with a railroad diagram for every nonterminal
+      -o <file>           output file (default the grammar's name with .html)
+      --title <title>     page title (default the grammar's file name)
+  conflicts <grammar>     explain every conflict of the lr(1) table with an example
+  ambiguity <grammar>     look for a sentence with more than one parse tree, exits with 1 if there is one
+      --length <n>        longest sentence to try, in terminals (default 10)
+      --limit <n>         parses before giving up (default 200000)
+  sentences <grammar>     print sentences of the grammar's language, one per line
+      --count <n>         how many (default 10)
+      --depth <n>         deepest derivation tree (default 12)
+      --seed <n>          seed for the random choices (default 0)
+      --all               every sentence in order of derivation steps instead of random ones
+      --no-coverage       pick productions at random instead of the least used ones first
+  coverage <grammar> <input>...
+                          parse each input file, or the files in each directory, and list the
+                          productions none of them used
+      --details           also list the states never entered and the actions never taken
+      --check             exit with 1 if a production was never used
+  diff <old> <new>        compare two versions of a grammar, exits with 1 if they differ
+      --length <n>        longest sentence to compare the languages on, in terminals (default 6)
+      --limit <n>         parses of each grammar before the language comparison stops (default 100000)
+  repl <grammar>          parse lines typed in, showing the tokens, each parser action with the state
+                          stack and the tree, :reload reads the grammar again and :help lists the commands
+  ll1 <grammar>           print the ll(1) predictive table, or every conflict that prevents one
+  generate <grammar>      generate a parser project
+      -o <dir>            output directory (default .)
+      --name <name>       project name (default Parser)
+      --target <target>   csharp (table driven, the default) or recursive-descent (needs an ll(1) grammar)
+      --templates <dir>   render the *.tmpl files in dir instead of a built in target
+      --messages <file>   embed the syntax error messages from a messages file
+  messages <grammar>      print a messages file with a placeholder entry for every state that can find an error
+      --update <file>     keep the entries of an existing file and add placeholders for the states it misses
+      --check <file>      report examples that no longer fail, conflicting messages and states without one
+
+grammar files have one production per line, blank lines and lines starting with # are ignored";
+
+struct Args {
+    positional: Vec<String>,
+    //flag -> value, None for flags without a value
+    flags: HashMap<String, Option<String>>,
+}
+
+impl Args {
+    fn value(&self, flag: &str) -> Option<&str> {
+        self.flags.get(flag).and_then(|x| x.as_deref())
+    }
+    fn has(&self, flag: &str) -> bool {
+        self.flags.contains_key(flag)
+    }
+}
+
+//value_flags take the next argument as their value
+fn parse_args(args: &[String], value_flags: &[&str], bool_flags: &[&str]) -> Result<Args, String> {
+    let mut parsed = Args {
+        positional: Vec::new(),
+        flags: HashMap::new(),
+    };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if value_flags.contains(&arg.as_str()) {
+            match iter.next() {
+                Some(v) => parsed.flags.insert(arg.clone(), Some(v.clone())),
+                None => return Err(format!("{} needs a value", arg)),
+            };
+        } else if bool_flags.contains(&arg.as_str()) {
+            parsed.flags.insert(arg.clone(), None);
+        } else if arg.starts_with('-') && arg.len() > 1 {
+            return Err(format!("unknown option {}", arg));
+        } else {
+            parsed.positional.push(arg.clone());
+        }
+    }
+    Ok(parsed)
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    exit(1)
+}
+
+fn read_grammar(path: &str) -> grammar::CFG {
+    grammar::CFG::load(Path::new(path)).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)))
+}
+
+fn grammar_arg(args: &Args) -> &str {
+    match args.positional.as_slice() {
+        [path] => path,
+        _ => fail("expected one grammar file"),
+    }
+}
+
+fn info(args: &[String]) {
+    let args = parse_args(args, &[], &[]).unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    print!(
+        "{}\n{}",
+        report::grammar_section(&cfg),
+        report::sets_section(&cfg)
+    );
+}
+
+fn report(args: &[String]) {
+    let args = parse_args(args, &["-o"], &[]).unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    let report = report::report(&cfg);
+    match args.value("-o") {
+        Some(out) => {
+            if let Err(e) = std::fs::write(out, report) {
+                fail(&format!("couldn't write {}: {}", out, e));
+            }
+        }
+        None => print!("{}", report),
+    }
+}
+
+fn conflict_message(cfg: &grammar::CFG, err: &parse_table::TableErr) -> String {
+    match err {
+        parse_table::TableErr::Conflict(a, b, state, symbol) => format!(
+            "conflict in state {} on {}: {:?} vs {:?}, lmaker conflicts shows an example",
+            state,
+            cfg.symbol_str(symbol),
+            a,
+            b
+        ),
+    }
+}
+
+fn build(args: &[String]) {
+    let args =
+        parse_args(args, &["-o", "--cache-dir"], &["--no-cache"]).unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    let mut cache = TableCache::new(Path::new(
+        args.value("--cache-dir").unwrap_or(DEFAULT_CACHE_DIR),
+    ));
+    cache.enabled = !args.has("--no-cache");
+    let (table, status) = cache
+        .get_or_generate(&cfg, "lr1", || lr1_generator::generate(&cfg))
+        .unwrap_or_else(|e| fail(&conflict_message(&cfg, &e)));
+    let status = match status {
+        CacheStatus::Hit => " (cached)",
+        _ => "",
+    };
+    println!("{} states{}", table.rows.len(), status);
+    if let Some(out) = args.value("-o") {
+        let saved = SavedTable::from_table(&table);
+        let out = PathBuf::from(out);
+        let bytes = if out.extension().map(|x| x == "json") == Some(true) {
+            saved.to_json().into_bytes()
+        } else {
+            saved.to_binary()
+        };
+        if let Err(e) = std::fs::write(&out, bytes) {
+            fail(&format!("couldn't write {}: {}", out.display(), e));
+        }
+    }
+}
+
+fn dot(args: &[String]) {
+    let args = parse_args(args, &["--state", "--distance"], &["--lalr", "--conflicts"])
+        .unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    let mut automaton = lr1_generator::automaton(&cfg);
+    if args.has("--lalr") {
+        automaton = automaton.lalr();
+    }
+    let number = |flag: &str| {
+        args.value(flag).map(|x| {
+            x.parse::<usize>()
+                .unwrap_or_else(|_| fail(&format!("{} needs a number", flag)))
+        })
+    };
+    let mut targets = Vec::new();
+    if args.has("--conflicts") {
+        targets.extend(automaton.conflicts(&cfg).iter().map(|x| x.0));
+        if targets.is_empty() {
+            fail("the automaton has no conflicts");
+        }
+    }
+    if let Some(state) = number("--state") {
+        if state >= automaton.states.len() {
+            fail(&format!("there's no state {}", state));
+        }
+        targets.push(state);
+    }
+    let states = match targets.is_empty() {
+        true => None,
+        false => Some(automaton.around(&targets, number("--distance").unwrap_or(1))),
+    };
+    print!(
+        "{}",
+        dot::automaton_dot(&cfg, &automaton, states.as_deref())
+    );
+}
+
+fn docs(args: &[String]) {
+    let args = parse_args(args, &["-o", "--title"], &[]).unwrap_or_else(|e| fail(&e));
+    let path = Path::new(grammar_arg(&args));
+    let cfg = read_grammar(grammar_arg(&args));
+    let name = path
+        .file_name()
+        .map(|x| x.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let html = grammar_docs::render(&cfg, args.value("--title").unwrap_or(&name));
+    let out = match args.value("-o") {
+        Some(out) => PathBuf::from(out),
+        None => path.with_extension("html"),
+    };
+    if let Err(e) = std::fs::write(&out, html) {
+        fail(&format!("couldn't write {}: {}", out.display(), e));
+    }
+    println!("{}", out.display());
+}
+
+fn conflicts(args: &[String]) {
+    let args = parse_args(args, &[], &[]).unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    let examples = counterexample::counterexamples(&cfg);
+    for example in &examples {
+        println!("{}", example.message(&cfg));
+    }
+    println!("{} conflicts", examples.len());
+}
+
+fn ambiguity(args: &[String]) {
+    let args = parse_args(args, &["--length", "--limit"], &[]).unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    let number = |flag: &str, default: usize| match args.value(flag) {
+        Some(x) => x
+            .parse::<usize>()
+            .unwrap_or_else(|_| fail(&format!("{} needs a number", flag))),
+        None => default,
+    };
+    let defaults = ambiguity::AmbiguityCheck::default();
+    let check = ambiguity::AmbiguityCheck {
+        max_length: number("--length", defaults.max_length),
+        max_parses: number("--limit", defaults.max_parses),
+        ..defaults
+    };
+    let report = ambiguity::check(&cfg, &check);
+    println!("{}", report.message(&cfg, &check));
+    if !report.ambiguous.is_empty() {
+        exit(1);
+    }
+}
+
+fn sentences(args: &[String]) {
+    let args = parse_args(
+        args,
+        &["--count", "--depth", "--seed"],
+        &["--all", "--no-coverage"],
+    )
+    .unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    let number = |flag: &str, default: u64| match args.value(flag) {
+        Some(x) => x
+            .parse::<u64>()
+            .unwrap_or_else(|_| fail(&format!("{} needs a number", flag))),
+        None => default,
+    };
+    let defaults = SentenceOptions::default();
+    let options = SentenceOptions {
+        mode: match args.has("--all") {
+            true => SentenceMode::Exhaustive(),
+            false => SentenceMode::Random(number("--seed", 0)),
+        },
+        count: number("--count", defaults.count as u64) as usize,
+        max_depth: number("--depth", defaults.max_depth as u64) as usize,
+        coverage: !args.has("--no-coverage"),
+    };
+    for sentence in grammar::generate_sentences(&cfg, &options) {
+        println!("{}", sentence_text(&cfg, &sentence));
+    }
+}
+
+//the files, with directories replaced by the files in them, sorted
+fn input_files(paths: &[String]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = PathBuf::from(path);
+        if !path.is_dir() {
+            files.push(path);
+            continue;
+        }
+        let entries = std::fs::read_dir(&path)
+            .unwrap_or_else(|e| fail(&format!("couldn't read {}: {}", path.display(), e)));
+        let mut inside: Vec<PathBuf> = entries
+            .filter_map(|x| x.ok().map(|x| x.path()))
+            .filter(|x| x.is_file())
+            .collect();
+        inside.sort();
+        files.extend(inside);
+    }
+    files
+}
+
+fn coverage(args: &[String]) {
+    let args = parse_args(args, &[], &["--details", "--check"]).unwrap_or_else(|e| fail(&e));
+    let (grammar, inputs) = match args.positional.split_first() {
+        Some((grammar, inputs)) if !inputs.is_empty() => (grammar, inputs),
+        _ => fail("expected a grammar file and at least one input"),
+    };
+    let cfg = read_grammar(grammar);
+    let table = lr1_generator::generate(&cfg).unwrap_or_else(|e| fail(&conflict_message(&cfg, &e)));
+    let mut coverage = coverage::Coverage::new(&table);
+    for file in input_files(inputs) {
+        let text = std::fs::read_to_string(&file)
+            .unwrap_or_else(|e| fail(&format!("couldn't read {}: {}", file.display(), e)));
+        let parsed = match parse_tree::tokenize(&cfg, &text) {
+            Ok(tokens) => lr_parser::parse_covering(&table, &tokens, &mut coverage).map(|_| ()),
+            //still an input, it just didn't get to the parser
+            Err(e) => {
+                coverage.inputs += 1;
+                Err(e)
+            }
+        };
+        if let Err(e) = parsed {
+            println!("{}: {}", file.display(), e.message(&cfg));
+        }
+    }
+    print!("{}", coverage.report(&table, args.has("--details")));
+    if args.has("--check") && !coverage.unused_productions().is_empty() {
+        exit(1);
+    }
+}
+
+fn diff(args: &[String]) {
+    let args = parse_args(args, &["--length", "--limit"], &[]).unwrap_or_else(|e| fail(&e));
+    let (old, new) = match args.positional.as_slice() {
+        [old, new] => (read_grammar(old), read_grammar(new)),
+        _ => fail("expected an old and a new grammar file"),
+    };
+    let number = |flag: &str, default: usize| match args.value(flag) {
+        Some(x) => x
+            .parse::<usize>()
+            .unwrap_or_else(|_| fail(&format!("{} needs a number", flag))),
+        None => default,
+    };
+    let defaults = grammar_diff::DiffOptions::default();
+    let options = grammar_diff::DiffOptions {
+        max_length: number("--length", defaults.max_length),
+        max_parses: number("--limit", defaults.max_parses),
+        ..defaults
+    };
+    let diff = grammar_diff::diff(&old, &new, &options);
+    print!("{}", diff.message(&options));
+    if !diff.is_empty() {
+        exit(1);
+    }
+}
+
+fn repl_command(args: &[String]) {
+    let args = parse_args(args, &[], &[]).unwrap_or_else(|e| fail(&e));
+    let mut session = repl::Repl::load(Path::new(grammar_arg(&args))).unwrap_or_else(|e| fail(&e));
+    println!("{}, :help lists the commands", session.summary());
+    let stdin = std::io::stdin();
+    let mut line = String::new();
+    loop {
+        print!("> ");
+        std::io::stdout()
+            .flush()
+            .unwrap_or_else(|e| fail(&e.to_string()));
+        line.clear();
+        match stdin.read_line(&mut line) {
+            //end of input, finish the prompt's line
+            Ok(0) => {
+                println!();
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => fail(&e.to_string()),
+        }
+        match session.eval(&line) {
+            Some(out) => print!("{}", out),
+            None => break,
+        }
+    }
+}
+
+fn ll1(args: &[String]) {
+    let args = parse_args(args, &[], &[]).unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    let table = match ll1_generator::generate(&cfg) {
+        Ok(table) => table,
+        Err(conflicts) => {
+            for conflict in &conflicts {
+                eprintln!("{}", ll1_generator::conflict_message(&cfg, conflict));
+            }
+            fail(&format!(
+                "grammar is not LL(1), {} conflicts",
+                conflicts.len()
+            ))
+        }
+    };
+    for (nonterminal, row) in table.rows.iter().enumerate() {
+        let mut cells: Vec<(&grammar::Symbol, &usize)> = row.iter().collect();
+        cells.sort();
+        for (lookahead, production) in cells {
+            println!(
+                "{}, {}: {}",
+                &cfg.nonterminal_symbols[nonterminal],
+                cfg.symbol_str(lookahead),
+                cfg.production_str(cfg.production(*production))
+            );
+        }
+    }
+}
+
+fn generate(args: &[String]) {
+    let args = parse_args(
+        args,
+        &["-o", "--name", "--target", "--templates", "--messages"],
+        &[],
+    )
+    .unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    let mut generator: Box<dyn CodeGenerator> =
+        match (args.value("--templates"), args.value("--target")) {
+            (Some(_), Some(_)) => fail("--templates and --target can't be used together"),
+            (Some(dir), None) => Box::new(
+                CodeGeneratorTemplate::from_directory(Path::new(dir))
+                    .unwrap_or_else(|e| fail(&e.to_string())),
+            ),
+            (None, None) | (None, Some("csharp")) => Box::new(CodeGeneratorCSharp::default()),
+            (None, Some("recursive-descent")) => Box::new(CodeGeneratorRecursiveDescent::default()),
+            (None, Some(target)) => fail(&format!("unknown target {}", target)),
+        };
+    let table = match args.value("--target") {
+        //works from the grammar alone, and refuses grammars that aren't LL(1) itself
+        Some("recursive-descent") => parse_table::Table {
+            rows: Vec::new(),
+            cfg,
+        },
+        _ => lr1_generator::generate(&cfg).unwrap_or_else(|e| fail(&conflict_message(&cfg, &e))),
+    };
+    let messages = match args.value("--messages") {
+        Some(path) => {
+            let compiled = read_messages(path).compile(&table);
+            let problems: Vec<&MessageProblem> = compiled
+                .problems
+                .iter()
+                .filter(|x| !matches!(x, MessageProblem::Missing(_, _)))
+                .collect();
+            for problem in &problems {
+                eprintln!("{}: {}", path, problem.message(&table.cfg));
+            }
+            if !problems.is_empty() {
+                fail(&format!("{} has {} problems", path, problems.len()));
+            }
+            compiled.by_state
+        }
+        None => BTreeMap::new(),
+    };
+    let data = CodeGeneratorData {
+        table,
+        project_name: String::from(args.value("--name").unwrap_or("Parser")),
+        messages,
+    };
+    let mut fs = DirectoryFileSystem::new(Path::new(args.value("-o").unwrap_or(".")));
+    match generator.generate(&data, &mut fs) {
+        Ok(files) => {
+            for file in files.files {
+                println!("{}", file.display());
+            }
+        }
+        Err(e) => fail(&e.to_string()),
+    }
+}
+
+fn read_messages(path: &str) -> Messages {
+    let text = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| fail(&format!("couldn't read {}: {}", path, e)));
+    Messages::parse(&text).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)))
+}
+
+fn messages(args: &[String]) {
+    let args = parse_args(args, &["--update", "--check"], &[]).unwrap_or_else(|e| fail(&e));
+    let cfg = read_grammar(grammar_arg(&args));
+    let table = lr1_generator::generate(&cfg).unwrap_or_else(|e| fail(&conflict_message(&cfg, &e)));
+    match (args.value("--update"), args.value("--check")) {
+        (Some(_), Some(_)) => fail("--update and --check can't be used together"),
+        (Some(path), None) => print!("{}", error_messages::list(&table, &read_messages(path))),
+        (None, Some(path)) => {
+            let problems = read_messages(path).compile(&table).problems;
+            for problem in &problems {
+                println!("{}: {}", path, problem.message(&table.cfg));
+            }
+            if !problems.is_empty() {
+                exit(1);
+            }
+        }
+        (None, None) => print!("{}", error_messages::list(&table, &Messages::default())),
+    }
+}
 
 fn main() {
-    let gstr = vec![
-        "S -> S .; A",
-        "S -> A",
-        "A -> E",
-        "A -> .id .:= E",
-        "E -> E .+ .id",
-        "E -> .id",
-    ];
-    let grammar_strings: Vec<String> = gstr.iter().map(|&x| String::from(x)).collect();
-    let cfg = grammar::CFG::from_strings(&grammar_strings);
-    cfg.print();
-    println!("\nFirsts:");
-    let firsts = cfg.generate_firsts();
-    for index in 0..firsts.len() {
-        print!("first({}) = ", cfg.nonterminal_symbols[index]);
-        for symbol in &firsts[index] {
-            print!(" {}", cfg.symbol_str(symbol));
-        }
-        println!();
-    }
-
-    println!("\nFollows:");
-    let follows = cfg.generate_follows(&firsts);
-    for index in 0..follows.len() {
-        print!("follow({}) = ", cfg.nonterminal_symbols[index]);
-        for symbol in &follows[index] {
-            print!(" {}", cfg.symbol_str(symbol));
-        }
-        println!();
-    }
-
-    match lr1_generator::generate(&cfg) {
-        Ok(_) => println!("GOOD!"),
-        Err(_) => println!("BAD!"),
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(|x| x.as_str()) {
+        Some("info") => info(&args[1..]),
+        Some("build") => build(&args[1..]),
+        Some("dot") => dot(&args[1..]),
+        Some("report") => report(&args[1..]),
+        Some("docs") => docs(&args[1..]),
+        Some("conflicts") => conflicts(&args[1..]),
+        Some("ambiguity") => ambiguity(&args[1..]),
+        Some("sentences") => sentences(&args[1..]),
+        Some("coverage") => coverage(&args[1..]),
+        Some("diff") => diff(&args[1..]),
+        Some("repl") => repl_command(&args[1..]),
+        Some("ll1") => ll1(&args[1..]),
+        Some("generate") => generate(&args[1..]),
+        Some("messages") => messages(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => println!("{}", USAGE),
+        Some(command) => fail(&format!("unknown command {}\n\n{}", command, USAGE)),
+        None => fail(USAGE),
     }
 }
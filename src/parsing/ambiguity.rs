@@ -0,0 +1,113 @@
+//bounded ambiguity checking, independent of the lr class of the grammar
+//every sentence up to max_length is parsed with a glr table and the ones with more than one tree are
+//reported. finding one proves the grammar ambiguous, finding none only says so up to that length
+//
+//sentences are built a terminal at a time and only extended while they're still a viable prefix,
+//so the search stays in the grammar's language instead of trying every string
+
+use super::glr;
+use super::grammar::{Symbol, CFG};
+use super::lr1_generator;
+use super::parse_tree::ParseTree;
+
+pub struct AmbiguityCheck {
+    //longest sentence tried, in terminals
+    pub max_length: usize,
+    //parses before the check gives up, prefixes count too
+    pub max_parses: usize,
+    //ambiguous sentences to find before stopping
+    pub max_reports: usize,
+}
+
+impl Default for AmbiguityCheck {
+    fn default() -> Self {
+        AmbiguityCheck {
+            max_length: 10,
+            max_parses: 200_000,
+            max_reports: 1,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Ambiguous {
+    pub sentence: Vec<Symbol>,
+    //two of its trees, or one if the others loop back on themselves
+    pub trees: Vec<ParseTree>,
+    //number of trees, None for infinitely many
+    pub count: Option<usize>,
+}
+
+pub struct AmbiguityReport {
+    //shortest first
+    pub ambiguous: Vec<Ambiguous>,
+    //sentences of the language that were parsed
+    pub sentences: usize,
+    //false if a limit stopped the check before every sentence up to max_length was tried
+    pub complete: bool,
+    //whether the lr(1) table has conflicts, to tell ambiguous grammars from ones that aren't lr(1)
+    pub conflicts: bool,
+}
+
+impl AmbiguityReport {
+    pub fn message(&self, cfg: &CFG, check: &AmbiguityCheck) -> String {
+        if let Some(first) = self.ambiguous.first() {
+            let words: Vec<&str> = first.sentence.iter().map(|x| cfg.symbol_str(x)).collect();
+            let trees: Vec<String> = first.trees.iter().map(|x| x.to_sexpr(cfg)).collect();
+            return format!(
+                "the grammar is ambiguous, {} has {} parse trees\n  {}",
+                match words.is_empty() {
+                    true => String::from("the empty sentence"),
+                    false => words.join(" "),
+                },
+                match first.count {
+                    Some(count) => count.to_string(),
+                    None => String::from("infinitely many"),
+                },
+                trees.join("\n  ")
+            );
+        }
+        let checked = match self.complete {
+            true => format!("no sentence up to {} terminals", check.max_length),
+            false => format!("none of the {} sentences checked", self.sentences),
+        };
+        match self.conflicts {
+            true => format!("the grammar isn't lr(1), but {} is ambiguous", checked),
+            false => String::from("the grammar is lr(1), so it isn't ambiguous"),
+        }
+    }
+}
+
+pub fn check(cfg: &CFG, check: &AmbiguityCheck) -> AmbiguityReport {
+    let table = lr1_generator::generate_glr(cfg);
+    let mut report = AmbiguityReport {
+        ambiguous: Vec::new(),
+        sentences: 0,
+        complete: true,
+        conflicts: !table.conflicts().is_empty(),
+    };
+    //an lr(1) grammar can't be ambiguous, there's nothing to look for
+    if !report.conflicts {
+        return report;
+    }
+    let (mut ambiguous, mut sentences) = (Vec::new(), 0);
+    report.complete = glr::viable_sentences(
+        &table,
+        check.max_length,
+        check.max_parses,
+        |sentence, forest| {
+            sentences += 1;
+            if forest.is_ambiguous() {
+                ambiguous.push(Ambiguous {
+                    sentence: sentence.to_vec(),
+                    trees: forest.trees(2),
+                    count: forest.count_trees(),
+                });
+            }
+            ambiguous.len() < check.max_reports
+        },
+    );
+    report.ambiguous = ambiguous;
+    report.sentences = sentences;
+    report
+}
@@ -0,0 +1,452 @@
+//examples explaining lr(1) conflicts, after bison's -Wcounterexamples and Isradatu & Myers
+//
+//a unifying counterexample is one sentential form with two derivations, one for each conflicting
+//action, which shows the grammar is ambiguous. when the search can't find one it settles for a
+//nonunifying pair, two forms that agree up to the conflict and then need different actions
+//
+//forms start with the shortest symbols reaching the conflicting state, then the conflict's
+//lookahead, then a search over every suffix of up to SUFFIX_LENGTH symbols. nonterminals in a form
+//are parsed by a glr table for the grammar with a stand-in terminal for each nonterminal,
+//N -> <N>, and a suffix is only extended while the form is still a viable prefix
+
+use super::glr;
+use super::grammar::{Symbol, CFG};
+use super::lr1_generator;
+use super::parse_table::TableCell;
+use super::parse_tree::{ParseTree, Token};
+use std::collections::VecDeque;
+
+const SUFFIX_LENGTH: usize = 4;
+//glr parses of candidate forms before the search gives up
+const SEARCH_LIMIT: usize = 5_000;
+//trees looked at for each form
+const TREE_LIMIT: usize = 16;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Derivation {
+    pub symbol: Symbol,
+    //None for a symbol of the form
+    pub production: Option<usize>,
+    pub children: Vec<Derivation>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Counterexample {
+    //the form and a derivation using each action
+    Unifying(Vec<Symbol>, Derivation, Derivation),
+    //a form and derivation for each action, the forms agree up to the conflict
+    Nonunifying((Vec<Symbol>, Derivation), (Vec<Symbol>, Derivation)),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ConflictExample {
+    pub state: usize,
+    pub symbol: Symbol,
+    //the first two actions if there are more
+    pub actions: (TableCell, TableCell),
+    //index of the conflict's lookahead in the forms
+    pub position: usize,
+    //None if neither action could be shown within the search limits
+    pub example: Option<Counterexample>,
+}
+
+//an example for every conflict of the grammar's lr(1) table, in Table::conflicts order
+pub fn counterexamples(cfg: &CFG) -> Vec<ConflictExample> {
+    let table = lr1_generator::generate_glr(cfg);
+    let conflicts = table.conflicts();
+    if conflicts.is_empty() {
+        return Vec::new();
+    }
+    let search = Search::new(cfg);
+    conflicts
+        .into_iter()
+        .map(|(state, symbol, cells)| search.explain(state, symbol, (cells[0], cells[1])))
+        .collect()
+}
+
+struct Search<'a> {
+    cfg: &'a CFG,
+    automaton: lr1_generator::Automaton,
+    //grammar with a stand-in terminal per nonterminal, and its glr table
+    table: super::parse_table::Table,
+    //stand-in terminal for each nonterminal
+    stand_ins: Vec<usize>,
+    //production id in the stand-in grammar -> id in cfg, None for N -> <N>
+    original: Vec<Option<usize>>,
+}
+
+impl<'a> Search<'a> {
+    fn new(cfg: &'a CFG) -> Search<'a> {
+        let mut extended = cfg.clone();
+        let mut stand_ins = vec![0; cfg.nonterminal_symbols.len()];
+        for (nonterminal, stand_in) in stand_ins.iter_mut().enumerate().skip(1) {
+            let mut name = format!("<{}>", &cfg.nonterminal_symbols[nonterminal]);
+            while extended.terminal_symbols.get(&name).is_some() {
+                name.push('\'');
+            }
+            *stand_in = extended.terminal_symbols.intern(&name);
+        }
+        let mut original = Vec::new();
+        let mut id = 0;
+        for (nonterminal, productions) in extended.productions.iter_mut().enumerate() {
+            for _ in 0..productions.len() {
+                original.push(Some(id));
+                id += 1;
+            }
+            if nonterminal > 0 {
+                productions.push(super::grammar::CFGProduction {
+                    nonterminal,
+                    rhs: vec![Symbol::Terminal(stand_ins[nonterminal])],
+                });
+                original.push(None);
+            }
+        }
+        Search {
+            cfg,
+            automaton: lr1_generator::automaton(cfg),
+            table: lr1_generator::generate_glr(&extended),
+            stand_ins,
+            original,
+        }
+    }
+
+    fn explain(
+        &self,
+        state: usize,
+        symbol: Symbol,
+        actions: (TableCell, TableCell),
+    ) -> ConflictExample {
+        let prefix = self.shortest_prefix(state);
+        let position = prefix.len();
+        let mut start = prefix;
+        if symbol != Symbol::EOF() {
+            start.push(symbol);
+        }
+        //symbols a suffix is made of, nonterminals first so examples stay general
+        //the end of input can't be followed by anything
+        let alphabet: Vec<Symbol> = match symbol {
+            Symbol::EOF() => Vec::new(),
+            _ => (1..self.cfg.nonterminal_symbols.len())
+                .map(Symbol::Nonterminal)
+                .chain(self.cfg.input_terminals())
+                .collect(),
+        };
+        let mut first: Option<(Vec<Symbol>, Derivation)> = None;
+        let mut second: Option<(Vec<Symbol>, Derivation)> = None;
+        let mut unifying = None;
+        let max_length = start.len() + SUFFIX_LENGTH;
+        glr::search_sentences(
+            &self.table,
+            start,
+            &alphabet,
+            max_length,
+            SEARCH_LIMIT,
+            |form| self.tokens(form),
+            |form, forest| {
+                let derivations: Vec<Derivation> = forest
+                    .trees(TREE_LIMIT)
+                    .iter()
+                    .map(|x| self.derivation(&root_child(x)))
+                    .collect();
+                let uses = |action: TableCell| {
+                    derivations
+                        .iter()
+                        .find(|x| uses_action(x, action, position, &symbol))
+                };
+                match (uses(actions.0), uses(actions.1)) {
+                    (Some(a), Some(b)) if a != b => {
+                        unifying = Some(Counterexample::Unifying(
+                            form.to_vec(),
+                            a.clone(),
+                            b.clone(),
+                        ));
+                        return false;
+                    }
+                    (a, b) => {
+                        if let (None, Some(a)) = (&first, a) {
+                            first = Some((form.to_vec(), a.clone()));
+                        }
+                        if let (None, Some(b)) = (&second, b) {
+                            second = Some((form.to_vec(), b.clone()));
+                        }
+                    }
+                }
+                true
+            },
+        );
+        ConflictExample {
+            state,
+            symbol,
+            actions,
+            position,
+            example: match (unifying, first, second) {
+                (Some(x), _, _) => Some(x),
+                (None, Some(a), Some(b)) => Some(Counterexample::Nonunifying(a, b)),
+                _ => None,
+            },
+        }
+    }
+
+    //fewest symbols taking the automaton from the start to state
+    fn shortest_prefix(&self, state: usize) -> Vec<Symbol> {
+        let mut from: Vec<Option<(usize, Symbol)>> = vec![None; self.automaton.states.len()];
+        let mut seen = vec![false; self.automaton.states.len()];
+        let mut queue = VecDeque::from(vec![0]);
+        seen[0] = true;
+        while let Some(current) = queue.pop_front() {
+            for (symbol, to) in &self.automaton.states[current].transitions {
+                if !seen[*to] {
+                    seen[*to] = true;
+                    from[*to] = Some((current, *symbol));
+                    queue.push_back(*to);
+                }
+            }
+        }
+        let mut prefix = Vec::new();
+        let mut current = state;
+        while let Some((previous, symbol)) = from[current] {
+            prefix.push(symbol);
+            current = previous;
+        }
+        prefix.reverse();
+        prefix
+    }
+
+    fn tokens(&self, form: &[Symbol]) -> Vec<Token> {
+        form.iter()
+            .enumerate()
+            .map(|(index, symbol)| Token {
+                symbol: match symbol {
+                    Symbol::Nonterminal(x) => Symbol::Terminal(self.stand_ins[*x]),
+                    _ => *symbol,
+                },
+                text: String::from(self.cfg.symbol_str(symbol)),
+                line: 1,
+                column: index + 1,
+            })
+            .collect()
+    }
+
+    //back to cfg's productions, N -> <N> becomes the symbol N of the form
+    fn derivation(&self, tree: &ParseTree) -> Derivation {
+        match tree {
+            ParseTree::Leaf(token) => Derivation {
+                symbol: token.symbol,
+                production: None,
+                children: Vec::new(),
+            },
+            ParseTree::Node(production, children) => match self.original[*production] {
+                Some(production) => Derivation {
+                    symbol: Symbol::Nonterminal(self.cfg.production(production).nonterminal),
+                    production: Some(production),
+                    children: children.iter().map(|x| self.derivation(x)).collect(),
+                },
+                None => Derivation {
+                    symbol: Symbol::Nonterminal(self.table.cfg.production(*production).nonterminal),
+                    production: None,
+                    children: Vec::new(),
+                },
+            },
+        }
+    }
+}
+
+//the tree under S' -> S <eof>
+fn root_child(tree: &ParseTree) -> ParseTree {
+    match tree {
+        ParseTree::Node(_, children) => children[0].clone(),
+        ParseTree::Leaf(_) => tree.clone(),
+    }
+}
+
+//whether the derivation takes action with the lookahead at position
+//the stack in the conflicting state holds the symbols before position one for one, so the action's
+//node starts right on the stack with symbols of the form as its children up to position
+fn uses_action(
+    derivation: &Derivation,
+    action: TableCell,
+    position: usize,
+    symbol: &Symbol,
+) -> bool {
+    //calls found with each node and the position of each of its children, returns the node's start
+    fn walk(
+        derivation: &Derivation,
+        at: &mut usize,
+        found: &mut dyn FnMut(&Derivation, &[usize], usize),
+    ) -> usize {
+        let start = *at;
+        if derivation.production.is_none() {
+            *at += 1;
+            return start;
+        }
+        let children: Vec<usize> = derivation
+            .children
+            .iter()
+            .map(|x| walk(x, at, found))
+            .collect();
+        found(derivation, &children, *at);
+        start
+    }
+    //the first count children are symbols of the form ending at position
+    let on_stack = |node: &Derivation, children: &[usize], count: usize| {
+        count <= position
+            && node.children[..count].iter().zip(children).enumerate().all(
+                |(index, (child, at))| {
+                    child.production.is_none() && *at == position - count + index
+                },
+            )
+    };
+    let mut used = false;
+    let mut at = 0;
+    walk(
+        derivation,
+        &mut at,
+        &mut |node, children, end| match action {
+            TableCell::Reduce(production) => {
+                used |= node.production == Some(production)
+                    && end == position
+                    && on_stack(node, children, children.len());
+            }
+            TableCell::Shift(_) => {
+                used |=
+                    node.children
+                        .iter()
+                        .zip(children)
+                        .enumerate()
+                        .any(|(index, (child, at))| {
+                            *at == position
+                                && child.production.is_none()
+                                && child.symbol == *symbol
+                                && on_stack(node, children, index)
+                        });
+            }
+            TableCell::Accept() | TableCell::Goto(_) => {}
+        },
+    );
+    //accepting means the start symbol ends right before the end of input
+    if let TableCell::Accept() = action {
+        used = at == position;
+    }
+    used
+}
+
+impl Derivation {
+    //one line per node, children indented under their parent, • before the symbol at position
+    pub fn lines(&self, cfg: &CFG, position: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        self.write_lines(cfg, 0, &mut 0, position, &mut lines);
+        lines
+    }
+
+    fn write_lines(
+        &self,
+        cfg: &CFG,
+        depth: usize,
+        at: &mut usize,
+        position: usize,
+        lines: &mut Vec<String>,
+    ) {
+        let indent = "  ".repeat(depth);
+        match self.production {
+            None => {
+                let marker = if *at == position { "• " } else { "" };
+                lines.push(format!(
+                    "{}{}{}",
+                    indent,
+                    marker,
+                    cfg.symbol_str(&self.symbol)
+                ));
+                *at += 1;
+            }
+            Some(production) => {
+                let rhs: Vec<&str> = cfg
+                    .production(production)
+                    .rhs
+                    .iter()
+                    .map(|x| cfg.symbol_str(x))
+                    .collect();
+                lines.push(format!(
+                    "{}{} -> {}",
+                    indent,
+                    cfg.symbol_str(&self.symbol),
+                    rhs.join(" ")
+                ));
+                for child in &self.children {
+                    child.write_lines(cfg, depth + 1, at, position, lines);
+                }
+            }
+        }
+    }
+}
+
+//E .+ E • .+ E
+pub fn form_str(cfg: &CFG, form: &[Symbol], position: usize) -> String {
+    let mut words: Vec<&str> = form.iter().map(|x| cfg.symbol_str(x)).collect();
+    words.insert(position.min(words.len()), "•");
+    words.join(" ")
+}
+
+fn action_str(cfg: &CFG, action: &TableCell) -> String {
+    match action {
+        TableCell::Shift(_) => String::from("shift"),
+        TableCell::Reduce(production) => {
+            let production = cfg.production(*production);
+            let rhs: Vec<&str> = production.rhs.iter().map(|x| cfg.symbol_str(x)).collect();
+            format!(
+                "reduce {} -> {}",
+                &cfg.nonterminal_symbols[production.nonterminal],
+                rhs.join(" ")
+            )
+        }
+        TableCell::Goto(_) => String::from("goto"),
+        TableCell::Accept() => String::from("accept"),
+    }
+}
+
+impl ConflictExample {
+    pub fn message(&self, cfg: &CFG) -> String {
+        let (a, b) = (
+            action_str(cfg, &self.actions.0),
+            action_str(cfg, &self.actions.1),
+        );
+        let mut out = format!(
+            "conflict in state {} on {} between {} and {}\n",
+            self.state,
+            cfg.describe(&self.symbol),
+            a,
+            b
+        );
+        let derivation = |title: &str, derivation: &Derivation, out: &mut String| {
+            out.push_str(&format!("  {} derivation\n", title));
+            for line in derivation.lines(cfg, self.position) {
+                out.push_str(&format!("    {}\n", line));
+            }
+        };
+        match &self.example {
+            Some(Counterexample::Unifying(form, x, y)) => {
+                out.push_str(&format!(
+                    "  ambiguous example: {}\n",
+                    form_str(cfg, form, self.position)
+                ));
+                derivation(&a, x, &mut out);
+                derivation(&b, y, &mut out);
+            }
+            Some(Counterexample::Nonunifying((form_a, x), (form_b, y))) => {
+                out.push_str(&format!(
+                    "  example for {}: {}\n",
+                    a,
+                    form_str(cfg, form_a, self.position)
+                ));
+                derivation(&a, x, &mut out);
+                out.push_str(&format!(
+                    "  example for {}: {}\n",
+                    b,
+                    form_str(cfg, form_b, self.position)
+                ));
+                derivation(&b, y, &mut out);
+            }
+            None => out.push_str("  no example found\n"),
+        }
+        out
+    }
+}
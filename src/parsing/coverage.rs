@@ -0,0 +1,130 @@
+//grammar coverage of a test corpus: which productions were reduced, which states of the lr table
+//were entered and which of its actions were taken while lr_parser::parse_covering ran the corpus
+//a production that's never reduced is part of the language no test exercises
+
+use super::grammar::Symbol;
+use super::parse_table::{Table, TableCell};
+use std::collections::HashMap;
+
+pub struct Coverage {
+    //times each production was reduced, by production id, accepting counts for the start rule
+    pub productions: Vec<usize>,
+    //times each state was entered, by state id
+    pub states: Vec<usize>,
+    //times each table cell was used, by state then symbol, a goto counts when a reduction uses it
+    pub actions: Vec<HashMap<Symbol, usize>>,
+    //inputs parsed, and how many were accepted
+    pub inputs: usize,
+    pub accepted: usize,
+}
+
+impl Coverage {
+    pub fn new(table: &Table) -> Coverage {
+        Coverage {
+            productions: vec![0; table.cfg.production_count()],
+            states: vec![0; table.rows.len()],
+            actions: vec![HashMap::new(); table.rows.len()],
+            inputs: 0,
+            accepted: 0,
+        }
+    }
+
+    pub(crate) fn entered(&mut self, state: usize) {
+        self.states[state] += 1;
+    }
+
+    pub(crate) fn took(&mut self, state: usize, symbol: &Symbol) {
+        *self.actions[state].entry(*symbol).or_default() += 1;
+    }
+
+    pub(crate) fn reduced(&mut self, production: usize) {
+        self.productions[production] += 1;
+    }
+
+    //production ids, in order
+    pub fn unused_productions(&self) -> Vec<usize> {
+        (0..self.productions.len())
+            .filter(|x| self.productions[*x] == 0)
+            .collect()
+    }
+
+    pub fn unused_states(&self) -> Vec<usize> {
+        (0..self.states.len())
+            .filter(|x| self.states[*x] == 0)
+            .collect()
+    }
+
+    //cells of the table that were never used, by state then symbol
+    pub fn unused_actions(&self, table: &Table) -> Vec<(usize, Symbol, TableCell)> {
+        let mut unused = Vec::new();
+        for (state, row) in table.rows.iter().enumerate() {
+            let mut cells: Vec<(&Symbol, &TableCell)> = row
+                .cells
+                .iter()
+                .filter(|(symbol, _)| !self.actions[state].contains_key(symbol))
+                .collect();
+            cells.sort();
+            unused.extend(cells.into_iter().map(|(s, c)| (state, *s, *c)));
+        }
+        unused
+    }
+
+    //summary, then every production never reduced, and with details the states and actions too
+    pub fn report(&self, table: &Table, details: bool) -> String {
+        let cfg = &table.cfg;
+        let cells: usize = table.rows.iter().map(|x| x.cells.len()).sum();
+        let unused_productions = self.unused_productions();
+        let unused_states = self.unused_states();
+        let unused_actions = self.unused_actions(table);
+        let mut out = format!(
+            "{} inputs, {} accepted\n{}\n{}\n{}\n",
+            self.inputs,
+            self.accepted,
+            ratio(
+                "productions reduced",
+                self.productions.len() - unused_productions.len(),
+                self.productions.len()
+            ),
+            ratio(
+                "states entered",
+                self.states.len() - unused_states.len(),
+                self.states.len()
+            ),
+            ratio("actions taken", cells - unused_actions.len(), cells)
+        );
+        if !unused_productions.is_empty() {
+            out.push_str("\nproductions never reduced:\n");
+            for id in unused_productions {
+                out.push_str(&format!(
+                    "  {:>3} {}\n",
+                    id,
+                    cfg.production_str(cfg.production(id))
+                ));
+            }
+        }
+        if details && !unused_states.is_empty() {
+            let states: Vec<String> = unused_states.iter().map(|x| x.to_string()).collect();
+            out.push_str(&format!("\nstates never entered: {}\n", states.join(", ")));
+        }
+        if details && !unused_actions.is_empty() {
+            out.push_str("\nactions never taken:\n");
+            for (state, symbol, cell) in unused_actions {
+                out.push_str(&format!(
+                    "  state {}, {}: {}\n",
+                    state,
+                    cfg.symbol_str(&symbol),
+                    cell.describe(cfg)
+                ));
+            }
+        }
+        out
+    }
+}
+
+fn ratio(what: &str, used: usize, total: usize) -> String {
+    let percent = match total {
+        0 => 100,
+        _ => used * 100 / total,
+    };
+    format!("{}: {} of {} ({}%)", what, used, total, percent)
+}
@@ -0,0 +1,223 @@
+//differential testing of the table generators against each other
+//earley parses any grammar without a table, so it's the reference. every algorithm whose table has no
+//conflicts has to accept exactly the sentences earley accepts, with the same tree, anything else is a
+//bug in that generator or the lr runtime. there's no minimal lr (ielr) generator yet, once there is
+//it goes in ALGORITHMS
+//
+//random_grammar and minimize are a small property testing strategy without the dependency: grammars
+//generated from a seed, and shrinking a failing one to the fewest and shortest productions that fail
+
+use super::earley;
+use super::grammar::sentences::{SentenceMode, SentenceOptions};
+use super::grammar::{self, Symbol, CFG};
+use super::lr1_generator;
+use super::lr_parser;
+use super::parse_table::{Table, TableErr};
+use super::parse_tree::{symbol_tokens, ParseTree};
+use crate::util::rng::Rng;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Algorithm {
+    Lr1(),
+    Lalr(),
+    Slr(),
+}
+
+pub const ALGORITHMS: [Algorithm; 3] = [Algorithm::Lr1(), Algorithm::Lalr(), Algorithm::Slr()];
+
+impl Algorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Lr1() => "lr(1)",
+            Algorithm::Lalr() => "lalr(1)",
+            Algorithm::Slr() => "slr(1)",
+        }
+    }
+
+    pub fn generate(&self, cfg: &CFG) -> Result<Table, TableErr> {
+        match self {
+            Algorithm::Lr1() => lr1_generator::generate(cfg),
+            Algorithm::Lalr() => lr1_generator::generate_lalr(cfg),
+            Algorithm::Slr() => lr1_generator::generate_slr(cfg),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Mismatch {
+    pub algorithm: Algorithm,
+    pub sentence: Vec<Symbol>,
+    //earley's tree, None if it rejects the sentence
+    pub expected: Option<ParseTree>,
+    pub actual: Option<ParseTree>,
+}
+
+impl Mismatch {
+    pub fn message(&self, cfg: &CFG) -> String {
+        let words: Vec<&str> = self.sentence.iter().map(|x| cfg.symbol_str(x)).collect();
+        let tree = |tree: &Option<ParseTree>| match tree {
+            Some(tree) => tree.to_sexpr(cfg),
+            None => String::from("rejects it"),
+        };
+        format!(
+            "{} disagrees with earley on \"{}\"\n  earley: {}\n  {}: {}",
+            self.algorithm.name(),
+            words.join(" "),
+            tree(&self.expected),
+            self.algorithm.name(),
+            tree(&self.actual)
+        )
+    }
+}
+
+//every disagreement with earley, algorithms with conflicts in their table are skipped
+pub fn compare(cfg: &CFG, corpus: &[Vec<Symbol>]) -> Vec<Mismatch> {
+    let tables: Vec<(Algorithm, Table)> = ALGORITHMS
+        .iter()
+        .filter_map(|x| x.generate(cfg).ok().map(|table| (*x, table)))
+        .collect();
+    let mut mismatches = Vec::new();
+    //nothing to compare, and earley can be slow on the ambiguous grammars this leaves
+    if tables.is_empty() {
+        return mismatches;
+    }
+    for sentence in corpus {
+        let tokens = symbol_tokens(cfg, sentence);
+        let expected = earley::parse(cfg, &tokens).ok();
+        for (algorithm, table) in &tables {
+            let actual = lr_parser::parse(table, &tokens).ok();
+            if actual != expected {
+                mismatches.push(Mismatch {
+                    algorithm: *algorithm,
+                    sentence: sentence.clone(),
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+    }
+    mismatches
+}
+
+//sentences of the language, then near misses made from them by dropping, replacing or adding a terminal
+//so both accepting and rejecting get checked. at most size of each, without duplicates
+//random sentences rather than exhaustive ones, ambiguous grammars derive the same few sentences over
+//and over before reaching new ones
+pub fn corpus(cfg: &CFG, size: usize, seed: u64) -> Vec<Vec<Symbol>> {
+    let terminals = cfg.input_terminals();
+    let sentences = grammar::generate_sentences(
+        cfg,
+        &SentenceOptions {
+            mode: SentenceMode::Random(seed),
+            count: size,
+            max_depth: 6,
+            coverage: true,
+        },
+    );
+    let mut seen: HashSet<Vec<Symbol>> = sentences.iter().cloned().collect();
+    let mut corpus = sentences.clone();
+    let mut rng = Rng::new(seed);
+    for sentence in sentences.iter().cycle().take(size) {
+        if terminals.is_empty() {
+            break;
+        }
+        let mut mutated = sentence.clone();
+        let terminal = terminals[rng.below(terminals.len())];
+        match rng.below(3) {
+            0 if !mutated.is_empty() => {
+                mutated.remove(rng.below(mutated.len()));
+            }
+            1 if !mutated.is_empty() => {
+                let at = rng.below(mutated.len());
+                mutated[at] = terminal;
+            }
+            _ => mutated.insert(rng.below(mutated.len() + 1), terminal),
+        }
+        if seen.insert(mutated.clone()) {
+            corpus.push(mutated);
+        }
+    }
+    corpus
+}
+
+const NONTERMINAL_NAMES: [&str; 3] = ["A", "B", "C"];
+const TERMINAL_NAMES: [&str; 3] = [".a", ".b", ".c"];
+
+//production strings for CFG::from_strings: 1 to 3 nonterminals each with 1 to 3 productions of up
+//to 3 symbols, over 1 to 3 terminals. A is the start symbol
+pub fn random_grammar(seed: u64) -> Vec<String> {
+    let mut rng = Rng::new(seed);
+    let nonterminals = 1 + rng.below(NONTERMINAL_NAMES.len());
+    let terminals = 1 + rng.below(TERMINAL_NAMES.len());
+    let mut lines = Vec::new();
+    for lhs in NONTERMINAL_NAMES.iter().take(nonterminals) {
+        for _ in 0..1 + rng.below(3) {
+            let rhs: Vec<&str> = (0..rng.below(4))
+                .map(|_| {
+                    let symbol = rng.below(nonterminals + terminals);
+                    match symbol < nonterminals {
+                        true => NONTERMINAL_NAMES[symbol],
+                        false => TERMINAL_NAMES[symbol - nonterminals],
+                    }
+                })
+                .collect();
+            lines.push(format!("{} -> {}", lhs, rhs_str(&rhs)));
+        }
+    }
+    lines
+}
+
+fn rhs_str(rhs: &[&str]) -> String {
+    match rhs.is_empty() {
+        true => String::from("<empty>"),
+        false => rhs.join(" "),
+    }
+}
+
+//shrinks a grammar that fails, one production or rhs symbol at a time, until removing anything
+//else makes it pass. the first production stays so the start symbol doesn't change
+pub fn minimize<F>(grammar: &[String], fails: F) -> Vec<String>
+where
+    F: Fn(&[String]) -> bool,
+{
+    let mut grammar = grammar.to_vec();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut i = 1;
+        while i < grammar.len() {
+            let mut smaller = grammar.clone();
+            smaller.remove(i);
+            match fails(&smaller) {
+                true => {
+                    grammar = smaller;
+                    changed = true;
+                }
+                false => i += 1,
+            }
+        }
+        for i in 0..grammar.len() {
+            let mut j = 0;
+            loop {
+                let (lhs, rhs) = grammar[i].split_once(" -> ").unwrap();
+                let rhs: Vec<&str> = rhs.split_whitespace().filter(|x| *x != "<empty>").collect();
+                if j >= rhs.len() {
+                    break;
+                }
+                let mut shorter = rhs.clone();
+                shorter.remove(j);
+                let mut smaller = grammar.clone();
+                smaller[i] = format!("{} -> {}", lhs, rhs_str(&shorter));
+                match fails(&smaller) {
+                    true => {
+                        grammar = smaller;
+                        changed = true;
+                    }
+                    false => j += 1,
+                }
+            }
+        }
+    }
+    grammar
+}
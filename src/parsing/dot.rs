@@ -0,0 +1,80 @@
+//graphviz export of the lr automaton, one box per state listing its items and one edge per transition
+//  lmaker dot grammar.lm --conflicts | dot -Tsvg > automaton.svg
+
+use super::grammar::{Symbol, CFG};
+use super::lr1_generator::{Automaton, AutomatonItem};
+use super::parse_table::TableCell;
+
+//states to draw, None for all of them
+//edges to states that aren't drawn are left out
+pub fn automaton_dot(cfg: &CFG, automaton: &Automaton, states: Option<&[usize]>) -> String {
+    let conflicts = automaton.conflicts(cfg);
+    let shown = |state: usize| states.map(|x| x.contains(&state)).unwrap_or(true);
+    let mut out = String::from(
+        "digraph automaton {\n\trankdir=LR;\n\tnode [shape=box, fontname=\"monospace\"];\n",
+    );
+    for (id, state) in automaton.states.iter().enumerate() {
+        if !shown(id) {
+            continue;
+        }
+        let mut label = format!("state {}\\l", id);
+        for item in &state.items {
+            label.push_str(&escape(&item_str(cfg, item)));
+            label.push_str("\\l");
+        }
+        let state_conflicts: Vec<&(usize, Symbol, Vec<TableCell>)> =
+            conflicts.iter().filter(|x| x.0 == id).collect();
+        for (_, symbol, cells) in &state_conflicts {
+            let cells: Vec<String> = cells.iter().map(|x| x.describe(cfg)).collect();
+            label.push_str(&escape(&format!(
+                "conflict on {}: {}",
+                cfg.symbol_str(symbol),
+                cells.join(" / ")
+            )));
+            label.push_str("\\l");
+        }
+        let style = match state_conflicts.is_empty() {
+            true => "",
+            false => ", color=red, penwidth=2",
+        };
+        out.push_str(&format!("\ts{} [label=\"{}\"{}];\n", id, label, style));
+    }
+    for (id, state) in automaton.states.iter().enumerate() {
+        for (symbol, to) in &state.transitions {
+            if shown(id) && shown(*to) {
+                out.push_str(&format!(
+                    "\ts{} -> s{} [label=\"{}\"];\n",
+                    id,
+                    to,
+                    escape(cfg.symbol_str(symbol))
+                ));
+            }
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+//E -> E • .+ .id, .+ <eof>
+pub fn item_str(cfg: &CFG, item: &AutomatonItem) -> String {
+    let production = cfg.production(item.production);
+    let mut out = format!("{} ->", &cfg.nonterminal_symbols[production.nonterminal]);
+    for (index, symbol) in production.rhs.iter().enumerate() {
+        if index == item.dot {
+            out.push_str(" •");
+        }
+        if *symbol != Symbol::Empty() {
+            out.push(' ');
+            out.push_str(cfg.symbol_str(symbol));
+        }
+    }
+    if item.dot == production.rhs.len() {
+        out.push_str(" •");
+    }
+    let lookaheads: Vec<&str> = item.lookaheads.iter().map(|x| cfg.symbol_str(x)).collect();
+    format!("{}, {}", out, lookaheads.join(" "))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,257 @@
+//earley parser, works on any CFG straight from the grammar with no table, for trying out grammars
+//that aren't LR(1) yet, it's cubic in the worst case so it isn't meant for real inputs
+//<empty> is handled by moving the dot past nullable nonterminals as they're predicted
+//resource: Aycock & Horspool, Practical Earley Parsing
+
+use super::grammar::{Symbol, CFG};
+use super::parse_tree::{ParseError, ParseTree, Token};
+use std::collections::HashSet;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+struct Item {
+    production: usize,
+    dot: usize,
+    //position the item started at
+    origin: usize,
+}
+
+struct Earley<'a> {
+    cfg: &'a CFG,
+    //rhs of each production without <empty>
+    rhs: Vec<Vec<Symbol>>,
+    //items that end at each position, sets are for lookups
+    chart: Vec<Vec<Item>>,
+    sets: Vec<HashSet<Item>>,
+    tokens: Vec<Token>,
+}
+
+impl Earley<'_> {
+    fn next_symbol(&self, item: &Item) -> Option<Symbol> {
+        self.rhs[item.production].get(item.dot).copied()
+    }
+
+    fn add(&mut self, position: usize, item: Item) {
+        if self.sets[position].insert(item) {
+            self.chart[position].push(item);
+        }
+    }
+
+    fn predict_and_complete(&mut self, position: usize, nullable: &[bool]) {
+        let mut k = 0;
+        while k < self.chart[position].len() {
+            let item = self.chart[position][k];
+            k += 1;
+            match self.next_symbol(&item) {
+                Some(Symbol::Nonterminal(nt)) => {
+                    for production in &self.cfg.productions[nt] {
+                        let production = self.cfg.production_id(production);
+                        self.add(
+                            position,
+                            Item {
+                                production,
+                                dot: 0,
+                                origin: position,
+                            },
+                        );
+                    }
+                    //the nonterminal can finish right here, so can the item waiting on it
+                    if nullable[nt] {
+                        self.add(
+                            position,
+                            Item {
+                                dot: item.dot + 1,
+                                ..item
+                            },
+                        );
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    let lhs = Symbol::Nonterminal(self.cfg.production(item.production).nonterminal);
+                    let mut j = 0;
+                    while j < self.chart[item.origin].len() {
+                        let waiting = self.chart[item.origin][j];
+                        j += 1;
+                        if self.next_symbol(&waiting) == Some(lhs) {
+                            self.add(
+                                position,
+                                Item {
+                                    dot: waiting.dot + 1,
+                                    ..waiting
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn expected(&self, position: usize) -> Vec<Symbol> {
+        let mut expected: Vec<Symbol> = self.chart[position]
+            .iter()
+            .filter_map(|x| self.next_symbol(x))
+            .filter(|x| matches!(x, Symbol::Terminal(_) | Symbol::EOF()))
+            .collect();
+        expected.sort();
+        expected.dedup();
+        expected
+    }
+
+    //tree for a completed item from start to end, None if every derivation loops back onto the path
+    //path has the nonterminal and span of every node above this one
+    fn build(
+        &self,
+        production: usize,
+        start: usize,
+        end: usize,
+        path: &mut Vec<(usize, usize, usize)>,
+    ) -> Option<ParseTree> {
+        let nonterminal = self.cfg.production(production).nonterminal;
+        path.push((nonterminal, start, end));
+        let mut children = Vec::new();
+        let found = self.build_children(
+            production,
+            self.rhs[production].len(),
+            start,
+            end,
+            path,
+            &mut children,
+        );
+        path.pop();
+        if !found {
+            return None;
+        }
+        children.reverse();
+        Some(ParseTree::Node(production, children))
+    }
+
+    //fills children right to left for rhs[..dot] deriving tokens[start..end]
+    fn build_children(
+        &self,
+        production: usize,
+        dot: usize,
+        start: usize,
+        end: usize,
+        path: &mut Vec<(usize, usize, usize)>,
+        children: &mut Vec<ParseTree>,
+    ) -> bool {
+        if dot == 0 {
+            return start == end;
+        }
+        let prefix = |middle: usize| {
+            self.sets[middle].contains(&Item {
+                production,
+                dot: dot - 1,
+                origin: start,
+            })
+        };
+        match self.rhs[production][dot - 1] {
+            Symbol::Nonterminal(nt) => {
+                for middle in (start..=end).rev() {
+                    if !prefix(middle) || path.contains(&(nt, middle, end)) {
+                        continue;
+                    }
+                    for item in &self.chart[end] {
+                        let completed = item.origin == middle
+                            && item.dot == self.rhs[item.production].len()
+                            && self.cfg.production(item.production).nonterminal == nt;
+                        if !completed {
+                            continue;
+                        }
+                        if let Some(child) = self.build(item.production, middle, end, path) {
+                            let len = children.len();
+                            children.push(child);
+                            if self.build_children(
+                                production,
+                                dot - 1,
+                                start,
+                                middle,
+                                path,
+                                children,
+                            ) {
+                                return true;
+                            }
+                            children.truncate(len);
+                        }
+                    }
+                }
+                false
+            }
+            symbol => {
+                if end == start || self.tokens[end - 1].symbol != symbol || !prefix(end - 1) {
+                    return false;
+                }
+                children.push(ParseTree::Leaf(self.tokens[end - 1].clone()));
+                self.build_children(production, dot - 1, start, end - 1, path, children)
+            }
+        }
+    }
+}
+
+//tokens shouldn't include the end of input, it's added here
+//ambiguous input gives one of its trees, use glr for all of them
+pub fn parse(cfg: &CFG, tokens: &[Token]) -> Result<ParseTree, ParseError> {
+    let mut tokens = tokens.to_vec();
+    tokens.push(Token::eof_after(&tokens));
+    let rhs = (0..cfg.production_count())
+        .map(|x| {
+            cfg.production(x)
+                .rhs
+                .iter()
+                .filter(|x| **x != Symbol::Empty())
+                .copied()
+                .collect()
+        })
+        .collect();
+    let mut earley = Earley {
+        cfg,
+        rhs,
+        chart: vec![Vec::new(); tokens.len() + 1],
+        sets: vec![HashSet::new(); tokens.len() + 1],
+        tokens,
+    };
+    let nullable = cfg.nullable();
+    for production in &cfg.productions[0] {
+        let production = cfg.production_id(production);
+        earley.add(
+            0,
+            Item {
+                production,
+                dot: 0,
+                origin: 0,
+            },
+        );
+    }
+    for position in 0..earley.tokens.len() {
+        earley.predict_and_complete(position, &nullable);
+        let symbol = earley.tokens[position].symbol;
+        let scanned: Vec<Item> = earley.chart[position]
+            .iter()
+            .filter(|x| earley.next_symbol(x) == Some(symbol))
+            .map(|x| Item {
+                dot: x.dot + 1,
+                ..*x
+            })
+            .collect();
+        if scanned.is_empty() {
+            return Err(ParseError::Unexpected(
+                earley.tokens[position].clone(),
+                earley.expected(position),
+            ));
+        }
+        for item in scanned {
+            earley.add(position + 1, item);
+        }
+    }
+    //every start rule ends with <eof>, so anything after it is already complete
+    let end = earley.tokens.len();
+    let mut path = Vec::new();
+    earley.chart[end]
+        .iter()
+        .filter(|x| x.origin == 0 && earley.cfg.production(x.production).nonterminal == 0)
+        .find_map(|x| earley.build(x.production, 0, end, &mut path))
+        .ok_or_else(|| {
+            ParseError::Unexpected(earley.tokens[end - 1].clone(), earley.expected(end - 1))
+        })
+}
@@ -0,0 +1,323 @@
+//hand written syntax error messages attached to LR states through example inputs, like menhir's .messages files
+//each example is run through the table and its message belongs to the state the error is found in,
+//so the examples keep working as the grammar changes and check can point out the ones that no longer do
+//
+//file layout, blocks of lines separated by blank lines, alternating examples and messages:
+//  # lines starting with # are comments, list writes ## notes about each state
+//  .id .= .id .id
+//  id = ( id id
+//
+//  expected `;` after the expression
+//
+//an example is read with parse_tree::tokenize, a trailing <eof> word is dropped so the empty input can be written
+//messages can span several lines, PLACEHOLDER counts as no message
+
+use super::grammar::{Symbol, CFG};
+use super::lr_parser::{self, Step};
+use super::parse_table::{Table, TableCell};
+use super::parse_tree::{tokenize, ParseError, Token};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt;
+
+pub const PLACEHOLDER: &str = "<YOUR SYNTAX ERROR MESSAGE HERE>";
+//configurations error_sentences looks at before giving up on the states it hasn't reached
+const SEARCH_LIMIT: usize = 100_000;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MessageEntry {
+    //line number and text of each example
+    pub sentences: Vec<(usize, String)>,
+    pub message: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Messages {
+    pub entries: Vec<MessageEntry>,
+}
+
+pub enum MessagesError {
+    //line of the examples
+    MissingMessage(usize),
+}
+
+impl fmt::Display for MessagesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MessagesError::MissingMessage(line) => {
+                write!(f, "the examples on line {} have no message", line)
+            }
+        }
+    }
+}
+
+impl fmt::Debug for MessagesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MessageProblem {
+    //line of an example that doesn't tokenize
+    BadSentence(usize, ParseError),
+    //line of an example that parses without an error, the grammar has probably changed
+    NoError(usize),
+    //lines of two examples with different messages that fail in the same state, and the state
+    Conflict(usize, usize, usize),
+    //state that can find an error but has no message, and an input that fails there
+    Missing(usize, Vec<Symbol>),
+}
+
+impl MessageProblem {
+    pub fn message(&self, cfg: &CFG) -> String {
+        match self {
+            MessageProblem::BadSentence(line, e) => format!("line {}: {}", line, e.message(cfg)),
+            MessageProblem::NoError(line) => {
+                format!("line {}: the example parses without an error", line)
+            }
+            MessageProblem::Conflict(a, b, state) => format!(
+                "lines {} and {}: both fail in state {} but have different messages",
+                a, b, state
+            ),
+            MessageProblem::Missing(state, sentence) => format!(
+                "state {} has no message, it's reached by: {}",
+                state,
+                sentence_str(cfg, sentence)
+            ),
+        }
+    }
+}
+
+//messages by state, and everything wrong with the file for this table
+pub struct Compiled {
+    pub by_state: BTreeMap<usize, String>,
+    pub problems: Vec<MessageProblem>,
+}
+
+impl Messages {
+    pub fn parse(text: &str) -> Result<Messages, MessagesError> {
+        let mut blocks: Vec<Vec<(usize, &str)>> = Vec::new();
+        let mut in_block = false;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.trim().is_empty() {
+                in_block = false;
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            if !in_block {
+                blocks.push(Vec::new());
+                in_block = true;
+            }
+            blocks.last_mut().unwrap().push((index + 1, line));
+        }
+        let mut entries = Vec::new();
+        let mut blocks = blocks.into_iter();
+        while let Some(sentences) = blocks.next() {
+            let message = match blocks.next() {
+                Some(message) => message,
+                None => return Err(MessagesError::MissingMessage(sentences[0].0)),
+            };
+            let message: Vec<&str> = message.iter().map(|x| x.1).collect();
+            entries.push(MessageEntry {
+                sentences: sentences
+                    .iter()
+                    .map(|(line, text)| (*line, String::from(text.trim())))
+                    .collect(),
+                message: message.join("\n"),
+            });
+        }
+        Ok(Messages { entries })
+    }
+
+    //PLACEHOLDER messages are left out, examples still have to fail but can't conflict
+    pub fn compile(&self, table: &Table) -> Compiled {
+        let cfg = &table.cfg;
+        let mut by_state = BTreeMap::new();
+        //line of the example that set each state's message
+        let mut lines: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut covered = HashSet::new();
+        let mut problems = Vec::new();
+        for entry in &self.entries {
+            for (line, sentence) in &entry.sentences {
+                let state = match read_sentence(cfg, sentence) {
+                    Ok(tokens) => match error_state(table, &tokens) {
+                        Some(state) => state,
+                        None => {
+                            problems.push(MessageProblem::NoError(*line));
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        problems.push(MessageProblem::BadSentence(*line, e));
+                        continue;
+                    }
+                };
+                if entry.message == PLACEHOLDER {
+                    continue;
+                }
+                covered.insert(state);
+                match by_state.get(&state) {
+                    Some(message) if *message != entry.message => {
+                        problems.push(MessageProblem::Conflict(lines[&state], *line, state))
+                    }
+                    Some(_) => {}
+                    None => {
+                        by_state.insert(state, entry.message.clone());
+                        lines.insert(state, *line);
+                    }
+                }
+            }
+        }
+        for (state, sentence) in error_sentences(table) {
+            if !covered.contains(&state) {
+                problems.push(MessageProblem::Missing(state, sentence));
+            }
+        }
+        Compiled { by_state, problems }
+    }
+}
+
+fn read_sentence(cfg: &CFG, sentence: &str) -> Result<Vec<Token>, ParseError> {
+    let sentence = sentence.trim();
+    let sentence = sentence.strip_suffix("<eof>").unwrap_or(sentence);
+    tokenize(cfg, sentence)
+}
+
+//terminal names separated by spaces, <eof> for the empty input
+pub fn sentence_str(cfg: &CFG, sentence: &[Symbol]) -> String {
+    let names: Vec<&str> = sentence
+        .iter()
+        .filter(|x| **x != Symbol::EOF())
+        .map(|x| cfg.symbol_str(x))
+        .collect();
+    match names.is_empty() {
+        true => String::from("<eof>"),
+        false => names.join(" "),
+    }
+}
+
+//state the table finds the first error in, None if the input parses
+//tokens shouldn't include the end of input, it's added here
+pub fn error_state(table: &Table, tokens: &[Token]) -> Option<usize> {
+    let mut states = vec![0];
+    let eof = Token::eof_after(tokens);
+    for token in tokens.iter().chain(std::iter::once(&eof)) {
+        match lr_parser::advance(table, &mut states, &token.symbol) {
+            Step::Shifted => {}
+            Step::Accepted => return None,
+            Step::Error => return Some(*states.last().unwrap()),
+        }
+    }
+    None
+}
+
+//a shortest failing input for each state that can find an error, sorted by state
+//an input is the shortest way to reach the state followed by a token it doesn't expect,
+//or nothing more when the end of input is one, so error_state on it gives the state back
+pub fn error_sentences(table: &Table) -> Vec<(usize, Vec<Symbol>)> {
+    let cfg = &table.cfg;
+    let mut inputs = cfg.input_terminals();
+    inputs.push(Symbol::EOF());
+    //errors are only found right after a shift, or before anything is read
+    let mut targets: HashSet<usize> = table
+        .rows
+        .iter()
+        .flat_map(|row| {
+            row.cells
+                .iter()
+                .filter_map(|(symbol, cell)| match (symbol, cell) {
+                    (Symbol::Terminal(_), TableCell::Shift(state)) => Some(*state),
+                    _ => None,
+                })
+        })
+        .collect();
+    targets.insert(0);
+    targets.retain(|x| table.expected(*x).len() < inputs.len());
+    let mut found: BTreeMap<usize, Vec<Symbol>> = BTreeMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    queue.push_back((vec![0], Vec::new()));
+    seen.insert(vec![0]);
+    while let Some((states, prefix)) = queue.pop_front() {
+        let top = *states.last().unwrap();
+        if targets.contains(&top) && !found.contains_key(&top) {
+            let expected = table.expected(top);
+            let mut sentence: Vec<Symbol> = prefix.clone();
+            if expected.contains(&Symbol::EOF()) {
+                let bad = inputs.iter().find(|x| !expected.contains(x)).unwrap();
+                sentence.push(*bad);
+            }
+            found.insert(top, sentence);
+            if found.len() == targets.len() {
+                break;
+            }
+        }
+        if seen.len() > SEARCH_LIMIT {
+            continue;
+        }
+        for symbol in table.expected(top) {
+            if symbol == Symbol::EOF() {
+                continue;
+            }
+            let mut next = states.clone();
+            if let Step::Shifted = lr_parser::advance(table, &mut next, &symbol) {
+                if seen.insert(next.clone()) {
+                    let mut prefix = prefix.clone();
+                    prefix.push(symbol);
+                    queue.push_back((next, prefix));
+                }
+            }
+        }
+    }
+    found.into_iter().collect()
+}
+
+//a messages file with an entry for every error state, ## notes say what the state expects
+//entries from existing are kept, so this also updates a file after the grammar changes
+pub fn list(table: &Table, existing: &Messages) -> String {
+    let cfg = &table.cfg;
+    let compiled = existing.compile(table);
+    let mut out = String::new();
+    let mut write_entry = |sentences: &[String], state: Option<usize>, message: &str| {
+        for sentence in sentences {
+            out.push_str(sentence);
+            out.push('\n');
+        }
+        if let Some(state) = state {
+            out.push_str(&format!(
+                "## fails in state {}, which expects {}\n",
+                state,
+                cfg.describe_list(&table.expected(state))
+            ));
+        }
+        out.push('\n');
+        out.push_str(message);
+        out.push_str("\n\n");
+    };
+    let state_of = |sentence: &str| {
+        read_sentence(cfg, sentence)
+            .ok()
+            .and_then(|tokens| error_state(table, &tokens))
+    };
+    //states with an entry already, even if it's a placeholder
+    let mut listed = HashSet::new();
+    for entry in &existing.entries {
+        let sentences: Vec<String> = entry.sentences.iter().map(|x| x.1.clone()).collect();
+        listed.extend(sentences.iter().filter_map(|x| state_of(x)));
+        write_entry(&sentences, state_of(&sentences[0]), &entry.message);
+    }
+    for problem in compiled.problems {
+        match problem {
+            MessageProblem::Missing(state, sentence) if !listed.contains(&state) => {
+                write_entry(&[sentence_str(cfg, &sentence)], Some(state), PLACEHOLDER)
+            }
+            _ => {}
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
@@ -0,0 +1,550 @@
+//generalized lr parsing for grammars whose tables have conflicts, tables come from lr1_generator::generate_glr
+//every action in a cell is followed at once on a graph structured stack, and all the parses share one
+//packed forest, so ambiguous input doesn't blow up into one stack or tree per parse
+//resource: Tomita, Efficient Parsing for Natural Language, and Scott & Johnstone, Right Nulled GLR Parsers
+
+use super::grammar::Symbol;
+use super::parse_table::{Table, TableCell};
+use super::parse_tree::{symbol_tokens, ParseError, ParseTree, Token};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+//one way a forest node was derived
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Packed {
+    pub production: usize,
+    //forest node ids, one per rhs symbol that isn't <empty>
+    pub children: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ForestNode {
+    pub symbol: Symbol,
+    //range of tokens covered, end exclusive, terminals and <eof> cover exactly their own token
+    pub start: usize,
+    pub end: usize,
+    //empty for terminals and <eof>, more than one when the input is ambiguous here
+    pub alternatives: Vec<Packed>,
+}
+
+//shared packed parse forest, nodes are unique per symbol and range
+pub struct Forest {
+    pub nodes: Vec<ForestNode>,
+    //start rule S' -> S <eof> over the whole input
+    pub root: usize,
+    //the input, with the end of input token on the end
+    pub tokens: Vec<Token>,
+}
+
+struct GssNode {
+    state: usize,
+    //number of tokens read when the node was pushed
+    level: usize,
+    //node below and the forest node for the symbol between them
+    edges: Vec<(usize, usize)>,
+}
+
+struct Glr<'a> {
+    table: &'a Table,
+    gss: Vec<GssNode>,
+    forest: Vec<ForestNode>,
+    forest_ids: HashMap<(Symbol, usize, usize), usize>,
+}
+
+impl Glr<'_> {
+    fn forest_node(&mut self, symbol: Symbol, start: usize, end: usize) -> usize {
+        let forest = &mut self.forest;
+        *self
+            .forest_ids
+            .entry((symbol, start, end))
+            .or_insert_with(|| {
+                forest.push(ForestNode {
+                    symbol,
+                    start,
+                    end,
+                    alternatives: Vec::new(),
+                });
+                forest.len() - 1
+            })
+    }
+
+    fn add_packed(&mut self, node: usize, packed: Packed) {
+        if !self.forest[node].alternatives.contains(&packed) {
+            self.forest[node].alternatives.push(packed);
+        }
+    }
+
+    //node in the level with the state, created if needed, true if it was created
+    fn node_in(&mut self, level: &mut Vec<usize>, state: usize, index: usize) -> (usize, bool) {
+        if let Some(node) = level.iter().find(|x| self.gss[**x].state == state) {
+            return (*node, false);
+        }
+        self.gss.push(GssNode {
+            state,
+            level: index,
+            edges: Vec::new(),
+        });
+        level.push(self.gss.len() - 1);
+        (self.gss.len() - 1, true)
+    }
+
+    //every path of len edges down from node: the node it ends on and the forest labels, left to right
+    fn paths(&self, node: usize, len: usize) -> Vec<(usize, Vec<usize>)> {
+        let mut paths = vec![(node, Vec::new())];
+        for _ in 0..len {
+            paths = paths
+                .iter()
+                .flat_map(|(n, labels)| {
+                    self.gss[*n].edges.iter().map(move |(below, label)| {
+                        let mut labels = labels.clone();
+                        labels.push(*label);
+                        (*below, labels)
+                    })
+                })
+                .collect();
+        }
+        for path in paths.iter_mut() {
+            path.1.reverse();
+        }
+        paths
+    }
+
+    //does every reduction possible on the lookahead, until no new stack node or edge shows up
+    //reductions are redone from the start each round, since a new edge can open new paths
+    //for nodes that were already reduced
+    fn reduce_all(&mut self, level: &mut Vec<usize>, index: usize, lookahead: Symbol) {
+        let cfg = &self.table.cfg;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            let mut k = 0;
+            while k < level.len() {
+                let node = level[k];
+                k += 1;
+                for action in self.table.rows[self.gss[node].state].actions(&lookahead) {
+                    let production = match action {
+                        TableCell::Reduce(p) => p,
+                        _ => continue,
+                    };
+                    let nonterminal = cfg.production(production).nonterminal;
+                    for (below, children) in self.paths(node, cfg.production(production).rhs_len())
+                    {
+                        let below_row = &self.table.rows[self.gss[below].state];
+                        let goto = match below_row.cells.get(&Symbol::Nonterminal(nonterminal)) {
+                            Some(TableCell::Goto(s)) => *s,
+                            _ => continue,
+                        };
+                        let start = self.gss[below].level;
+                        let label =
+                            self.forest_node(Symbol::Nonterminal(nonterminal), start, index);
+                        self.add_packed(
+                            label,
+                            Packed {
+                                production,
+                                children,
+                            },
+                        );
+                        let (top, created) = self.node_in(level, goto, index);
+                        changed |= created;
+                        if !self.gss[top].edges.contains(&(below, label)) {
+                            self.gss[top].edges.push((below, label));
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn expected(&self, level: &[usize]) -> Vec<Symbol> {
+        let mut expected: Vec<Symbol> = level
+            .iter()
+            .flat_map(|node| self.table.expected(self.gss[*node].state))
+            .collect();
+        expected.sort();
+        expected.dedup();
+        expected
+    }
+}
+
+//breadth first search for sentences the table accepts, shortest first. found gets each one with its
+//forest and returns false to stop. a sentence is extended by each symbol of alphabet while it's
+//shorter than max_length and could still start one that parses, tokens turns it into the input
+//true if every sentence was looked at, false if found stopped it or max_parses ran out
+pub fn search_sentences<T, F>(
+    table: &Table,
+    start: Vec<Symbol>,
+    alphabet: &[Symbol],
+    max_length: usize,
+    max_parses: usize,
+    tokens: T,
+    mut found: F,
+) -> bool
+where
+    T: Fn(&[Symbol]) -> Vec<Token>,
+    F: FnMut(&[Symbol], Forest) -> bool,
+{
+    let mut queue = VecDeque::from(vec![start]);
+    let mut parses = 0;
+    while let Some(sentence) = queue.pop_front() {
+        if parses >= max_parses {
+            return false;
+        }
+        parses += 1;
+        let viable = match parse(table, &tokens(&sentence)) {
+            Ok(forest) => {
+                if !found(&sentence, forest) {
+                    return false;
+                }
+                true
+            }
+            //failing at the end means a longer sentence could still parse
+            Err(ParseError::Unexpected(token, _)) => token.symbol == Symbol::EOF(),
+            Err(_) => false,
+        };
+        if viable && sentence.len() < max_length {
+            for symbol in alphabet {
+                let mut longer = sentence.clone();
+                longer.push(*symbol);
+                queue.push_back(longer);
+            }
+        }
+    }
+    true
+}
+
+//search_sentences over the terminals of the table's grammar, starting from the empty sentence
+pub fn viable_sentences<F>(table: &Table, max_length: usize, max_parses: usize, found: F) -> bool
+where
+    F: FnMut(&[Symbol], Forest) -> bool,
+{
+    let cfg = &table.cfg;
+    search_sentences(
+        table,
+        Vec::new(),
+        &cfg.input_terminals(),
+        max_length,
+        max_parses,
+        |sentence| symbol_tokens(cfg, sentence),
+        found,
+    )
+}
+
+//tokens shouldn't include the end of input, it's added here
+pub fn parse(table: &Table, tokens: &[Token]) -> Result<Forest, ParseError> {
+    let mut tokens = tokens.to_vec();
+    tokens.push(Token::eof_after(&tokens));
+    let mut glr = Glr {
+        table,
+        gss: vec![GssNode {
+            state: 0,
+            level: 0,
+            edges: Vec::new(),
+        }],
+        forest: Vec::new(),
+        forest_ids: HashMap::new(),
+    };
+    let mut level = vec![0];
+    for (index, token) in tokens.iter().enumerate() {
+        glr.reduce_all(&mut level, index, token.symbol);
+        if token.symbol == Symbol::EOF() {
+            if let Some(root) = accept(&mut glr, &level, index) {
+                return Ok(Forest {
+                    nodes: glr.forest,
+                    root,
+                    tokens,
+                });
+            }
+            return Err(ParseError::Unexpected(token.clone(), glr.expected(&level)));
+        }
+        let mut next = Vec::new();
+        for node in level.iter() {
+            for action in table.rows[glr.gss[*node].state].actions(&token.symbol) {
+                if let TableCell::Shift(state) = action {
+                    let leaf = glr.forest_node(token.symbol, index, index + 1);
+                    let (top, _) = glr.node_in(&mut next, state, index + 1);
+                    if !glr.gss[top].edges.contains(&(*node, leaf)) {
+                        glr.gss[top].edges.push((*node, leaf));
+                    }
+                }
+            }
+        }
+        if next.is_empty() {
+            return Err(ParseError::Unexpected(token.clone(), glr.expected(&level)));
+        }
+        level = next;
+    }
+    unreachable!("the end of input is never shifted")
+}
+
+//builds the S' node from every stack that can accept, eof is the token at index
+fn accept(glr: &mut Glr, level: &[usize], index: usize) -> Option<usize> {
+    let cfg = &glr.table.cfg;
+    let accepting: Vec<usize> = level
+        .iter()
+        .filter(|node| {
+            glr.table.rows[glr.gss[**node].state]
+                .actions(&Symbol::EOF())
+                .contains(&TableCell::Accept())
+        })
+        .copied()
+        .collect();
+    if accepting.is_empty() {
+        return None;
+    }
+    let root = glr.forest_node(Symbol::Nonterminal(0), 0, index + 1);
+    let eof = glr.forest_node(Symbol::EOF(), index, index + 1);
+    for node in accepting {
+        for production in &cfg.productions[0] {
+            let symbols: Vec<Symbol> = production
+                .rhs
+                .iter()
+                .filter(|x| **x != Symbol::Empty())
+                .copied()
+                .collect();
+            //every start rule ends with <eof>, which is still the lookahead
+            for (below, mut children) in glr.paths(node, symbols.len() - 1) {
+                let matches = below == 0
+                    && children
+                        .iter()
+                        .zip(&symbols)
+                        .all(|(child, symbol)| glr.forest[*child].symbol == *symbol);
+                if matches {
+                    children.push(eof);
+                    let packed = Packed {
+                        production: cfg.production_id(production),
+                        children,
+                    };
+                    glr.add_packed(root, packed);
+                }
+            }
+        }
+    }
+    Some(root)
+}
+
+impl Forest {
+    fn is_leaf(&self, node: usize) -> bool {
+        !matches!(self.nodes[node].symbol, Symbol::Nonterminal(_))
+    }
+
+    fn leaf(&self, node: usize) -> ParseTree {
+        ParseTree::Leaf(self.tokens[self.nodes[node].start].clone())
+    }
+
+    fn reachable(&self) -> Vec<usize> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = vec![self.root];
+        seen[self.root] = true;
+        let mut reachable = Vec::new();
+        while let Some(node) = stack.pop() {
+            reachable.push(node);
+            for child in self.nodes[node]
+                .alternatives
+                .iter()
+                .flat_map(|a| &a.children)
+            {
+                if !seen[*child] {
+                    seen[*child] = true;
+                    stack.push(*child);
+                }
+            }
+        }
+        reachable.sort_unstable();
+        reachable
+    }
+
+    //nodes with more than one derivation, in the order they were created
+    pub fn ambiguities(&self) -> Vec<usize> {
+        self.reachable()
+            .into_iter()
+            .filter(|x| self.nodes[*x].alternatives.len() > 1)
+            .collect()
+    }
+
+    pub fn is_ambiguous(&self) -> bool {
+        !self.ambiguities().is_empty()
+    }
+
+    //number of distinct trees, None if there are infinitely many (a cycle like A -> A)
+    //saturates at usize::MAX
+    pub fn count_trees(&self) -> Option<usize> {
+        //None while the node is being counted
+        fn count(
+            forest: &Forest,
+            node: usize,
+            memo: &mut HashMap<usize, Option<usize>>,
+        ) -> Option<usize> {
+            match memo.get(&node) {
+                Some(Some(n)) => return Some(*n),
+                Some(None) => return None,
+                None => {}
+            }
+            if forest.is_leaf(node) {
+                return Some(1);
+            }
+            memo.insert(node, None);
+            let mut total: usize = 0;
+            for alternative in &forest.nodes[node].alternatives {
+                let mut product: usize = 1;
+                for child in &alternative.children {
+                    product = product.saturating_mul(count(forest, *child, memo)?);
+                }
+                total = total.saturating_add(product);
+            }
+            memo.insert(node, Some(total));
+            Some(total)
+        }
+        count(self, self.root, &mut HashMap::new())
+    }
+
+    //up to limit trees, derivations that loop back on themselves are skipped
+    pub fn trees(&self, limit: usize) -> Vec<ParseTree> {
+        let mut path = HashSet::new();
+        self.enumerate(self.root, &mut path, limit)
+    }
+
+    fn enumerate(&self, node: usize, path: &mut HashSet<usize>, limit: usize) -> Vec<ParseTree> {
+        if self.is_leaf(node) {
+            return vec![self.leaf(node)];
+        }
+        path.insert(node);
+        let mut trees = Vec::new();
+        for alternative in &self.nodes[node].alternatives {
+            if trees.len() >= limit {
+                break;
+            }
+            if alternative.children.iter().any(|x| path.contains(x)) {
+                continue;
+            }
+            let mut partial: Vec<Vec<ParseTree>> = vec![Vec::new()];
+            for child in &alternative.children {
+                let options = self.enumerate(*child, path, limit);
+                let mut next = Vec::new();
+                'outer: for prefix in &partial {
+                    for option in &options {
+                        if next.len() >= limit {
+                            break 'outer;
+                        }
+                        let mut children = prefix.clone();
+                        children.push(option.clone());
+                        next.push(children);
+                    }
+                }
+                partial = next;
+            }
+            for children in partial {
+                if trees.len() < limit {
+                    trees.push(ParseTree::Node(alternative.production, children));
+                }
+            }
+        }
+        path.remove(&node);
+        trees
+    }
+
+    //the only tree, None if the input was ambiguous
+    pub fn tree(&self) -> Option<ParseTree> {
+        match self.is_ambiguous() {
+            true => None,
+            false => Some(self.disambiguate(|_, _| 0)),
+        }
+    }
+
+    //builds one tree, choose picks which derivation to use at each ambiguous node by index
+    //alternatives that would loop back on the current derivation aren't offered
+    //example, prefer the earliest production: forest.disambiguate(|_, alts| (0..alts.len()).min_by_key(|i| alts[*i].production).unwrap())
+    pub fn disambiguate<F>(&self, mut choose: F) -> ParseTree
+    where
+        F: FnMut(&ForestNode, &[&Packed]) -> usize,
+    {
+        let heights = self.heights();
+        let mut path = HashSet::new();
+        self.extract(self.root, &mut path, &heights, false, &mut choose)
+    }
+
+    //height of the shortest derivation of each node, None for nodes that only derive through cycles
+    fn heights(&self) -> Vec<Option<usize>> {
+        let mut heights: Vec<Option<usize>> = (0..self.nodes.len())
+            .map(|x| match self.is_leaf(x) {
+                true => Some(0),
+                false => None,
+            })
+            .collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in 0..self.nodes.len() {
+                for alternative in &self.nodes[node].alternatives {
+                    let height = alternative
+                        .children
+                        .iter()
+                        .map(|x| heights[*x])
+                        .try_fold(0, |max, h| h.map(|h| max.max(h + 1)));
+                    if let Some(h) = height {
+                        if heights[node].map(|x| h < x).unwrap_or(true) {
+                            heights[node] = Some(h);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        heights
+    }
+
+    //shortest: only follow the shortest derivations, which always terminates
+    fn extract<F>(
+        &self,
+        node: usize,
+        path: &mut HashSet<usize>,
+        heights: &[Option<usize>],
+        shortest: bool,
+        choose: &mut F,
+    ) -> ParseTree
+    where
+        F: FnMut(&ForestNode, &[&Packed]) -> usize,
+    {
+        if self.is_leaf(node) {
+            return self.leaf(node);
+        }
+        let usable = |a: &&Packed| {
+            a.children
+                .iter()
+                .all(|x| !path.contains(x) && heights[*x].is_some())
+        };
+        let candidates: Vec<&Packed> = self.nodes[node]
+            .alternatives
+            .iter()
+            .filter(usable)
+            .collect();
+        let (chosen, shortest) = match candidates.len() {
+            _ if shortest || candidates.is_empty() => {
+                let height = |a: &&Packed| {
+                    a.children
+                        .iter()
+                        .map(|x| heights[*x].unwrap_or(usize::MAX))
+                        .max()
+                        .unwrap_or(0)
+                };
+                (
+                    self.nodes[node].alternatives.iter().min_by_key(height),
+                    true,
+                )
+            }
+            1 => (Some(candidates[0]), false),
+            _ => (
+                Some(candidates[choose(&self.nodes[node], &candidates)]),
+                false,
+            ),
+        };
+        let chosen = chosen.expect("forest nodes have at least one derivation");
+        path.insert(node);
+        let children = chosen
+            .children
+            .iter()
+            .map(|x| self.extract(*x, path, heights, shortest, choose))
+            .collect();
+        path.remove(&node);
+        ParseTree::Node(chosen.production, children)
+    }
+}
@@ -1,6 +1,13 @@
 use std::collections::{HashMap, HashSet};
 
-#[derive(Eq, PartialEq, Hash, Copy, Clone)]
+pub mod builder;
+pub mod sentences;
+pub mod transform;
+
+use builder::GrammarError;
+pub use sentences::generate_sentences;
+
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Debug)]
 pub enum Symbol {
     Terminal(usize),
     Nonterminal(usize),
@@ -8,309 +15,292 @@ pub enum Symbol {
     EOF(),
 }
 
-pub enum FirstSet<'a> {
-    Other(Symbol),
-    Nonterminal(&'a HashSet<Symbol>),
-}
-
-#[derive(Eq, PartialEq, Hash)]
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
 pub struct CFGProduction {
     pub nonterminal: usize,
     pub rhs: Vec<Symbol>,
 }
 
-pub struct CFG<'a> {
-    //first vec: lhs id, next vec: list of productions where that nonterminal is the lhs
-    pub productions: Vec<Vec<CFGProduction>>,
-    pub nonterminal_symbols: Vec<&'a str>,
-    pub terminal_symbols: Vec<&'a str>,
+impl CFGProduction {
+    //symbols a parser pops when reducing, <empty> doesn't count
+    pub fn rhs_len(&self) -> usize {
+        self.rhs.iter().filter(|x| **x != Symbol::Empty()).count()
+    }
 }
 
-fn set_has_nonterm(sets: &Vec<HashSet<Symbol>>) -> bool {
-    for set in sets {
-        for symbol in set {
-            match symbol {
-                Symbol::Nonterminal(_) => return true,
-                _ => (),
-            };
+//interned symbol names, ids are handed out in order starting at 0
+#[derive(Clone, Default, Debug)]
+pub struct SymbolTable {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable::default()
+    }
+    //id of name, registering it if it's new
+    pub fn intern(&mut self, name: &str) -> usize {
+        match self.ids.get(name) {
+            Some(id) => *id,
+            None => {
+                let id = self.names.len();
+                self.names.push(String::from(name));
+                self.ids.insert(String::from(name), id);
+                id
+            }
         }
     }
-    false
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|x| x.as_str())
+    }
 }
 
-impl CFG<'_> {
-    //generate the first set for each nonterminal in a CFG.
-    //example: if A-> b|c|De and D->d|<empty>, first(A)={b,c,d,e}
-    //return- first vec is indexed by nonterminal id, second vec contains list of terminals in the first set
-    pub fn generate_firsts(&self) -> Vec<HashSet<Symbol>> {
-        let mut firsts = Vec::new();
-        //prepopulate list
-        for nt in 0..self.nonterminal_symbols.len() {
-            firsts = self.generate_first_from_prods(nt, firsts);
+impl std::ops::Index<usize> for SymbolTable {
+    type Output = str;
+    fn index(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+}
+
+//the id map is derived from the names
+impl PartialEq for SymbolTable {
+    fn eq(&self, other: &SymbolTable) -> bool {
+        self.names == other.names
+    }
+}
+
+impl Eq for SymbolTable {}
+
+impl<'a> IntoIterator for &'a SymbolTable {
+    type Item = &'a str;
+    type IntoIter = std::iter::Map<std::slice::Iter<'a, String>, fn(&String) -> &str>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.names.iter().map(|x| x.as_str())
+    }
+}
+
+impl<'a> std::iter::FromIterator<&'a str> for SymbolTable {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> SymbolTable {
+        let mut table = SymbolTable::new();
+        for name in iter {
+            table.intern(name);
         }
-        //we do passes until all nonterminals are eliminated from the first sets
-        let mut iter = 0;
-        loop {
-            iter += 1;
-            for nonterminal in 0..self.nonterminal_symbols.len() {
-                let (to_add, to_remove) = self.generate_first_nonterm(nonterminal, &firsts);
-                for item in to_remove {
-                    firsts[nonterminal].remove(&item);
-                }
-                for item in to_add {
-                    firsts[nonterminal].insert(item);
+        table
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CFG {
+    //first vec: lhs id, next vec: list of productions where that nonterminal is the lhs
+    pub productions: Vec<Vec<CFGProduction>>,
+    pub nonterminal_symbols: SymbolTable,
+    pub terminal_symbols: SymbolTable,
+    //friendlier names for terminals in error messages, by terminal id
+    pub aliases: HashMap<usize, String>,
+}
+
+//splits the text of a grammar file into the production strings from_strings expects
+//blank lines and lines starting with # are skipped
+pub fn grammar_lines(text: &str) -> Vec<String> {
+    numbered_lines(text)
+        .into_iter()
+        .map(|(_, x)| String::from(x))
+        .collect()
+}
+
+//grammar_lines with the 1 based line each one came from, for error messages
+fn numbered_lines(text: &str) -> Vec<(usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, x)| (i + 1, x.trim()))
+        .filter(|(_, x)| !x.is_empty() && !x.starts_with('#'))
+        .collect()
+}
+
+impl CFG {
+    //nonterminals that can derive the empty string, indexed by nonterminal id
+    pub fn nullable(&self) -> Vec<bool> {
+        let mut nullable = vec![false; self.nonterminal_symbols.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for prod in self.productions.iter().flatten() {
+                if !nullable[prod.nonterminal]
+                    && prod.rhs.iter().all(|x| match x {
+                        Symbol::Empty() => true,
+                        Symbol::Nonterminal(n) => nullable[*n],
+                        _ => false,
+                    })
+                {
+                    nullable[prod.nonterminal] = true;
+                    changed = true;
                 }
             }
-            if !set_has_nonterm(&firsts) || iter > 100 {
-                println!("broken iter={}", iter);
-                break;
-            }
         }
-        //transform firsts into desired structure
-        firsts
+        nullable
     }
-    //generates the first set for a specific nonterminal from it's productions
-    //this is the set of possible first terminals for a derviation of the nonterminal
-    //this function is only one step of generate_firsts, it will not remove nonterminals in the resulting set
-    fn generate_first_from_prods(
-        &self,
-        nonterminal: usize,
-        mut firsts: Vec<HashSet<Symbol>>,
-    ) -> Vec<HashSet<Symbol>> {
-        let mut my_firsts = HashSet::new();
-        for prod in &self.productions[nonterminal] {
-            for symbol in &prod.rhs {
-                match symbol {
-                    Symbol::Empty() => {
-                        my_firsts.insert(*symbol);
-                    }
-                    _ => {
-                        my_firsts.insert(*symbol);
-                        break;
-                    }
+    //generate the first set for each nonterminal in a CFG.
+    //example: if A-> b|c|De and D->d|<empty>, first(A)={b,c,d,e}
+    //return- first vec is indexed by nonterminal id, second vec contains list of terminals in the first set
+    //nullable nonterminals also have <empty> in their set
+    pub fn generate_firsts(&self) -> Vec<HashSet<Symbol>> {
+        let mut firsts = vec![HashSet::new(); self.nonterminal_symbols.len()];
+        //we do passes until no first set grows
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for prod in self.productions.iter().flatten() {
+                for symbol in self.first_of_sequence(&prod.rhs, &firsts) {
+                    changed |= firsts[prod.nonterminal].insert(symbol);
                 }
             }
         }
-        firsts.push(my_firsts);
         firsts
     }
-    //loops through each inprogress first set and replaces nonterminals with their first sets
-    fn generate_first_nonterm(
+
+    //first set of a string of symbols, contains <empty> if every symbol in it is nullable
+    //example "aBc" -> {a}, "Bc" -> {a,b,c} if B -> a|b|<empty>, "B" -> {a,b,<empty>}
+    pub fn first_of_sequence(
         &self,
-        nonterminal: usize,
-        firsts: &Vec<HashSet<Symbol>>,
-    ) -> (Vec<Symbol>, Vec<Symbol>) {
-        let mut to_add = Vec::new();
-        let mut to_remove = Vec::new();
-        for symbol in &firsts[nonterminal] {
+        string: &[Symbol],
+        firsts: &[HashSet<Symbol>],
+    ) -> HashSet<Symbol> {
+        let mut result = HashSet::new();
+        for symbol in string {
             match symbol {
+                Symbol::Empty() => continue,
                 Symbol::Nonterminal(x) => {
-                    to_remove.push(*symbol);
-                    if *x != nonterminal {
-                        //don't want to add my first set to itself
-                        for other_item in &firsts[*x] {
-                            match other_item {
-                                Symbol::Empty() => {
-                                    //if the other nonterminal conatins the empty string, we need to add our own first set to it
-                                    for my_item in &firsts[nonterminal] {
-                                        to_add.push(*my_item);
-                                    }
-                                }
-                                _ => (),
-                            }
-                            to_add.push(*other_item);
-                        }
+                    result.extend(firsts[*x].iter().filter(|s| **s != Symbol::Empty()));
+                    if !firsts[*x].contains(&Symbol::Empty()) {
+                        return result;
                     }
                 }
-                _ => (),
+                _ => {
+                    result.insert(*symbol);
+                    return result;
+                }
             }
         }
-        return (to_add, to_remove);
+        result.insert(Symbol::Empty());
+        result
     }
 
-    //generates the first set for a string of symbols
-    //this is the set of possible first terminals in the derived string
-    //example "aBc" -> {a}, "<empty>aBc" -> {a}, "Bc" -> {a,b,c} if B -> a|b|<empty>, "<empty><empty><empty>" -> None
-    pub fn get_first<'a>(
+    //first set of the string followed by lookahead, this is first(string lookahead) for lr(1) items
+    //never contains <empty>, since lookahead can't derive it
+    pub fn first_of_string(
         &self,
         string: &[Symbol],
-        firsts: &'a Vec<HashSet<Symbol>>,
-    ) -> Option<FirstSet<'a>> {
-        match string.first() {
-            Some(symbol) => match symbol {
-                Symbol::Terminal(_) | Symbol::EOF() => Option::Some(FirstSet::Other(*symbol)),
-                Symbol::Nonterminal(x) => Option::Some(FirstSet::Nonterminal(&firsts[*x])),
-                Symbol::Empty() => self.get_first(&string[1..], firsts),
-            },
-            None => Option::None,
+        firsts: &[HashSet<Symbol>],
+        lookahead: Symbol,
+    ) -> HashSet<Symbol> {
+        let mut result = self.first_of_sequence(string, firsts);
+        if result.remove(&Symbol::Empty()) {
+            result.insert(lookahead);
         }
+        result
     }
 
     //generate the follow set for each nonterminal in a CFG.
     //example: if A-> b|c|De and D->d|<empty>, follow(D)={e,d}
     //return- first vec is indexed by nonterminal id, second vec contains list of terminals in the first set
-    pub fn generate_follows(&self, firsts: &Vec<HashSet<Symbol>>) -> Vec<HashSet<Symbol>> {
-        let mut follows = Vec::new();
-        //populate follow sets
-        for _ in 0..self.nonterminal_symbols.len() {
-            follows.push(HashSet::new());
-        }
+    pub fn generate_follows(&self, firsts: &[HashSet<Symbol>]) -> Vec<HashSet<Symbol>> {
+        let mut follows = vec![HashSet::new(); self.nonterminal_symbols.len()];
         //1. first rule is always S' -> S <eof>, so follow(S') = <eof>
         follows[0].insert(Symbol::EOF());
-        let mut keep_going = true;
-        let mut iter = 0;
-        while keep_going && iter < 100 {
-            keep_going = false;
-            //repeat these steps until no follow set grows
-            //2. for each production A-> (stuff1)X(stuff2) ...
-            //if <empty> is in first(stuff2), add [first(stuff2) union follow(A)] - <empty> to follow (x)
-            //otherwise, add first(stuff2) to follow(X)
-            for lhs in &self.productions {
-                for production in lhs {
-                    for index in 0..production.rhs.len() {
-                        let symbol = production.rhs[index];
-                        match symbol {
-                            Symbol::Nonterminal(x) => {
-                                let ret = self
-                                    .add_follow_nonterminal(follows, firsts, production, x, index);
-                                follows = ret.1;
-                                if ret.0 {
-                                    keep_going = true;
-                                }
-                            }
-                            _ => (),
+        //repeat these steps until no follow set grows
+        //2. for each production A-> (stuff1)X(stuff2) ...
+        //if <empty> is in first(stuff2), add [first(stuff2) union follow(A)] - <empty> to follow (x)
+        //otherwise, add first(stuff2) to follow(X)
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in self.productions.iter().flatten() {
+                for (index, symbol) in production.rhs.iter().enumerate() {
+                    if let Symbol::Nonterminal(x) = symbol {
+                        let mut beta = self.first_of_sequence(&production.rhs[index + 1..], firsts);
+                        if beta.remove(&Symbol::Empty()) {
+                            beta.extend(follows[production.nonterminal].iter().copied());
                         }
-                    }
-                }
-            }
-            iter += 1;
-        }
-        println!("iter={}", iter);
-        follows
-    }
-
-    //returns (bool: was anything added?, updated follow sets)
-    fn add_follow_nonterminal(
-        &self,
-        mut follows: Vec<HashSet<Symbol>>,
-        firsts: &Vec<HashSet<Symbol>>,
-        production: &CFGProduction,
-        nonterm_id: usize,
-        symbol_index: usize,
-    ) -> (bool, Vec<HashSet<Symbol>>) {
-        let mut added = false;
-        let beta_first = self.get_first(
-            &production.rhs[(symbol_index + 1)..production.rhs.len()],
-            firsts,
-        );
-        let mut add_lhs = false;
-        match beta_first {
-            Some(FirstSet::Nonterminal(set)) => {
-                for symbol in set {
-                    match symbol {
-                        Symbol::Empty() => {
-                            add_lhs = true;
-                        }
-                        _ => {
-                            if follows[nonterm_id].insert(*symbol) {
-                                added = true;
-                            }
+                        for symbol in beta {
+                            changed |= follows[*x].insert(symbol);
                         }
                     }
                 }
             }
-            Some(FirstSet::Other(symbol)) => match symbol {
-                Symbol::Empty() => (),
-                _ => {
-                    if follows[nonterm_id].insert(symbol) {
-                        added = true;
-                    }
-                }
-            },
-            None => add_lhs = true,
         }
-
-        if add_lhs && production.nonterminal != nonterm_id {
-            //brazy borrow checker stuff
-            let prod: &mut std::collections::HashSet<Symbol>;
-            let mine: &mut std::collections::HashSet<Symbol>;
-            if production.nonterminal > nonterm_id {
-                let tmp = follows.split_at_mut(production.nonterminal);
-                prod = &mut tmp.1[0];
-                mine = &mut tmp.0[nonterm_id];
-            } else {
-                let tmp = follows.split_at_mut(nonterm_id);
-                prod = &mut tmp.0[production.nonterminal];
-                mine = &mut tmp.1[0];
-            }
-            //have to add follow(LHS) to follow(symbol)
-            for symbol in prod.iter() {
-                match symbol {
-                    Symbol::Empty() => (),
-                    _ => {
-                        if mine.insert(*symbol) {
-                            added = true;
-                        }
-                    }
-                }
-            }
-        }
-        //added
-        //
-        (added, follows)
+        follows
     }
 
     //start state is first item in strings
-    //terminals start with dot
+    //terminals start with dot, <error> is the terminal for error rules
     //nonterminals are anything not starting with dot
     //whitespace separating each element
     //each entry in strings is a production: term -> .number .* term
-    fn register<'a>(k: &'a str, last: &mut usize, m: &mut HashMap<&'a str, usize>) -> usize {
-        match m.get(&k) {
-            Some(n) => *n,
-            None => {
-                *last += 1;
-                let v = *last;
-                m.insert(k, v);
-                v
-            }
-        }
-    }
-    //assuming valid grammar with all nonterminals having at least 1 production
+    //or an alias for a terminal: .number = "a number", the quotes are optional
+    //every nonterminal needs a production, one that only appears on a rhs is an error
     //start rule is S'-> S where S is the first nonterminal
-    pub fn from_strings(strings: &[String]) -> CFG {
+    //errors say which string they're in, counting from 1
+    pub fn from_strings(strings: &[String]) -> Result<CFG, GrammarError> {
+        let lines: Vec<(usize, &str)> = strings
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (i + 1, x.as_str()))
+            .collect();
+        CFG::from_lines(&lines)
+    }
+
+    fn from_lines(lines: &[(usize, &str)]) -> Result<CFG, GrammarError> {
+        let at = |line: usize, error: GrammarError| GrammarError::AtLine(line, Box::new(error));
         struct RHS<'a> {
+            line: usize,
             prod: CFGProduction,
             iter: std::str::SplitWhitespace<'a>,
         }
         let mut productions: Vec<Vec<CFGProduction>> = Vec::new();
         productions.push(Vec::new()); //for the created start rule
-        let mut nt_map: HashMap<&str, usize> = HashMap::new(); //0 is reserved for created start rule: S' -> S$
-        let mut nt_symbols: Vec<&str> = Vec::new();
-        //set up start rule
-        nt_symbols.push("S'");
+        let mut nt_symbols = SymbolTable::new();
+        //0 is reserved for created start rule: S' -> S$
+        nt_symbols.intern(builder::START_SYMBOL);
         productions[0].push(CFGProduction {
             nonterminal: 0,
             rhs: vec![Symbol::Nonterminal(1), Symbol::EOF()],
         });
-        let mut nt_last: usize = 0;
-        let mut t_map: HashMap<&str, usize> = HashMap::new();
-        let mut t_symbols: Vec<&str> = Vec::new();
-        let mut t_last: usize = 0;
+        let mut t_symbols = SymbolTable::new();
         let mut rh_sides: Vec<RHS> = Vec::new();
-        for string in strings {
+        let mut alias_lines: Vec<(usize, &str, &str)> = Vec::new();
+        for (line, string) in lines {
+            if let Some((terminal, alias)) = string.split_once('=') {
+                let terminal = terminal.trim();
+                if terminal.starts_with('.') && !terminal.contains(char::is_whitespace) {
+                    alias_lines.push((*line, terminal, alias.trim()));
+                    continue;
+                }
+            }
             let mut iter = string.split_whitespace();
             //LHS
-            let lhs;
-            match iter.next() {
-                Some(s) => {
-                    lhs = CFG::register(s, &mut nt_last, &mut nt_map);
-                    if lhs >= productions.len() {
-                        productions.push(Vec::new());
-                        nt_symbols.push(s);
-                    }
+            let lhs = match iter.next() {
+                Some(builder::START_SYMBOL) => {
+                    let name = String::from(builder::START_SYMBOL);
+                    return Err(at(*line, GrammarError::ReservedName(name)));
                 }
-                None => panic!("No LHS!"),
+                Some(s) => nt_symbols.intern(s),
+                None => return Err(at(*line, GrammarError::ExpectedArrow())),
+            };
+            if lhs >= productions.len() {
+                productions.push(Vec::new());
             }
             let prod = CFGProduction {
                 nonterminal: lhs,
@@ -319,40 +309,104 @@ impl CFG<'_> {
             //arrow
             match iter.next() {
                 Some("->") => {}
-                _ => panic!("Expected ->"),
+                _ => return Err(at(*line, GrammarError::ExpectedArrow())),
             };
             rh_sides.push(RHS {
-                prod: prod,
-                iter: iter,
+                line: *line,
+                prod,
+                iter,
             });
         }
+        //S' -> S needs an S
+        if rh_sides.is_empty() {
+            return Err(GrammarError::NoNonterminals());
+        }
+        //first nonterminal used without productions of its own, and the line it's on
+        let mut undefined = None;
         //RHS
         for mut rhs in rh_sides {
             for elem in rhs.iter {
                 let symbol: Symbol = match elem.chars().next() {
                     Some('<') if elem == "<eof>" => Symbol::EOF(),
                     Some('<') if elem == "<empty>" => Symbol::Empty(),
-                    Some('.') => {
-                        let n = CFG::register(&elem, &mut t_last, &mut t_map);
-                        if n == t_last {
-                            //register in terminal symbol table
-                            t_symbols.push(elem);
+                    Some('<') if elem == builder::ERROR_SYMBOL => {
+                        Symbol::Terminal(t_symbols.intern(elem))
+                    }
+                    Some('.') => Symbol::Terminal(t_symbols.intern(elem)),
+                    _ if elem == builder::START_SYMBOL => {
+                        let name = String::from(builder::START_SYMBOL);
+                        return Err(at(rhs.line, GrammarError::ReservedName(name)));
+                    }
+                    _ => {
+                        let id = nt_symbols.intern(elem);
+                        if id >= productions.len() && undefined.is_none() {
+                            undefined = Some((rhs.line, String::from(elem)));
                         }
-                        Symbol::Terminal(n - 1) //n is one higher than the corresponding string
+                        Symbol::Nonterminal(id)
                     }
-                    None => panic!("No char!"), //empty somehow
-                    _ => Symbol::Nonterminal(CFG::register(elem, &mut nt_last, &mut nt_map)), //nonterminal
                 };
                 rhs.prod.rhs.push(symbol);
             }
             //combine into ruleset
             productions[rhs.prod.nonterminal].push(rhs.prod);
         }
-        return CFG {
-            productions: productions,
+        //probably a misspelling, like GrammarBuilder this is an error
+        if let Some((line, name)) = undefined {
+            return Err(at(line, GrammarError::NoRules(name)));
+        }
+        //after the productions so aliases don't change terminal ids
+        let mut aliases = HashMap::new();
+        for (line, terminal, alias) in alias_lines {
+            let alias = match alias.len() >= 2 && alias.starts_with('"') && alias.ends_with('"') {
+                true => &alias[1..alias.len() - 1],
+                false => alias,
+            };
+            //a terminal no production uses, probably misspelled
+            let terminal = match t_symbols.get(terminal) {
+                Some(id) => id,
+                None => {
+                    let name = String::from(terminal);
+                    return Err(at(line, GrammarError::UnknownTerminal(name)));
+                }
+            };
+            aliases.insert(terminal, String::from(alias));
+        }
+        Ok(CFG {
+            productions,
             nonterminal_symbols: nt_symbols,
             terminal_symbols: t_symbols,
-        };
+            aliases,
+        })
+    }
+    //reads a grammar file, see grammar_lines for the format, errors have the line in the file
+    pub fn load(path: &std::path::Path) -> Result<CFG, GrammarError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| GrammarError::Unreadable(e.to_string()))?;
+        CFG::from_lines(&numbered_lines(&text))
+    }
+    //productions are numbered in the order of self.productions, this is what TableCell::Reduce refers to
+    pub fn production_id(&self, production: &CFGProduction) -> usize {
+        let mut id = 0;
+        for lhs in &self.productions {
+            for prod in lhs {
+                if std::ptr::eq(prod, production) {
+                    return id;
+                }
+                id += 1;
+            }
+        }
+        panic!("production is not part of this grammar")
+    }
+    //inverse of production_id
+    pub fn production(&self, id: usize) -> &CFGProduction {
+        self.productions
+            .iter()
+            .flatten()
+            .nth(id)
+            .expect("production id out of range")
+    }
+    pub fn production_count(&self) -> usize {
+        self.productions.iter().map(|x| x.len()).sum()
     }
     //pretty prints to stdout
     pub fn print(&self) {
@@ -365,16 +419,47 @@ impl CFG<'_> {
     }
     //prints to stdout
     pub fn print_production(&self, production: &CFGProduction) {
-        print!("{} ->", self.nonterminal_symbols[production.nonterminal]);
+        print!("{}", self.production_str(production));
+    }
+    //E -> E .+ T, like in a grammar file
+    pub fn production_str(&self, production: &CFGProduction) -> String {
+        let mut out = format!("{} ->", &self.nonterminal_symbols[production.nonterminal]);
         for symbol in production.rhs.iter() {
-            print!(" {}", self.symbol_str(symbol));
+            out.push(' ');
+            out.push_str(self.symbol_str(symbol));
+        }
+        out
+    }
+    //every terminal except <error>, which never comes from the input
+    pub fn input_terminals(&self) -> Vec<Symbol> {
+        let error = self.terminal_symbols.get(builder::ERROR_SYMBOL);
+        (0..self.terminal_symbols.len())
+            .filter(|x| Some(*x) != error)
+            .map(Symbol::Terminal)
+            .collect()
+    }
+    //name for error messages: the alias, or the name in backticks, or end of input
+    pub fn describe(&self, symbol: &Symbol) -> String {
+        match symbol {
+            Symbol::Terminal(x) if self.aliases.contains_key(x) => self.aliases[x].clone(),
+            Symbol::EOF() => String::from("end of input"),
+            _ => format!("`{}`", self.symbol_str(symbol)),
+        }
+    }
+    //`.+`, `.;` or end of input
+    pub fn describe_list(&self, symbols: &[Symbol]) -> String {
+        let names: Vec<String> = symbols.iter().map(|x| self.describe(x)).collect();
+        match names.split_last() {
+            None => String::new(),
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
         }
     }
     //returns a reference to the symbol's string
     pub fn symbol_str(&self, symbol: &Symbol) -> &str {
         match symbol {
-            Symbol::Terminal(x) => self.terminal_symbols[*x],
-            Symbol::Nonterminal(x) => self.nonterminal_symbols[*x],
+            Symbol::Terminal(x) => &self.terminal_symbols[*x],
+            Symbol::Nonterminal(x) => &self.nonterminal_symbols[*x],
             Symbol::Empty() => "<empty>",
             Symbol::EOF() => "<eof>",
         }
@@ -0,0 +1,269 @@
+//typed alternative to CFG::from_strings, names are used as given so terminals can hold
+//spaces, dots or anything else the string format can't express
+//
+//GrammarBuilder::new()
+//    .terminal("+")
+//    .terminal("id")
+//    .nonterminal("E")
+//    .rule("E", [nt("E"), t("+"), t("id")])
+//    .rule("E", [t("id")])
+//    .build()
+
+use super::{CFGProduction, Symbol, SymbolTable, CFG};
+use std::collections::HashMap;
+use std::fmt;
+
+//name of the nonterminal for the created start rule S' -> S <eof>
+pub const START_SYMBOL: &str = "S'";
+//terminal for yacc style error rules, Stmt -> <error> .; matches a broken statement
+//it's never in the input, the LR runtime shifts it while recovering from a syntax error
+pub const ERROR_SYMBOL: &str = "<error>";
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RuleSymbol {
+    Terminal(String),
+    Nonterminal(String),
+    Empty(),
+    EOF(),
+}
+
+pub fn t(name: &str) -> RuleSymbol {
+    RuleSymbol::Terminal(String::from(name))
+}
+
+pub fn nt(name: &str) -> RuleSymbol {
+    RuleSymbol::Nonterminal(String::from(name))
+}
+
+pub fn empty() -> RuleSymbol {
+    RuleSymbol::Empty()
+}
+
+pub fn eof() -> RuleSymbol {
+    RuleSymbol::EOF()
+}
+
+//declared on first use, see ERROR_SYMBOL
+pub fn error() -> RuleSymbol {
+    RuleSymbol::Terminal(String::from(ERROR_SYMBOL))
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum GrammarError {
+    EmptyName(),
+    //name is reserved for the created start rule
+    ReservedName(String),
+    DuplicateTerminal(String),
+    DuplicateNonterminal(String),
+    UnknownTerminal(String),
+    UnknownNonterminal(String),
+    //lhs, rule index within the lhs
+    DuplicateRule(String, usize),
+    //nonterminal that was declared but has no rules
+    NoRules(String),
+    NoNonterminals(),
+    //a production line without -> after its nonterminal
+    ExpectedArrow(),
+    //1 based line of a grammar file, or position in the strings given to CFG::from_strings
+    AtLine(usize, Box<GrammarError>),
+    //the grammar file couldn't be read
+    Unreadable(String),
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GrammarError::EmptyName() => write!(f, "symbol names can't be empty"),
+            GrammarError::ReservedName(name) => write!(f, "{} is reserved", name),
+            GrammarError::DuplicateTerminal(name) => {
+                write!(f, "terminal {} is declared twice", name)
+            }
+            GrammarError::DuplicateNonterminal(name) => {
+                write!(f, "nonterminal {} is declared twice", name)
+            }
+            GrammarError::UnknownTerminal(name) => write!(f, "unknown terminal {}", name),
+            GrammarError::UnknownNonterminal(name) => write!(f, "unknown nonterminal {}", name),
+            GrammarError::DuplicateRule(name, index) => {
+                write!(f, "rule {} of {} is a duplicate", index, name)
+            }
+            GrammarError::NoRules(name) => write!(f, "nonterminal {} has no rules", name),
+            GrammarError::NoNonterminals() => write!(f, "grammar has no nonterminals"),
+            GrammarError::ExpectedArrow() => write!(f, "expected -> after the nonterminal"),
+            GrammarError::AtLine(line, error) => write!(f, "line {}: {}", line, error),
+            GrammarError::Unreadable(message) => write!(f, "couldn't read the file: {}", message),
+        }
+    }
+}
+
+impl fmt::Debug for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+//every call is checked when it's made, the first error is kept and returned from build
+pub struct GrammarBuilder {
+    productions: Vec<Vec<CFGProduction>>,
+    nonterminal_symbols: SymbolTable,
+    terminal_symbols: SymbolTable,
+    aliases: HashMap<usize, String>,
+    start: Option<usize>,
+    error: Option<GrammarError>,
+}
+
+impl Default for GrammarBuilder {
+    fn default() -> GrammarBuilder {
+        GrammarBuilder::new()
+    }
+}
+
+impl GrammarBuilder {
+    pub fn new() -> GrammarBuilder {
+        let mut nonterminal_symbols = SymbolTable::new();
+        nonterminal_symbols.intern(START_SYMBOL);
+        GrammarBuilder {
+            //the start rule is filled in by build
+            productions: vec![Vec::new()],
+            nonterminal_symbols,
+            terminal_symbols: SymbolTable::new(),
+            aliases: HashMap::new(),
+            start: None,
+            error: None,
+        }
+    }
+
+    fn fail(mut self, error: GrammarError) -> GrammarBuilder {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+        self
+    }
+
+    pub fn terminal(mut self, name: &str) -> GrammarBuilder {
+        if name.is_empty() {
+            return self.fail(GrammarError::EmptyName());
+        }
+        if self.terminal_symbols.get(name).is_some() {
+            return self.fail(GrammarError::DuplicateTerminal(String::from(name)));
+        }
+        self.terminal_symbols.intern(name);
+        self
+    }
+
+    pub fn terminals<'a, I: IntoIterator<Item = &'a str>>(self, names: I) -> GrammarBuilder {
+        names.into_iter().fold(self, |b, name| b.terminal(name))
+    }
+
+    //name used for the terminal in error messages instead of `name`
+    pub fn alias(mut self, terminal: &str, alias: &str) -> GrammarBuilder {
+        match self.terminal_symbols.get(terminal) {
+            Some(id) => {
+                self.aliases.insert(id, String::from(alias));
+                self
+            }
+            None => self.fail(GrammarError::UnknownTerminal(String::from(terminal))),
+        }
+    }
+
+    //the first nonterminal declared is the start symbol unless start is called
+    pub fn nonterminal(mut self, name: &str) -> GrammarBuilder {
+        if name.is_empty() {
+            return self.fail(GrammarError::EmptyName());
+        }
+        if name == START_SYMBOL {
+            return self.fail(GrammarError::ReservedName(String::from(name)));
+        }
+        if self.nonterminal_symbols.get(name).is_some() {
+            return self.fail(GrammarError::DuplicateNonterminal(String::from(name)));
+        }
+        self.nonterminal_symbols.intern(name);
+        self.productions.push(Vec::new());
+        self
+    }
+
+    pub fn nonterminals<'a, I: IntoIterator<Item = &'a str>>(self, names: I) -> GrammarBuilder {
+        names.into_iter().fold(self, |b, name| b.nonterminal(name))
+    }
+
+    pub fn start(mut self, name: &str) -> GrammarBuilder {
+        match self.nonterminal_id(name) {
+            Ok(id) => {
+                self.start = Some(id);
+                self
+            }
+            Err(e) => self.fail(e),
+        }
+    }
+
+    //an empty rhs is the same as [empty()]
+    pub fn rule<I: IntoIterator<Item = RuleSymbol>>(mut self, lhs: &str, rhs: I) -> GrammarBuilder {
+        let nonterminal = match self.nonterminal_id(lhs) {
+            Ok(id) => id,
+            Err(e) => return self.fail(e),
+        };
+        let mut symbols = Vec::new();
+        for symbol in rhs {
+            let symbol = match symbol {
+                RuleSymbol::Terminal(name) => match self.terminal_symbols.get(&name) {
+                    Some(id) => Symbol::Terminal(id),
+                    None if name == ERROR_SYMBOL => {
+                        Symbol::Terminal(self.terminal_symbols.intern(ERROR_SYMBOL))
+                    }
+                    None => return self.fail(GrammarError::UnknownTerminal(name)),
+                },
+                RuleSymbol::Nonterminal(name) => match self.nonterminal_id(&name) {
+                    Ok(id) => Symbol::Nonterminal(id),
+                    Err(e) => return self.fail(e),
+                },
+                RuleSymbol::Empty() => Symbol::Empty(),
+                RuleSymbol::EOF() => Symbol::EOF(),
+            };
+            symbols.push(symbol);
+        }
+        if symbols.is_empty() {
+            symbols.push(Symbol::Empty());
+        }
+        let production = CFGProduction {
+            nonterminal,
+            rhs: symbols,
+        };
+        if self.productions[nonterminal].contains(&production) {
+            let index = self.productions[nonterminal].len();
+            return self.fail(GrammarError::DuplicateRule(String::from(lhs), index));
+        }
+        self.productions[nonterminal].push(production);
+        self
+    }
+
+    fn nonterminal_id(&self, name: &str) -> Result<usize, GrammarError> {
+        match self.nonterminal_symbols.get(name) {
+            Some(0) => Err(GrammarError::ReservedName(String::from(name))),
+            Some(id) => Ok(id),
+            None => Err(GrammarError::UnknownNonterminal(String::from(name))),
+        }
+    }
+
+    pub fn build(mut self) -> Result<CFG, GrammarError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        if self.nonterminal_symbols.len() < 2 {
+            return Err(GrammarError::NoNonterminals());
+        }
+        if let Some(id) = (1..self.productions.len()).find(|x| self.productions[*x].is_empty()) {
+            return Err(GrammarError::NoRules(String::from(
+                self.nonterminal_symbols.name(id),
+            )));
+        }
+        self.productions[0].push(CFGProduction {
+            nonterminal: 0,
+            rhs: vec![Symbol::Nonterminal(self.start.unwrap_or(1)), Symbol::EOF()],
+        });
+        Ok(CFG {
+            productions: self.productions,
+            nonterminal_symbols: self.nonterminal_symbols,
+            terminal_symbols: self.terminal_symbols,
+            aliases: self.aliases,
+        })
+    }
+}
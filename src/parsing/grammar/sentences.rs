@@ -0,0 +1,233 @@
+//sentences of a grammar's language, for fuzzing parsers and building test corpora
+//random sentences are reproducible from a seed, and with coverage on each nonterminal picks the
+//production used least so far, so a handful of sentences exercises every production it can reach.
+//exhaustive mode lists every sentence in order of derivation steps
+//
+//depth is how deep a derivation tree may nest, the start symbol is at depth 1. productions that
+//can't finish within the depth left are never picked, so every derivation ends
+
+use super::{Symbol, CFG};
+use crate::util::rng::Rng;
+use std::collections::{HashSet, VecDeque};
+
+pub enum SentenceMode {
+    //seed
+    Random(u64),
+    Exhaustive(),
+}
+
+pub struct SentenceOptions {
+    pub mode: SentenceMode,
+    //most sentences to return, fewer if the language up to max_depth runs out
+    pub count: usize,
+    pub max_depth: usize,
+    //random mode prefers the productions used least so far
+    pub coverage: bool,
+}
+
+impl Default for SentenceOptions {
+    fn default() -> Self {
+        SentenceOptions {
+            mode: SentenceMode::Random(0),
+            count: 10,
+            max_depth: 12,
+            coverage: true,
+        }
+    }
+}
+
+//sentential forms exhaustive mode expands before giving up
+const EXPANSION_LIMIT: usize = 1_000_000;
+//random derivations tried for each sentence asked for, duplicates don't count
+const ATTEMPTS_PER_SENTENCE: usize = 20;
+
+//distinct sentences of terminals, without the end of input
+pub fn generate_sentences(cfg: &CFG, options: &SentenceOptions) -> Vec<Vec<Symbol>> {
+    let heights = Heights::new(cfg);
+    let start = match cfg.productions[0].first().map(|x| x.rhs[0]) {
+        Some(Symbol::Nonterminal(start)) => start,
+        _ => return Vec::new(),
+    };
+    if heights.nonterminals[start] > options.max_depth {
+        return Vec::new();
+    }
+    match options.mode {
+        SentenceMode::Random(seed) => random(cfg, &heights, start, options, seed),
+        SentenceMode::Exhaustive() => exhaustive(cfg, &heights, start, options),
+    }
+}
+
+//terminal names without the leading ., which parse_tree::tokenize reads back as the same terminals
+//the grammar has no lexer definitions, so this is as concrete as the text gets
+pub fn sentence_text(cfg: &CFG, sentence: &[Symbol]) -> String {
+    let words: Vec<&str> = sentence
+        .iter()
+        .map(|symbol| {
+            let name = cfg.symbol_str(symbol);
+            match name.strip_prefix('.') {
+                Some(word) if !word.is_empty() && cfg.terminal_symbols.get(word).is_none() => word,
+                _ => name,
+            }
+        })
+        .collect();
+    words.join(" ")
+}
+
+//smallest derivation tree height of each nonterminal and production, usize::MAX if it has none
+struct Heights {
+    nonterminals: Vec<usize>,
+    //by production id
+    productions: Vec<usize>,
+}
+
+impl Heights {
+    fn new(cfg: &CFG) -> Heights {
+        let mut nonterminals = vec![usize::MAX; cfg.nonterminal_symbols.len()];
+        let production_height = |nonterminals: &[usize], rhs: &[Symbol]| {
+            let mut height = 0;
+            for symbol in rhs {
+                if let Symbol::Nonterminal(x) = symbol {
+                    height = height.max(nonterminals[*x]);
+                }
+            }
+            height.saturating_add(1)
+        };
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (lhs, productions) in cfg.productions.iter().enumerate() {
+                for production in productions {
+                    let height = production_height(&nonterminals, &production.rhs);
+                    if height < nonterminals[lhs] {
+                        nonterminals[lhs] = height;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        let productions = cfg
+            .productions
+            .iter()
+            .flatten()
+            .map(|x| production_height(&nonterminals, &x.rhs))
+            .collect();
+        Heights {
+            nonterminals,
+            productions,
+        }
+    }
+
+    //ids of the nonterminal's productions that can finish when it's at depth
+    fn fitting(&self, cfg: &CFG, nonterminal: usize, depth: usize, max_depth: usize) -> Vec<usize> {
+        let first = first_production(cfg, nonterminal);
+        (first..first + cfg.productions[nonterminal].len())
+            .filter(|x| {
+                self.productions[*x] != usize::MAX && depth - 1 + self.productions[*x] <= max_depth
+            })
+            .collect()
+    }
+}
+
+//id of the nonterminal's first production
+fn first_production(cfg: &CFG, nonterminal: usize) -> usize {
+    cfg.productions[..nonterminal].iter().map(|x| x.len()).sum()
+}
+
+fn random(
+    cfg: &CFG,
+    heights: &Heights,
+    start: usize,
+    options: &SentenceOptions,
+    seed: u64,
+) -> Vec<Vec<Symbol>> {
+    let mut rng = Rng::new(seed);
+    let mut uses = vec![0; cfg.production_count()];
+    let mut seen = HashSet::new();
+    let mut sentences = Vec::new();
+    for _ in 0..options.count * ATTEMPTS_PER_SENTENCE {
+        if sentences.len() >= options.count {
+            break;
+        }
+        let mut sentence = Vec::new();
+        //symbols still to derive, in reverse, with their depth
+        let mut stack = vec![(Symbol::Nonterminal(start), 1)];
+        while let Some((symbol, depth)) = stack.pop() {
+            let nonterminal = match symbol {
+                Symbol::Nonterminal(x) => x,
+                Symbol::Empty() => continue,
+                _ => {
+                    sentence.push(symbol);
+                    continue;
+                }
+            };
+            let mut choices = heights.fitting(cfg, nonterminal, depth, options.max_depth);
+            if options.coverage {
+                let least = choices.iter().map(|x| uses[*x]).min().unwrap();
+                choices.retain(|x| uses[*x] == least);
+            }
+            let production = choices[rng.below(choices.len())];
+            uses[production] += 1;
+            for symbol in cfg.production(production).rhs.iter().rev() {
+                stack.push((*symbol, depth + 1));
+            }
+        }
+        if seen.insert(sentence.clone()) {
+            sentences.push(sentence);
+        }
+    }
+    sentences
+}
+
+fn exhaustive(
+    cfg: &CFG,
+    heights: &Heights,
+    start: usize,
+    options: &SentenceOptions,
+) -> Vec<Vec<Symbol>> {
+    let mut seen = HashSet::new();
+    let mut sentences = Vec::new();
+    //terminals derived so far, and the symbols left with their depth
+    type Form = (Vec<Symbol>, Vec<(Symbol, usize)>);
+    let mut queue: VecDeque<Form> =
+        VecDeque::from(vec![(Vec::new(), vec![(Symbol::Nonterminal(start), 1)])]);
+    let mut expansions = 0;
+    while let Some((mut done, mut rest)) = queue.pop_front() {
+        if sentences.len() >= options.count || expansions >= EXPANSION_LIMIT {
+            break;
+        }
+        expansions += 1;
+        //move terminals up to the leftmost nonterminal across
+        let nonterminal = loop {
+            match rest.first().copied() {
+                None => break None,
+                Some((Symbol::Nonterminal(x), depth)) => break Some((x, depth)),
+                Some((symbol, _)) => {
+                    rest.remove(0);
+                    if symbol != Symbol::Empty() {
+                        done.push(symbol);
+                    }
+                }
+            }
+        };
+        let (nonterminal, depth) = match nonterminal {
+            Some(x) => x,
+            None => {
+                if seen.insert(done.clone()) {
+                    sentences.push(done);
+                }
+                continue;
+            }
+        };
+        for production in heights.fitting(cfg, nonterminal, depth, options.max_depth) {
+            let mut next: Vec<(Symbol, usize)> = cfg
+                .production(production)
+                .rhs
+                .iter()
+                .map(|x| (*x, depth + 1))
+                .collect();
+            next.extend_from_slice(&rest[1..]);
+            queue.push_back((done.clone(), next));
+        }
+    }
+    sentences
+}
@@ -0,0 +1,606 @@
+//grammar rewrites that keep the language the same
+//each returns the new grammar along with which original productions every new production came from,
+//so actions and error messages can be mapped back to the grammar the user wrote
+//the start rule S' -> S <eof> is never rewritten and S' stays nonterminal 0
+
+use super::{CFGProduction, Symbol, SymbolTable, CFG};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+pub struct Transformed {
+    pub cfg: CFG,
+    //indexed by production id in cfg, ids of the original productions it was made from
+    //empty for productions the transform introduced, like A' -> <empty>
+    pub origins: Vec<Vec<usize>>,
+}
+
+impl Transformed {
+    pub fn origin(&self, production: usize) -> &[usize] {
+        &self.origins[production]
+    }
+    //next was made from self.cfg, maps its productions back to the grammar self was made from
+    pub fn then(self, next: Transformed) -> Transformed {
+        let origins = next
+            .origins
+            .iter()
+            .map(|ids| merge(ids.iter().flat_map(|id| self.origins[*id].iter().copied())))
+            .collect();
+        Transformed {
+            cfg: next.cfg,
+            origins,
+        }
+    }
+}
+
+pub enum TransformError {
+    //nonterminal that can derive itself, A =>+ A, left recursion can't be removed
+    Cycle(String),
+    //nonterminal that is still left recursive through a nullable prefix, remove epsilons first
+    HiddenLeftRecursion(String),
+    //nonterminal whose rules are all left recursive, so it can't derive a sentence
+    NonTerminating(String),
+    //the start symbol can't derive any sentence
+    EmptyLanguage(),
+}
+
+impl fmt::Display for TransformError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransformError::Cycle(name) => write!(f, "{} can derive itself", name),
+            TransformError::HiddenLeftRecursion(name) => write!(
+                f,
+                "{} is left recursive through a nullable prefix, remove epsilon productions first",
+                name
+            ),
+            TransformError::NonTerminating(name) => {
+                write!(f, "every rule of {} is left recursive", name)
+            }
+            TransformError::EmptyLanguage() => write!(f, "the grammar doesn't accept any input"),
+        }
+    }
+}
+
+impl fmt::Debug for TransformError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+fn merge<I: Iterator<Item = usize>>(ids: I) -> Vec<usize> {
+    let mut ids: Vec<usize> = ids.collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+//production being rewritten, rhs never contains <empty> so an empty rhs is an epsilon production
+#[derive(Clone)]
+struct Rule {
+    rhs: Vec<Symbol>,
+    origins: Vec<usize>,
+}
+
+fn push_rule(rules: &mut Vec<Rule>, rule: Rule) {
+    match rules.iter_mut().find(|r| r.rhs == rule.rhs) {
+        Some(existing) => {
+            existing.origins = merge(existing.origins.iter().chain(&rule.origins).copied())
+        }
+        None => rules.push(rule),
+    }
+}
+
+fn is_nullable(symbol: &Symbol, nullable: &[bool]) -> bool {
+    matches!(symbol, Symbol::Nonterminal(x) if nullable[*x])
+}
+
+//a node on a cycle of the graph, if there is one
+fn find_cycle(edges: &[Vec<usize>]) -> Option<usize> {
+    //0 unvisited, 1 on the stack, 2 done
+    fn visit(node: usize, edges: &[Vec<usize>], state: &mut Vec<u8>) -> Option<usize> {
+        state[node] = 1;
+        for next in &edges[node] {
+            match state[*next] {
+                0 => {
+                    if let Some(x) = visit(*next, edges, state) {
+                        return Some(x);
+                    }
+                }
+                1 => return Some(*next),
+                _ => {}
+            }
+        }
+        state[node] = 2;
+        None
+    }
+    let mut state = vec![0; edges.len()];
+    (0..edges.len()).find_map(|node| match state[node] {
+        0 => visit(node, edges, &mut state),
+        _ => None,
+    })
+}
+
+struct Work {
+    //indexed by nonterminal like CFG::productions
+    rules: Vec<Vec<Rule>>,
+    nonterminal_symbols: SymbolTable,
+    terminal_symbols: SymbolTable,
+    aliases: HashMap<usize, String>,
+}
+
+impl Work {
+    fn new(cfg: &CFG) -> Work {
+        let mut id = 0;
+        let rules = cfg
+            .productions
+            .iter()
+            .map(|prods| {
+                prods
+                    .iter()
+                    .map(|p| {
+                        id += 1;
+                        Rule {
+                            rhs: p
+                                .rhs
+                                .iter()
+                                .filter(|x| **x != Symbol::Empty())
+                                .copied()
+                                .collect(),
+                            origins: vec![id - 1],
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Work {
+            rules,
+            nonterminal_symbols: cfg.nonterminal_symbols.clone(),
+            terminal_symbols: cfg.terminal_symbols.clone(),
+            aliases: cfg.aliases.clone(),
+        }
+    }
+
+    fn finish(self) -> Transformed {
+        let mut origins = Vec::new();
+        let productions = self
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(nonterminal, rules)| {
+                rules
+                    .into_iter()
+                    .map(|rule| {
+                        origins.push(rule.origins);
+                        CFGProduction {
+                            nonterminal,
+                            rhs: match rule.rhs.is_empty() {
+                                true => vec![Symbol::Empty()],
+                                false => rule.rhs,
+                            },
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Transformed {
+            cfg: CFG {
+                productions,
+                nonterminal_symbols: self.nonterminal_symbols,
+                terminal_symbols: self.terminal_symbols,
+                aliases: self.aliases,
+            },
+            origins,
+        }
+    }
+
+    fn name(&self, nonterminal: usize) -> String {
+        String::from(self.nonterminal_symbols.name(nonterminal))
+    }
+
+    //new nonterminal named after base, with 's added until the name is unused
+    fn add_nonterminal(&mut self, base: usize) -> usize {
+        let mut name = format!("{}'", self.nonterminal_symbols.name(base));
+        while self.nonterminal_symbols.get(&name).is_some() {
+            name.push('\'');
+        }
+        self.rules.push(Vec::new());
+        self.nonterminal_symbols.intern(&name)
+    }
+
+    fn nullable(&self) -> Vec<bool> {
+        let mut nullable = vec![false; self.rules.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (nonterminal, rules) in self.rules.iter().enumerate() {
+                if !nullable[nonterminal]
+                    && rules
+                        .iter()
+                        .any(|r| r.rhs.iter().all(|x| is_nullable(x, &nullable)))
+                {
+                    nullable[nonterminal] = true;
+                    changed = true;
+                }
+            }
+        }
+        nullable
+    }
+
+    //nonterminals that can derive a string of terminals
+    fn generating(&self) -> Vec<bool> {
+        let mut generating = vec![false; self.rules.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (nonterminal, rules) in self.rules.iter().enumerate() {
+                if !generating[nonterminal]
+                    && rules.iter().any(|r| {
+                        r.rhs.iter().all(|x| match x {
+                            Symbol::Nonterminal(n) => generating[*n],
+                            _ => true,
+                        })
+                    })
+                {
+                    generating[nonterminal] = true;
+                    changed = true;
+                }
+            }
+        }
+        generating
+    }
+
+    //edge A -> B when A =>* a B b
+    //left_only: a must be nullable, otherwise both a and b must be nullable
+    fn derivation_edges(&self, nullable: &[bool], left_only: bool) -> Vec<Vec<usize>> {
+        self.rules
+            .iter()
+            .map(|rules| {
+                let mut edges = Vec::new();
+                for rule in rules {
+                    for (i, symbol) in rule.rhs.iter().enumerate() {
+                        if let Symbol::Nonterminal(x) = symbol {
+                            if left_only
+                                || rule.rhs[i + 1..].iter().all(|s| is_nullable(s, nullable))
+                            {
+                                edges.push(*x);
+                            }
+                        }
+                        if !is_nullable(symbol, nullable) {
+                            break;
+                        }
+                    }
+                }
+                edges
+            })
+            .collect()
+    }
+
+    //drops every symbol that isn't kept, along with the rules that use them, and renumbers the rest
+    fn retain(self, keep_nonterminals: &[bool], keep_terminals: &[bool]) -> Work {
+        fn renumber(keep: &[bool]) -> Vec<Option<usize>> {
+            let mut next = 0;
+            keep.iter()
+                .map(|k| match k {
+                    true => {
+                        next += 1;
+                        Some(next - 1)
+                    }
+                    false => None,
+                })
+                .collect()
+        }
+        let nonterminals = renumber(keep_nonterminals);
+        let terminals = renumber(keep_terminals);
+        let map = |symbol: &Symbol| match symbol {
+            Symbol::Nonterminal(x) => nonterminals[*x].map(Symbol::Nonterminal),
+            Symbol::Terminal(x) => terminals[*x].map(Symbol::Terminal),
+            _ => Some(*symbol),
+        };
+        let rules = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(nonterminal, _)| keep_nonterminals[*nonterminal])
+            .map(|(_, rules)| {
+                rules
+                    .iter()
+                    .filter_map(|rule| {
+                        Some(Rule {
+                            rhs: rule.rhs.iter().map(map).collect::<Option<Vec<Symbol>>>()?,
+                            origins: rule.origins.clone(),
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        let keep_names = |symbols: &SymbolTable, keep: &[bool]| {
+            symbols
+                .iter()
+                .zip(keep)
+                .filter(|(_, k)| **k)
+                .map(|(name, _)| name)
+                .collect::<SymbolTable>()
+        };
+        Work {
+            rules,
+            nonterminal_symbols: keep_names(&self.nonterminal_symbols, keep_nonterminals),
+            terminal_symbols: keep_names(&self.terminal_symbols, keep_terminals),
+            aliases: self
+                .aliases
+                .iter()
+                .filter_map(|(t, alias)| terminals[*t].map(|t| (t, alias.clone())))
+                .collect(),
+        }
+    }
+}
+
+//a nonterminal that is left recursive, directly or through other nonterminals, if there is one
+pub fn left_recursive(cfg: &CFG) -> Option<usize> {
+    left_recursive_work(&Work::new(cfg))
+}
+
+//A -> A a | b becomes A -> b A', A' -> a A' | <empty>
+//indirect recursion is made direct first by substituting earlier nonterminals into later ones
+//resource: dragon book algorithm 4.19
+pub fn remove_left_recursion(cfg: &CFG) -> Result<Transformed, TransformError> {
+    let mut work = Work::new(cfg);
+    let nullable = work.nullable();
+    if let Some(x) = find_cycle(&work.derivation_edges(&nullable, false)) {
+        return Err(TransformError::Cycle(work.name(x)));
+    }
+    let order: Vec<usize> = (1..work.rules.len()).collect();
+    for (i, a) in order.iter().enumerate() {
+        for b in &order[..i] {
+            let mut rules = Vec::new();
+            for rule in std::mem::take(&mut work.rules[*a]) {
+                if rule.rhs.first() != Some(&Symbol::Nonterminal(*b)) {
+                    push_rule(&mut rules, rule);
+                    continue;
+                }
+                for sub in &work.rules[*b] {
+                    push_rule(
+                        &mut rules,
+                        Rule {
+                            rhs: sub.rhs.iter().chain(&rule.rhs[1..]).copied().collect(),
+                            origins: merge(rule.origins.iter().chain(&sub.origins).copied()),
+                        },
+                    );
+                }
+            }
+            work.rules[*a] = rules;
+        }
+        remove_direct_left_recursion(&mut work, *a)?;
+    }
+    if let Some(x) = left_recursive_work(&work) {
+        return Err(TransformError::HiddenLeftRecursion(work.name(x)));
+    }
+    Ok(work.finish())
+}
+
+fn left_recursive_work(work: &Work) -> Option<usize> {
+    find_cycle(&work.derivation_edges(&work.nullable(), true))
+}
+
+fn remove_direct_left_recursion(work: &mut Work, a: usize) -> Result<(), TransformError> {
+    let rules = std::mem::take(&mut work.rules[a]);
+    let (recursive, base): (Vec<Rule>, Vec<Rule>) = rules
+        .into_iter()
+        //A -> A doesn't change the language
+        .filter(|r| r.rhs != [Symbol::Nonterminal(a)])
+        .partition(|r| r.rhs.first() == Some(&Symbol::Nonterminal(a)));
+    if recursive.is_empty() {
+        work.rules[a] = base;
+        return Ok(());
+    }
+    if base.is_empty() {
+        return Err(TransformError::NonTerminating(work.name(a)));
+    }
+    let tail = work.add_nonterminal(a);
+    for mut rule in base {
+        rule.rhs.push(Symbol::Nonterminal(tail));
+        push_rule(&mut work.rules[a], rule);
+    }
+    for mut rule in recursive {
+        rule.rhs.remove(0);
+        rule.rhs.push(Symbol::Nonterminal(tail));
+        push_rule(&mut work.rules[tail], rule);
+    }
+    push_rule(
+        &mut work.rules[tail],
+        Rule {
+            rhs: Vec::new(),
+            origins: Vec::new(),
+        },
+    );
+    Ok(())
+}
+
+//A -> a b | a c becomes A -> a A', A' -> b | c, repeated until no two rules of a nonterminal
+//start with the same symbol
+pub fn left_factor(cfg: &CFG) -> Transformed {
+    let mut work = Work::new(cfg);
+    //new nonterminals are pushed onto the end, so they get factored too
+    let mut nonterminal = 1;
+    while nonterminal < work.rules.len() {
+        while factor_once(&mut work, nonterminal) {}
+        nonterminal += 1;
+    }
+    work.finish()
+}
+
+fn factor_once(work: &mut Work, a: usize) -> bool {
+    let rules = &work.rules[a];
+    let first = (0..rules.len()).find_map(|i| {
+        let symbol = rules[i].rhs.first()?;
+        match rules[i + 1..].iter().any(|r| r.rhs.first() == Some(symbol)) {
+            true => Some(*symbol),
+            false => None,
+        }
+    });
+    let first = match first {
+        Some(symbol) => symbol,
+        None => return false,
+    };
+    let in_group = |r: &Rule| r.rhs.first() == Some(&first);
+    let group: Vec<Rule> = rules.iter().filter(|r| in_group(r)).cloned().collect();
+    let mut prefix = 1;
+    while group
+        .iter()
+        .all(|r| r.rhs.len() > prefix && r.rhs[prefix] == group[0].rhs[prefix])
+    {
+        prefix += 1;
+    }
+    let tail = work.add_nonterminal(a);
+    let mut factored = Some(Rule {
+        rhs: group[0].rhs[..prefix]
+            .iter()
+            .copied()
+            .chain(std::iter::once(Symbol::Nonterminal(tail)))
+            .collect(),
+        origins: merge(group.iter().flat_map(|r| r.origins.iter().copied())),
+    });
+    //the factored rule takes the place of the first rule in the group
+    let mut rules = Vec::new();
+    for rule in std::mem::take(&mut work.rules[a]) {
+        if !in_group(&rule) {
+            push_rule(&mut rules, rule);
+        } else if let Some(f) = factored.take() {
+            push_rule(&mut rules, f);
+        }
+    }
+    work.rules[a] = rules;
+    for mut rule in group {
+        rule.rhs.drain(..prefix);
+        push_rule(&mut work.rules[tail], rule);
+    }
+    true
+}
+
+//every rule with nullable symbols is replaced by the versions with and without each of them,
+//then epsilon productions are dropped
+//if the start symbol was nullable, S' -> <eof> is added after S' -> S <eof> so the empty input
+//is still accepted
+pub fn remove_epsilons(cfg: &CFG) -> Transformed {
+    let mut work = Work::new(cfg);
+    let nullable = work.nullable();
+    for (a, rules) in work.rules.iter_mut().enumerate() {
+        let mut result = Vec::new();
+        for rule in rules.iter() {
+            let mut variants: Vec<Vec<Symbol>> = vec![Vec::new()];
+            for symbol in &rule.rhs {
+                let with: Vec<Vec<Symbol>> = variants
+                    .iter()
+                    .map(|v| v.iter().chain(std::iter::once(symbol)).copied().collect())
+                    .collect();
+                //keeping the symbol first, so the full rule stays ahead of its shorter versions
+                //and S' -> S <eof> stays production 0 when S is nullable
+                if is_nullable(symbol, &nullable) {
+                    variants = with.into_iter().chain(variants).collect();
+                } else {
+                    variants = with;
+                }
+            }
+            for rhs in variants {
+                if !rhs.is_empty() && rhs != [Symbol::Nonterminal(a)] {
+                    push_rule(
+                        &mut result,
+                        Rule {
+                            rhs,
+                            origins: rule.origins.clone(),
+                        },
+                    );
+                }
+            }
+        }
+        *rules = result;
+    }
+    //nonterminals that only derived <empty> have no rules left, so nothing can use them
+    let mut changed = true;
+    while changed {
+        let empty: Vec<bool> = work.rules.iter().map(|r| r.is_empty()).collect();
+        changed = false;
+        for rules in work.rules.iter_mut() {
+            let before = rules.len();
+            rules.retain(|r| {
+                r.rhs
+                    .iter()
+                    .all(|x| !matches!(x, Symbol::Nonterminal(n) if empty[*n]))
+            });
+            changed |= rules.len() != before;
+        }
+    }
+    let keep: Vec<bool> = work.rules.iter().map(|r| !r.is_empty()).collect();
+    let terminals = vec![true; work.terminal_symbols.len()];
+    work.retain(&keep, &terminals).finish()
+}
+
+//A -> B with B -> c | d becomes A -> c | d
+//the origins of a copied rule are the unit productions it went through plus the rule itself
+pub fn remove_unit_productions(cfg: &CFG) -> Transformed {
+    let mut work = Work::new(cfg);
+    let count = work.rules.len();
+    let mut result = vec![Vec::new(); count];
+    for (a, rules) in result.iter_mut().enumerate() {
+        //unit productions used to get from a to each nonterminal
+        let mut chains: Vec<Option<Vec<usize>>> = vec![None; count];
+        chains[a] = Some(Vec::new());
+        let mut queue = VecDeque::new();
+        queue.push_back(a);
+        while let Some(b) = queue.pop_front() {
+            let chain = chains[b].clone().unwrap_or_default();
+            for rule in &work.rules[b] {
+                let origins = merge(chain.iter().chain(&rule.origins).copied());
+                match rule.rhs.as_slice() {
+                    [Symbol::Nonterminal(c)] => {
+                        if chains[*c].is_none() {
+                            chains[*c] = Some(origins);
+                            queue.push_back(*c);
+                        }
+                    }
+                    _ => push_rule(
+                        rules,
+                        Rule {
+                            rhs: rule.rhs.clone(),
+                            origins,
+                        },
+                    ),
+                }
+            }
+        }
+    }
+    work.rules = result;
+    work.finish()
+}
+
+//drops nonterminals that can't derive any sentence, then every symbol that can't be reached
+//from the start rule
+pub fn remove_useless_symbols(cfg: &CFG) -> Result<Transformed, TransformError> {
+    let mut work = Work::new(cfg);
+    let generating = work.generating();
+    if !generating[0] {
+        return Err(TransformError::EmptyLanguage());
+    }
+    for rules in work.rules.iter_mut() {
+        rules.retain(|r| {
+            r.rhs
+                .iter()
+                .all(|x| !matches!(x, Symbol::Nonterminal(n) if !generating[*n]))
+        });
+    }
+    let mut nonterminals = vec![false; work.rules.len()];
+    let mut terminals = vec![false; work.terminal_symbols.len()];
+    nonterminals[0] = true;
+    let mut stack = vec![0];
+    while let Some(a) = stack.pop() {
+        for symbol in work.rules[a].iter().flat_map(|r| r.rhs.iter()) {
+            match symbol {
+                Symbol::Nonterminal(x) if !nonterminals[*x] => {
+                    nonterminals[*x] = true;
+                    stack.push(*x);
+                }
+                Symbol::Terminal(x) => terminals[*x] = true,
+                _ => {}
+            }
+        }
+    }
+    Ok(work.retain(&nonterminals, &terminals).finish())
+}
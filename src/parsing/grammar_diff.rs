@@ -0,0 +1,349 @@
+//what changed between two versions of a grammar: productions, terminals and aliases, FIRST and
+//FOLLOW sets, conflicts, and sentences only one of them accepts
+//symbols are matched by name, the ids from interning depend on where a symbol first appears and
+//mean nothing across grammars
+//
+//the languages are searched with glr::viable_sentences, every viable prefix of each grammar up
+//to max_length terminals is extended a terminal at a time, and each sentence found is parsed by the
+//other grammar. equal languages up to that length doesn't mean equal languages
+
+use super::glr;
+use super::grammar::{Symbol, CFG};
+use super::lr1_generator;
+use super::parse_table::Table;
+use super::parse_tree::symbol_tokens;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+pub struct DiffOptions {
+    //longest sentence compared, in terminals
+    pub max_length: usize,
+    //parses of each grammar's prefixes before the language search gives up
+    pub max_parses: usize,
+    //sentences only one grammar accepts kept for each side, shortest first
+    pub max_examples: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            max_length: 6,
+            max_parses: 100_000,
+            max_examples: 5,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SetChange {
+    pub nonterminal: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct GrammarDiff {
+    //productions written like the grammar file, E -> E .+ T
+    pub added_productions: Vec<String>,
+    pub removed_productions: Vec<String>,
+    //old and new, a nonterminal's productions that are in the same place among those that changed
+    pub changed_productions: Vec<(String, String)>,
+    pub added_terminals: Vec<String>,
+    pub removed_terminals: Vec<String>,
+    //terminal, old alias, new alias
+    pub changed_aliases: Vec<(String, Option<String>, Option<String>)>,
+    //only nonterminals in both grammars
+    pub firsts: Vec<SetChange>,
+    pub follows: Vec<SetChange>,
+    //conflicting table cells of the lr(1) tables, old then new
+    pub conflicts: (usize, usize),
+    //sentences as terminal names
+    pub only_old: Vec<Vec<String>>,
+    pub only_new: Vec<Vec<String>>,
+    //false if max_parses stopped the language search early
+    pub complete: bool,
+}
+
+impl GrammarDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_productions.is_empty()
+            && self.removed_productions.is_empty()
+            && self.changed_productions.is_empty()
+            && self.added_terminals.is_empty()
+            && self.removed_terminals.is_empty()
+            && self.changed_aliases.is_empty()
+            && self.firsts.is_empty()
+            && self.follows.is_empty()
+            && self.conflicts.0 == self.conflicts.1
+            && self.only_old.is_empty()
+            && self.only_new.is_empty()
+    }
+
+    pub fn message(&self, options: &DiffOptions) -> String {
+        let mut out = String::new();
+        if !self.added_productions.is_empty()
+            || !self.removed_productions.is_empty()
+            || !self.changed_productions.is_empty()
+        {
+            out.push_str("productions\n");
+            for production in &self.removed_productions {
+                out.push_str(&format!("  - {}\n", production));
+            }
+            for production in &self.added_productions {
+                out.push_str(&format!("  + {}\n", production));
+            }
+            for (old, new) in &self.changed_productions {
+                out.push_str(&format!("  ~ {}\n    {}\n", old, new));
+            }
+        }
+        if !self.added_terminals.is_empty()
+            || !self.removed_terminals.is_empty()
+            || !self.changed_aliases.is_empty()
+        {
+            out.push_str("terminals\n");
+            for terminal in &self.removed_terminals {
+                out.push_str(&format!("  - {}\n", terminal));
+            }
+            for terminal in &self.added_terminals {
+                out.push_str(&format!("  + {}\n", terminal));
+            }
+            let alias = |x: &Option<String>| match x {
+                Some(alias) => format!("\"{}\"", alias),
+                None => String::from("no alias"),
+            };
+            for (terminal, old, new) in &self.changed_aliases {
+                out.push_str(&format!(
+                    "  ~ {} {} -> {}\n",
+                    terminal,
+                    alias(old),
+                    alias(new)
+                ));
+            }
+        }
+        write_sets("first sets", &self.firsts, &mut out);
+        write_sets("follow sets", &self.follows, &mut out);
+        if self.conflicts.0 != self.conflicts.1 {
+            let status = |count: usize| match count {
+                0 => String::from("lr(1)"),
+                _ => format!("{} conflicts", count),
+            };
+            out.push_str(&format!(
+                "conflicts\n  {} -> {}\n",
+                status(self.conflicts.0),
+                status(self.conflicts.1)
+            ));
+        }
+        let searched = match self.complete {
+            true => format!("sentences up to {} terminals", options.max_length),
+            false => format!(
+                "sentences up to {} terminals, stopped after {} parses",
+                options.max_length, options.max_parses
+            ),
+        };
+        if !self.only_old.is_empty() || !self.only_new.is_empty() {
+            out.push_str(&format!("language, {}\n", searched));
+            for sentence in &self.only_old {
+                out.push_str(&format!("  only old: {}\n", sentence_str(sentence)));
+            }
+            for sentence in &self.only_new {
+                out.push_str(&format!("  only new: {}\n", sentence_str(sentence)));
+            }
+        } else {
+            out.push_str(&format!("same language for {}\n", searched));
+        }
+        out
+    }
+}
+
+fn sentence_str(sentence: &[String]) -> String {
+    match sentence.is_empty() {
+        true => String::from("the empty sentence"),
+        false => sentence.join(" "),
+    }
+}
+
+fn write_sets(title: &str, changes: &[SetChange], out: &mut String) {
+    if changes.is_empty() {
+        return;
+    }
+    out.push_str(&format!("{}\n", title));
+    for change in changes {
+        let mut parts = Vec::new();
+        if !change.added.is_empty() {
+            parts.push(format!("added {}", change.added.join(" ")));
+        }
+        if !change.removed.is_empty() {
+            parts.push(format!("removed {}", change.removed.join(" ")));
+        }
+        out.push_str(&format!("  {}: {}\n", change.nonterminal, parts.join(", ")));
+    }
+}
+
+pub fn diff(old: &CFG, new: &CFG, options: &DiffOptions) -> GrammarDiff {
+    let mut diff = GrammarDiff {
+        complete: true,
+        ..GrammarDiff::default()
+    };
+    diff_productions(old, new, &mut diff);
+
+    let old_terminals: BTreeSet<&str> = old.terminal_symbols.iter().collect();
+    let new_terminals: BTreeSet<&str> = new.terminal_symbols.iter().collect();
+    diff.removed_terminals = old_terminals
+        .difference(&new_terminals)
+        .map(|x| String::from(*x))
+        .collect();
+    diff.added_terminals = new_terminals
+        .difference(&old_terminals)
+        .map(|x| String::from(*x))
+        .collect();
+    for terminal in old_terminals.intersection(&new_terminals) {
+        let alias = |cfg: &CFG| {
+            let id = cfg.terminal_symbols.get(terminal).unwrap();
+            cfg.aliases.get(&id).cloned()
+        };
+        if alias(old) != alias(new) {
+            diff.changed_aliases
+                .push((String::from(*terminal), alias(old), alias(new)));
+        }
+    }
+
+    let old_firsts = old.generate_firsts();
+    let new_firsts = new.generate_firsts();
+    diff.firsts = diff_sets(old, &old_firsts, new, &new_firsts);
+    diff.follows = diff_sets(
+        old,
+        &old.generate_follows(&old_firsts),
+        new,
+        &new.generate_follows(&new_firsts),
+    );
+
+    let old_table = lr1_generator::generate_glr(old);
+    let new_table = lr1_generator::generate_glr(new);
+    diff.conflicts = (old_table.conflicts().len(), new_table.conflicts().len());
+
+    let (old_sentences, old_complete) = language(&old_table, options);
+    let (new_sentences, new_complete) = language(&new_table, options);
+    diff.complete = old_complete && new_complete;
+    diff.only_old = only_in(&old_sentences, &new_table, options);
+    diff.only_new = only_in(&new_sentences, &old_table, options);
+    diff
+}
+
+//each production written out, by nonterminal name in grammar order, leaving out S'
+fn productions_by_name(cfg: &CFG) -> BTreeMap<&str, Vec<String>> {
+    let mut productions: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (nonterminal, list) in cfg.productions.iter().enumerate().skip(1) {
+        productions
+            .entry(&cfg.nonterminal_symbols[nonterminal])
+            .or_default()
+            .extend(list.iter().map(|x| cfg.production_str(x)));
+    }
+    productions
+}
+
+fn diff_productions(old: &CFG, new: &CFG, diff: &mut GrammarDiff) {
+    let old_productions = productions_by_name(old);
+    let new_productions = productions_by_name(new);
+    let names: BTreeSet<&str> = old_productions
+        .keys()
+        .chain(new_productions.keys())
+        .copied()
+        .collect();
+    let empty = Vec::new();
+    for name in names {
+        let before = old_productions.get(name).unwrap_or(&empty);
+        let after = new_productions.get(name).unwrap_or(&empty);
+        let removed: Vec<&String> = before.iter().filter(|x| !after.contains(x)).collect();
+        let added: Vec<&String> = after.iter().filter(|x| !before.contains(x)).collect();
+        //pair them up in order, whatever's left over was added or removed
+        let paired = removed.len().min(added.len());
+        for i in 0..paired {
+            diff.changed_productions
+                .push((removed[i].clone(), added[i].clone()));
+        }
+        diff.removed_productions
+            .extend(removed[paired..].iter().map(|x| (*x).clone()));
+        diff.added_productions
+            .extend(added[paired..].iter().map(|x| (*x).clone()));
+    }
+}
+
+fn set_names(cfg: &CFG, set: &HashSet<Symbol>) -> BTreeSet<String> {
+    set.iter()
+        .map(|x| String::from(cfg.symbol_str(x)))
+        .collect()
+}
+
+fn diff_sets(
+    old: &CFG,
+    old_sets: &[HashSet<Symbol>],
+    new: &CFG,
+    new_sets: &[HashSet<Symbol>],
+) -> Vec<SetChange> {
+    let mut changes = Vec::new();
+    let mut names: Vec<&str> = old.nonterminal_symbols.iter().skip(1).collect();
+    names.sort();
+    for name in names {
+        let (old_id, new_id) = match (
+            old.nonterminal_symbols.get(name),
+            new.nonterminal_symbols.get(name),
+        ) {
+            (Some(old_id), Some(new_id)) if new_id != 0 => (old_id, new_id),
+            _ => continue,
+        };
+        let before = set_names(old, &old_sets[old_id]);
+        let after = set_names(new, &new_sets[new_id]);
+        if before != after {
+            changes.push(SetChange {
+                nonterminal: String::from(name),
+                added: after.difference(&before).cloned().collect(),
+                removed: before.difference(&after).cloned().collect(),
+            });
+        }
+    }
+    changes
+}
+
+//sentences of the table's grammar up to max_length as terminal names, shortest first, and whether the
+//search finished
+fn language(table: &Table, options: &DiffOptions) -> (Vec<Vec<String>>, bool) {
+    let cfg = &table.cfg;
+    let names = |sentence: &[Symbol]| -> Vec<String> {
+        sentence
+            .iter()
+            .map(|x| String::from(cfg.symbol_str(x)))
+            .collect()
+    };
+    let mut sentences = Vec::new();
+    let complete = glr::viable_sentences(
+        table,
+        options.max_length,
+        options.max_parses,
+        |sentence, _| {
+            sentences.push(names(sentence));
+            true
+        },
+    );
+    (sentences, complete)
+}
+
+//the sentences of one grammar that the other grammar's table doesn't accept
+//a terminal the other grammar doesn't have is enough to reject a sentence
+fn only_in(sentences: &[Vec<String>], other: &Table, options: &DiffOptions) -> Vec<Vec<String>> {
+    let cfg = &other.cfg;
+    let accepts = |sentence: &[String]| {
+        let symbols: Option<Vec<Symbol>> = sentence
+            .iter()
+            .map(|x| cfg.terminal_symbols.get(x).map(Symbol::Terminal))
+            .collect();
+        match symbols {
+            Some(symbols) => glr::parse(other, &symbol_tokens(cfg, &symbols)).is_ok(),
+            None => false,
+        }
+    };
+    sentences
+        .iter()
+        .filter(|x| !accepts(x))
+        .take(options.max_examples)
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,176 @@
+use crate::parsing::grammar::{transform, Symbol, CFG};
+use crate::parsing::parse_tree::{ParseError, ParseTree, Token};
+use std::collections::{HashMap, HashSet};
+
+//predictive parse table, nonterminal x lookahead -> production to expand with
+pub struct LL1Table {
+    //indexed by nonterminal, keys are terminals and EOF
+    pub rows: Vec<HashMap<Symbol, usize>>,
+    pub cfg: CFG,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum LL1Conflict {
+    //nonterminal, lookahead, two productions whose first sets both contain the lookahead
+    //also used when both productions can derive <empty>
+    FirstFirst(usize, Symbol, usize, usize),
+    //nonterminal, lookahead, production with the lookahead in its first set,
+    //nullable production that has it through follow(nonterminal)
+    FirstFollow(usize, Symbol, usize, usize),
+    //nonterminal that is left recursive, it will also show up in FirstFirst conflicts
+    LeftRecursive(usize),
+}
+
+//all conflicts are reported, not just the first one
+pub fn generate(cfg: &CFG) -> Result<LL1Table, Vec<LL1Conflict>> {
+    let firsts = cfg.generate_firsts();
+    let follows = cfg.generate_follows(&firsts);
+    let mut conflicts = Vec::new();
+    if let Some(nt) = transform::left_recursive(cfg) {
+        conflicts.push(LL1Conflict::LeftRecursive(nt));
+    }
+    //production, and whether it was added through the follow set
+    let mut rows: Vec<HashMap<Symbol, (usize, bool)>> =
+        vec![HashMap::new(); cfg.nonterminal_symbols.len()];
+    for id in 0..cfg.production_count() {
+        let production = cfg.production(id);
+        let nt = production.nonterminal;
+        let first = cfg.first_of_sequence(&production.rhs, &firsts);
+        let mut lookaheads: Vec<(Symbol, bool)> = first
+            .iter()
+            .filter(|x| **x != Symbol::Empty())
+            .map(|x| (*x, false))
+            .collect();
+        if first.contains(&Symbol::Empty()) {
+            lookaheads.extend(follows[nt].iter().map(|x| (*x, true)));
+        }
+        lookaheads.sort();
+        for (lookahead, via_follow) in lookaheads {
+            match rows[nt].get(&lookahead) {
+                None => {
+                    rows[nt].insert(lookahead, (id, via_follow));
+                }
+                Some((existing, _)) if *existing == id => {}
+                Some((existing, existing_follow)) => {
+                    conflicts.push(match (existing_follow, via_follow) {
+                        (false, true) => LL1Conflict::FirstFollow(nt, lookahead, *existing, id),
+                        (true, false) => LL1Conflict::FirstFollow(nt, lookahead, id, *existing),
+                        _ => LL1Conflict::FirstFirst(nt, lookahead, *existing, id),
+                    });
+                }
+            }
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+    Ok(LL1Table {
+        rows: rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|(k, (p, _))| (k, p)).collect())
+            .collect(),
+        cfg: cfg.clone(),
+    })
+}
+
+pub fn conflict_message(cfg: &CFG, conflict: &LL1Conflict) -> String {
+    match conflict {
+        LL1Conflict::FirstFirst(nt, lookahead, a, b) => format!(
+            "FIRST/FIRST conflict in {} on {}: `{}` and `{}` can both start with it",
+            &cfg.nonterminal_symbols[*nt],
+            cfg.symbol_str(lookahead),
+            cfg.production_str(cfg.production(*a)),
+            cfg.production_str(cfg.production(*b))
+        ),
+        LL1Conflict::FirstFollow(nt, lookahead, first, nullable) => format!(
+            "FIRST/FOLLOW conflict in {} on {}: `{}` can start with it, and `{}` can derive <empty> while {} can follow {}",
+            &cfg.nonterminal_symbols[*nt],
+            cfg.symbol_str(lookahead),
+            cfg.production_str(cfg.production(*first)),
+            cfg.production_str(cfg.production(*nullable)),
+            cfg.symbol_str(lookahead),
+            &cfg.nonterminal_symbols[*nt]
+        ),
+        LL1Conflict::LeftRecursive(nt) => format!(
+            "{} is left recursive, which LL(1) can't parse, see grammar::transform::remove_left_recursion",
+            &cfg.nonterminal_symbols[*nt]
+        ),
+    }
+}
+
+struct Frame {
+    production: usize,
+    //next rhs symbol to match
+    index: usize,
+    children: Vec<ParseTree>,
+}
+
+impl LL1Table {
+    //lookaheads the nonterminal can be expanded on, sorted
+    pub fn expected(&self, nonterminal: usize) -> Vec<Symbol> {
+        let mut expected: Vec<Symbol> = self.rows[nonterminal].keys().copied().collect();
+        expected.sort();
+        expected
+    }
+
+    //table driven, with an explicit stack so deep inputs don't overflow
+    //tokens shouldn't include the end of input, it's added here
+    pub fn parse(&self, tokens: &[Token]) -> Result<ParseTree, ParseError> {
+        let eof = Token::eof_after(tokens);
+        let mut pos = 0;
+        let token = |pos: usize| tokens.get(pos).unwrap_or(&eof);
+        let start = match self.rows[0].get(&token(0).symbol) {
+            Some(p) => *p,
+            None => return Err(ParseError::Unexpected(token(0).clone(), self.expected(0))),
+        };
+        let mut frames = vec![Frame {
+            production: start,
+            index: 0,
+            children: Vec::new(),
+        }];
+        while let Some(frame) = frames.last_mut() {
+            let rhs = &self.cfg.production(frame.production).rhs;
+            if frame.index == rhs.len() {
+                let node = ParseTree::Node(frame.production, std::mem::take(&mut frame.children));
+                frames.pop();
+                match frames.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => return Ok(node),
+                }
+                continue;
+            }
+            let symbol = rhs[frame.index];
+            frame.index += 1;
+            match symbol {
+                Symbol::Empty() => {}
+                Symbol::Nonterminal(nt) => match self.rows[nt].get(&token(pos).symbol) {
+                    Some(production) => frames.push(Frame {
+                        production: *production,
+                        index: 0,
+                        children: Vec::new(),
+                    }),
+                    None => {
+                        return Err(ParseError::Unexpected(
+                            token(pos).clone(),
+                            self.expected(nt),
+                        ))
+                    }
+                },
+                _ if token(pos).symbol == symbol => {
+                    frame.children.push(ParseTree::Leaf(token(pos).clone()));
+                    pos += 1;
+                }
+                _ => return Err(ParseError::Unexpected(token(pos).clone(), vec![symbol])),
+            }
+        }
+        unreachable!("the start rule ends with <eof>")
+    }
+
+    //lookaheads with an entry in the table, for printing
+    pub fn columns(&self) -> Vec<Symbol> {
+        let columns: HashSet<Symbol> = self.rows.iter().flat_map(|r| r.keys().copied()).collect();
+        let mut columns: Vec<Symbol> = columns.into_iter().collect();
+        columns.sort();
+        columns
+    }
+}
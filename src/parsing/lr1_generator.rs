@@ -2,8 +2,11 @@
 
 use super::grammar;
 use super::parse_table;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 struct Item<'a> {
@@ -18,7 +21,6 @@ struct ItemSet<'a> {
 }
 
 struct DFAState<'a> {
-    id: usize,
     itemset: ItemSet<'a>,
     transitions: HashMap<grammar::Symbol, usize>,
 }
@@ -28,67 +30,375 @@ struct DFA<'a> {
 }
 
 impl ItemSet<'_> {
+    #[allow(dead_code)]
     pub fn print(&self, cfg: &grammar::CFG) {
         for item in &self.set {
             cfg.print_production(item.production);
-            print!(" , {}\n", cfg.symbol_str(&item.lookahead));
+            println!(" , {}", cfg.symbol_str(&item.lookahead));
         }
     }
 }
 
-//resource for algorithm: http://www.orcca.on.ca/~watt/home/courses/2007-08/cs447a/notes/LR1%20Parsing%20Tables%20Example.pdf
-pub fn generate<'a>(
-    cfg: &'a grammar::CFG,
-) -> Result<parse_table::Table<'a>, parse_table::TableErr> {
+//the lr(1) automaton in a form that outlives the generator, for visualizing and checking it
+//state ids are the same as in the table generate makes for the same grammar
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Automaton {
+    pub states: Vec<AutomatonState>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AutomatonState {
+    //sorted by production then dot
+    pub items: Vec<AutomatonItem>,
+    //sorted by symbol
+    pub transitions: Vec<(grammar::Symbol, usize)>,
+}
+
+//items that only differ in lookahead are kept together
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AutomatonItem {
+    pub production: usize,
+    //index into the production's rhs the dot is before
+    pub dot: usize,
+    //sorted
+    pub lookaheads: Vec<grammar::Symbol>,
+}
+
+impl AutomatonItem {
+    pub fn next_symbol(&self, cfg: &grammar::CFG) -> Option<grammar::Symbol> {
+        cfg.production(self.production).rhs[self.dot..]
+            .iter()
+            .find(|x| **x != grammar::Symbol::Empty())
+            .copied()
+    }
+}
+
+impl Automaton {
+    pub fn transition(&self, state: usize, symbol: &grammar::Symbol) -> Option<usize> {
+        self.states[state]
+            .transitions
+            .iter()
+            .find(|x| x.0 == *symbol)
+            .map(|x| x.1)
+    }
+
+    //lalr(1) automaton, states with the same items ignoring lookaheads are merged
+    //merged states are numbered in order of their first lr(1) state, so the start state stays 0
+    pub fn lalr(&self) -> Automaton {
+        let mut ids: HashMap<Vec<(usize, usize)>, usize> = HashMap::new();
+        let mut merged_into = Vec::with_capacity(self.states.len());
+        let mut states: Vec<AutomatonState> = Vec::new();
+        for state in &self.states {
+            let core: Vec<(usize, usize)> =
+                state.items.iter().map(|x| (x.production, x.dot)).collect();
+            let id = *ids.entry(core).or_insert_with(|| {
+                states.push(AutomatonState {
+                    items: state
+                        .items
+                        .iter()
+                        .map(|x| AutomatonItem {
+                            lookaheads: Vec::new(),
+                            ..x.clone()
+                        })
+                        .collect(),
+                    transitions: Vec::new(),
+                });
+                states.len() - 1
+            });
+            merged_into.push(id);
+            //same core means the items line up
+            for (item, from) in states[id].items.iter_mut().zip(&state.items) {
+                item.lookaheads.extend(&from.lookaheads);
+                item.lookaheads.sort();
+                item.lookaheads.dedup();
+            }
+        }
+        for (state, id) in self.states.iter().zip(&merged_into) {
+            if states[*id].transitions.is_empty() {
+                states[*id].transitions = state
+                    .transitions
+                    .iter()
+                    .map(|(symbol, to)| (*symbol, merged_into[*to]))
+                    .collect();
+            }
+        }
+        Automaton { states }
+    }
+
+    //slr(1) automaton, the lalr states with the follow set of each item's lhs as its lookaheads
+    pub fn slr(&self, cfg: &grammar::CFG) -> Automaton {
+        let follows = cfg.generate_follows(&cfg.generate_firsts());
+        let mut slr = self.lalr();
+        for state in &mut slr.states {
+            for item in &mut state.items {
+                let lhs = cfg.production(item.production).nonterminal;
+                item.lookaheads = follows[lhs].iter().copied().collect();
+                item.lookaheads.sort();
+            }
+        }
+        slr
+    }
+
+    //every action on each lookahead that has more than one, like Table::conflicts
+    pub fn conflicts(
+        &self,
+        cfg: &grammar::CFG,
+    ) -> Vec<(usize, grammar::Symbol, Vec<parse_table::TableCell>)> {
+        let mut conflicts = Vec::new();
+        for (id, state) in self.states.iter().enumerate() {
+            let mut actions: BTreeMap<grammar::Symbol, Vec<parse_table::TableCell>> =
+                BTreeMap::new();
+            for (symbol, to) in &state.transitions {
+                if let grammar::Symbol::Terminal(_) = symbol {
+                    actions
+                        .entry(*symbol)
+                        .or_default()
+                        .push(parse_table::TableCell::Shift(*to));
+                }
+            }
+            for item in &state.items {
+                let cell = match item.next_symbol(cfg) {
+                    Some(grammar::Symbol::EOF()) => parse_table::TableCell::Accept(),
+                    Some(_) => continue,
+                    None => parse_table::TableCell::Reduce(item.production),
+                };
+                for lookahead in &item.lookaheads {
+                    let cells = actions.entry(*lookahead).or_default();
+                    if !cells.contains(&cell) {
+                        cells.push(cell);
+                    }
+                }
+            }
+            for (symbol, mut cells) in actions {
+                if cells.len() > 1 {
+                    cells.sort();
+                    conflicts.push((id, symbol, cells));
+                }
+            }
+        }
+        conflicts
+    }
+
+    //the states on the shortest way from the start to each of targets, and every state
+    //within distance transitions of one of them in either direction
+    pub fn around(&self, targets: &[usize], distance: usize) -> Vec<usize> {
+        let mut parent: Vec<Option<usize>> = vec![None; self.states.len()];
+        let mut seen = vec![false; self.states.len()];
+        let mut queue = std::collections::VecDeque::from(vec![0]);
+        seen[0] = true;
+        while let Some(state) = queue.pop_front() {
+            for (_, to) in &self.states[state].transitions {
+                if !seen[*to] {
+                    seen[*to] = true;
+                    parent[*to] = Some(state);
+                    queue.push_back(*to);
+                }
+            }
+        }
+        let mut incoming: Vec<Vec<usize>> = vec![Vec::new(); self.states.len()];
+        for (id, state) in self.states.iter().enumerate() {
+            for (_, to) in &state.transitions {
+                incoming[*to].push(id);
+            }
+        }
+        let mut chosen = vec![false; self.states.len()];
+        for target in targets {
+            let mut state = Some(*target);
+            while let Some(x) = state {
+                chosen[x] = true;
+                state = parent[x];
+            }
+            let mut frontier = vec![*target];
+            for _ in 0..distance {
+                let mut next = Vec::new();
+                for state in frontier {
+                    let outgoing = self.states[state].transitions.iter().map(|x| &x.1);
+                    for neighbour in outgoing.chain(&incoming[state]) {
+                        if !chosen[*neighbour] {
+                            chosen[*neighbour] = true;
+                            next.push(*neighbour);
+                        }
+                    }
+                }
+                frontier = next;
+            }
+        }
+        (0..self.states.len()).filter(|x| chosen[*x]).collect()
+    }
+}
+
+pub fn automaton(cfg: &grammar::CFG) -> Automaton {
     let firsts = cfg.generate_firsts();
     let dfa = generate_dfa(cfg, &firsts);
+    let states = dfa
+        .states
+        .iter()
+        .map(|state| {
+            let mut items: BTreeMap<(usize, usize), Vec<grammar::Symbol>> = BTreeMap::new();
+            for item in &state.itemset.set {
+                items
+                    .entry((cfg.production_id(item.production), item.reading))
+                    .or_default()
+                    .push(item.lookahead);
+            }
+            let mut transitions: Vec<(grammar::Symbol, usize)> =
+                state.transitions.iter().map(|(k, v)| (*k, *v)).collect();
+            transitions.sort();
+            AutomatonState {
+                items: items
+                    .into_iter()
+                    .map(|((production, dot), mut lookaheads)| {
+                        lookaheads.sort();
+                        AutomatonItem {
+                            production,
+                            dot,
+                            lookaheads,
+                        }
+                    })
+                    .collect(),
+                transitions,
+            }
+        })
+        .collect();
+    Automaton { states }
+}
+
+//resource for algorithm: http://www.orcca.on.ca/~watt/home/courses/2007-08/cs447a/notes/LR1%20Parsing%20Tables%20Example.pdf
+pub fn generate(cfg: &grammar::CFG) -> Result<parse_table::Table, parse_table::TableErr> {
+    generate_with(cfg, false)
+}
+
+//never fails, conflicting actions are kept in TableRow::extra for the glr runtime
+pub fn generate_glr(cfg: &grammar::CFG) -> parse_table::Table {
+    match generate_with(cfg, true) {
+        Ok(table) => table,
+        Err(_) => unreachable!("conflicts are kept"),
+    }
+}
+
+//lalr(1) table, from the lr(1) automaton with states of the same core merged
+//can have reduce/reduce conflicts the lr(1) table doesn't, and is usually much smaller
+pub fn generate_lalr(cfg: &grammar::CFG) -> Result<parse_table::Table, parse_table::TableErr> {
+    table_from_automaton(cfg, &automaton(cfg).lalr(), false)
+}
+
+//slr(1) table, the lalr automaton's states with follow sets as the lookaheads
+pub fn generate_slr(cfg: &grammar::CFG) -> Result<parse_table::Table, parse_table::TableErr> {
+    table_from_automaton(cfg, &automaton(cfg).slr(cfg), false)
+}
+
+fn generate_with(
+    cfg: &grammar::CFG,
+    keep_conflicts: bool,
+) -> Result<parse_table::Table, parse_table::TableErr> {
+    table_from_automaton(cfg, &automaton(cfg), keep_conflicts)
+}
+
+//shifts and gotos from the transitions, reductions from the completed items' lookaheads
+pub fn table_from_automaton(
+    cfg: &grammar::CFG,
+    automaton: &Automaton,
+    keep_conflicts: bool,
+) -> Result<parse_table::Table, parse_table::TableErr> {
     let mut table = parse_table::Table {
-        rows: Vec::with_capacity(dfa.states.len()),
-        cfg: cfg,
+        rows: Vec::with_capacity(automaton.states.len()),
+        cfg: cfg.clone(),
     };
-    for state in dfa.states {
-        match generate_table_row(&state) {
-            Ok(r) => table.rows.push(r),
-            Err(e) => return Result::Err(e),
-        };
+    for (id, state) in automaton.states.iter().enumerate() {
+        table
+            .rows
+            .push(generate_table_row(cfg, id, state, keep_conflicts)?);
     }
     Result::Ok(table)
 }
 
-fn generate_table_row(state: &DFAState) -> Result<parse_table::TableRow, parse_table::TableErr> {
+fn generate_table_row(
+    cfg: &grammar::CFG,
+    id: usize,
+    state: &AutomatonState,
+    keep_conflicts: bool,
+) -> Result<parse_table::TableRow, parse_table::TableErr> {
     let mut cells = HashMap::new();
+    let mut extra: HashMap<grammar::Symbol, Vec<parse_table::TableCell>> = HashMap::new();
 
-    for transition in &state.transitions {
-        match transition.0 {
-            grammar::Symbol::Terminal(_) => {
-                //shift/reduce
-                //TODO: this
-            }
-            grammar::Symbol::Nonterminal(_) => {
-                //goto
-                match cells.insert(*transition.0, parse_table::TableCell::Goto(*transition.1)) {
-                    None => (),
-                    //goto/goto conflict
-                    Some(x) => {
-                        return Err(parse_table::TableErr::Conflict(
-                            parse_table::TableCell::Goto(*transition.1),
-                            x,
-                        ))
+    for (symbol, to) in &state.transitions {
+        let cell = match symbol {
+            grammar::Symbol::Terminal(_) => parse_table::TableCell::Shift(*to),
+            grammar::Symbol::Nonterminal(_) => parse_table::TableCell::Goto(*to),
+            _ => panic!("non terminal/nonterminal transition in generating lr(1) table"),
+        };
+        //transitions are unique per symbol, so this can't conflict
+        cells.insert(*symbol, cell);
+    }
+    //items and lookaheads are sorted, so the first action on a symbol is the same every time
+    for item in &state.items {
+        let cell = match item.next_symbol(cfg) {
+            //S' -> S . <eof>
+            Some(grammar::Symbol::EOF()) => parse_table::TableCell::Accept(),
+            Some(_) => continue,
+            None => parse_table::TableCell::Reduce(item.production),
+        };
+        for lookahead in &item.lookaheads {
+            match cells.get(lookahead) {
+                None => {
+                    cells.insert(*lookahead, cell);
+                }
+                Some(x) if *x == cell => (),
+                Some(_) if keep_conflicts => {
+                    let cells = extra.entry(*lookahead).or_default();
+                    if !cells.contains(&cell) {
+                        cells.push(cell);
                     }
                 }
+                //shift/reduce or reduce/reduce conflict
+                Some(x) => return Err(parse_table::TableErr::Conflict(*x, cell, id, *lookahead)),
             }
-            grammar::Symbol::EOF() => (), //make some kind of accept
-            _ => panic!("empty transition in generating lr(1) table"),
         }
     }
 
-    Result::Ok(parse_table::TableRow { cells: cells })
+    //the smallest action goes in cells and the rest in extra, sorted
+    for (symbol, others) in extra.iter_mut() {
+        let cell = cells.get_mut(symbol).unwrap();
+        others.push(*cell);
+        others.sort();
+        *cell = others.remove(0);
+    }
+    Result::Ok(parse_table::TableRow { cells, extra })
 }
 
-fn generate_dfa<'a: 'b, 'b>(
-    cfg: &'a grammar::CFG,
-    firsts: &'a Vec<HashSet<grammar::Symbol>>,
-) -> DFA<'a> {
+//the symbol after the dot, <empty> is never read
+fn next_symbol(item: &Item) -> Option<grammar::Symbol> {
+    item.production.rhs[item.reading..]
+        .iter()
+        .find(|x| **x != grammar::Symbol::Empty())
+        .copied()
+}
+
+//item with the dot moved past the next symbol
+fn advance<'a>(item: &Item<'a>) -> Item<'a> {
+    let mut reading = item.reading;
+    while item.production.rhs[reading] == grammar::Symbol::Empty() {
+        reading += 1;
+    }
+    Item {
+        reading: reading + 1,
+        production: item.production,
+        lookahead: item.lookahead,
+    }
+}
+
+//order independent hash of an itemset, so equal sets can be found without comparing against every state
+fn itemset_hash(itemset: &ItemSet) -> u64 {
+    let mut total: u64 = 0;
+    for item in &itemset.set {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        total = total.wrapping_add(hasher.finish());
+    }
+    total
+}
+
+fn generate_dfa<'a>(cfg: &'a grammar::CFG, firsts: &[HashSet<grammar::Symbol>]) -> DFA<'a> {
     //set up start state
     let mut start_set = ItemSet {
         set: HashSet::new(),
@@ -100,94 +410,73 @@ fn generate_dfa<'a: 'b, 'b>(
         lookahead: grammar::Symbol::EOF(),
     };
     start_set.set.insert(start_item);
-    start_set = closure(start_set, cfg, &firsts);
-    let mut start_state = DFAState {
-        id: 0,
+    start_set = closure(start_set, cfg, firsts);
+    let start_state = DFAState {
         itemset: start_set,
         transitions: HashMap::new(),
     };
 
     //set up dfa
     let mut dfa = DFA { states: Vec::new() };
+    //itemset hash -> ids of states with that hash
+    let mut lookup: HashMap<u64, Vec<usize>> = HashMap::new();
+    lookup.insert(itemset_hash(&start_state.itemset), vec![0]);
     dfa.states.push(start_state);
-    //expand dfa
-    let mut added = true;
-    //should change data structure but idc at the moment. just want it to woooork
-    while added {
-        //buffers
-        let mut to_add = Vec::new();
-        let mut transitions = Vec::new();
-        added = false;
-        for i in 0..dfa.states.len() {
-            let state = &dfa.states[i];
-            //get all itemsets needed for this state
-            let adj_states = get_dfa_tranitions(state, cfg, firsts);
-            //find or create id for each itemset
-            let mut flag = false;
-            for adj in adj_states {
-                for j in 0..dfa.states.len() {
-                    if adj.1.set == dfa.states[j].itemset.set {
-                        transitions.push((i, adj.0, j)); //found id
-                        flag = true;
-                        break;
-                    }
-                }
-                if !flag {
-                    //not found
-                    //to_add.push(adj.1);
-                    let new_id = dfa.states.len() + transitions.len();
-                    to_add.push(DFAState {
-                        id: new_id,
-                        itemset: adj.1,
+    //expand dfa, every state is visited once and new states are added to the end
+    let mut i = 0;
+    while i < dfa.states.len() {
+        //get all itemsets needed for this state, in symbol order so state ids don't depend on hashing
+        let mut adj_states: Vec<(grammar::Symbol, ItemSet)> =
+            get_dfa_tranitions(&dfa.states[i], cfg, firsts)
+                .into_iter()
+                .collect();
+        adj_states.sort_by_key(|x| x.0);
+        //find or create id for each itemset
+        for (symbol, itemset) in adj_states {
+            let hash = itemset_hash(&itemset);
+            let bucket = lookup.entry(hash).or_default();
+            let found = bucket
+                .iter()
+                .find(|j| dfa.states[**j].itemset.set == itemset.set)
+                .copied();
+            let id = match found {
+                Some(j) => j,
+                None => {
+                    let new_id = dfa.states.len();
+                    bucket.push(new_id);
+                    dfa.states.push(DFAState {
+                        itemset,
                         transitions: HashMap::new(),
                     });
-                    transitions.push((i, adj.0, dfa.states.len() + transitions.len()));
-                    added = true;
+                    new_id
                 }
-            }
+            };
+            dfa.states[i].transitions.insert(symbol, id);
         }
-        //populate the real objects from the buffers
-        /*for new_state in to_add {
-            dfa.states.push(new_state);
-        }
-          for transition in transitions {
-              dfa.states[transition.0]
-                  .transitions
-                  .insert(transition.1, transition.2);
-          }*/
+        i += 1;
     }
     dfa
 }
 
-fn itemsets_equal(a: &ItemSet, b: &ItemSet) -> bool {
-    if a.set.len() != b.set.len() {
-        return false;
-    }
-    for a_item in &a.set {
-        if !b.set.contains(a_item) {
-            return false;
-        }
-    }
-    true
-}
-
-fn get_dfa_tranitions<'a: 'b, 'b>(
-    state: &'b DFAState<'b>,
+fn get_dfa_tranitions<'a>(
+    state: &DFAState<'a>,
     cfg: &'a grammar::CFG,
-    firsts: &'a Vec<HashSet<grammar::Symbol>>,
-) -> HashMap<grammar::Symbol, ItemSet<'b>> {
+    firsts: &[HashSet<grammar::Symbol>],
+) -> HashMap<grammar::Symbol, ItemSet<'a>> {
+    //group items by the symbol after the dot, moving the dot past it
     let mut map: HashMap<grammar::Symbol, ItemSet> = HashMap::new();
     for item in &state.itemset.set {
-        match map.get_mut(&item.lookahead) {
-            Some(itemset) => {
-                itemset.set.insert(*item);
-            }
-            None => {
-                let mut set = HashSet::new();
-                set.insert(*item);
-                map.insert(item.lookahead, ItemSet { set: set });
-            }
+        let symbol = match next_symbol(item) {
+            //accepting, no state after reading <eof>
+            Some(grammar::Symbol::EOF()) | None => continue,
+            Some(x) => x,
         };
+        map.entry(symbol)
+            .or_insert_with(|| ItemSet {
+                set: HashSet::new(),
+            })
+            .set
+            .insert(advance(item));
     }
     let mut res: HashMap<grammar::Symbol, ItemSet> = HashMap::new();
     for item in map {
@@ -197,18 +486,18 @@ fn get_dfa_tranitions<'a: 'b, 'b>(
     res
 }
 //populates an itemset with the closure of its items
-fn closure<'a: 'b, 'b>(
-    mut itemset: ItemSet<'b>,
-    cfg: &'b grammar::CFG,
-    firsts: &'a Vec<HashSet<grammar::Symbol>>,
-) -> ItemSet<'b> {
+fn closure<'a>(
+    mut itemset: ItemSet<'a>,
+    cfg: &'a grammar::CFG,
+    firsts: &[HashSet<grammar::Symbol>],
+) -> ItemSet<'a> {
     let mut add_buf;
     let mut keep_going = true;
     while keep_going {
         keep_going = false;
         add_buf = HashSet::new(); //may want to change this later
         for item in &itemset.set {
-            closure_item(cfg, firsts, &item, &mut add_buf);
+            closure_item(cfg, firsts, item, &mut add_buf);
         }
         for add in add_buf.into_iter() {
             if itemset.set.insert(add) {
@@ -220,45 +509,27 @@ fn closure<'a: 'b, 'b>(
 }
 
 //adds items to dest
-fn closure_item<'a: 'b, 'b: 'c, 'c>(
+//for A -> alpha . B beta, a: adds B -> . gamma, b for every b in first(beta a)
+fn closure_item<'a>(
     cfg: &'a grammar::CFG,
-    firsts: &'b Vec<HashSet<grammar::Symbol>>,
-    item: &'c Item,
-    dest: &'c mut HashSet<Item<'b>>,
+    firsts: &[HashSet<grammar::Symbol>],
+    item: &Item,
+    dest: &mut HashSet<Item<'a>>,
 ) {
-    let nt;
-    match item.production.rhs[item.reading] {
-        grammar::Symbol::Nonterminal(x) => nt = x,
+    let nt = match next_symbol(item) {
+        Some(grammar::Symbol::Nonterminal(x)) => x,
         _ => return,
-    }
+    };
     let prods = &cfg.productions[nt];
-    let beta = &item.production.rhs[item.reading + 1..item.production.rhs.len()];
-    let lookahead_option = cfg.get_first(beta, firsts);
-    let lookaheads;
-    match lookahead_option {
-        Some(x) => lookaheads = x,
-        None => lookaheads = grammar::FirstSet::Other(item.lookahead),
-    }
-    match lookaheads {
-        grammar::FirstSet::Other(x) => {
-            for prod in prods {
-                dest.insert(Item {
-                    reading: item.reading,
-                    lookahead: x.clone(),
-                    production: prod,
-                });
-            }
-        }
-        grammar::FirstSet::Nonterminal(x) => {
-            for lookahead in x {
-                for prod in prods {
-                    dest.insert(Item {
-                        reading: item.reading,
-                        lookahead: lookahead.clone(),
-                        production: prod,
-                    });
-                }
-            }
+    let after = advance(item);
+    let beta = &item.production.rhs[after.reading..];
+    for lookahead in cfg.first_of_string(beta, firsts, item.lookahead) {
+        for prod in prods {
+            dest.insert(Item {
+                reading: 0,
+                lookahead,
+                production: prod,
+            });
         }
     }
 }
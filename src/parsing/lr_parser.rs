@@ -0,0 +1,541 @@
+//runtime for the deterministic tables from lr1_generator, with syntax error recovery
+//on an error the strategies are tried in order until one applies:
+//  single token repair, insert, delete or replace one token, kept if the next few tokens then parse
+//  error rules, pop to a state that can shift <error>, shift it and skip input until it fits
+//  panic mode, skip input to a sync token and pop to a state that can take it
+//resource: Burke & Fisher, A Practical Method for LR and LL Syntactic Error Diagnosis and Recovery,
+//and Diekmann & Tratt, Don't Panic! Better, Fewer, Syntax Errors for LR Parsers (CPCT+)
+
+use super::coverage::Coverage;
+use super::grammar::builder::ERROR_SYMBOL;
+use super::grammar::{Symbol, CFG};
+use super::parse_table::{Table, TableCell};
+use super::parse_tree::{ParseError, ParseTree, Token};
+
+pub struct Recovery {
+    //panic mode skips to one of these, <eof> always counts
+    pub sync: Vec<Symbol>,
+    //try inserting, deleting or replacing one token first
+    pub repair: bool,
+    //how many tokens past a repair have to parse for it to be kept
+    pub check: usize,
+    //parsing stops after this many errors
+    pub max_errors: usize,
+}
+
+impl Default for Recovery {
+    fn default() -> Recovery {
+        Recovery {
+            sync: Vec::new(),
+            repair: true,
+            check: 3,
+            max_errors: 100,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RecoveryAction {
+    //terminal put in before the unexpected token, it's a leaf with no text in the tree
+    Inserted(Symbol),
+    Deleted(Token),
+    //unexpected token and the terminal it was parsed as
+    Replaced(Token, Symbol),
+    //tokens covered by an <error> leaf, can be none
+    ErrorRule(Vec<Token>),
+    //panic mode, the tokens that were skipped
+    Skipped(Vec<Token>),
+    //nothing applied, or there were too many errors
+    GaveUp(),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SyntaxError {
+    pub error: ParseError,
+    pub action: RecoveryAction,
+}
+
+impl SyntaxError {
+    //the parse error, then what was done about it
+    pub fn message(&self, cfg: &CFG) -> String {
+        let texts = |tokens: &[Token]| -> String {
+            let texts: Vec<&str> = tokens.iter().map(|x| x.text.as_str()).collect();
+            format!("`{}`", texts.join(" "))
+        };
+        let action = match &self.action {
+            RecoveryAction::Inserted(symbol) => format!("inserted {}", cfg.describe(symbol)),
+            RecoveryAction::Deleted(token) => format!("deleted `{}`", token.text),
+            RecoveryAction::Replaced(token, symbol) => {
+                format!("read `{}` as {}", token.text, cfg.describe(symbol))
+            }
+            RecoveryAction::ErrorRule(tokens) if tokens.is_empty() => {
+                String::from("used an error rule")
+            }
+            RecoveryAction::ErrorRule(tokens) => {
+                format!("used an error rule for {}", texts(tokens))
+            }
+            RecoveryAction::Skipped(tokens) if tokens.is_empty() => {
+                String::from("dropped back to a state that fits")
+            }
+            RecoveryAction::Skipped(tokens) => format!("skipped {}", texts(tokens)),
+            RecoveryAction::GaveUp() => String::from("gave up"),
+        };
+        format!("{}, {}", self.error.message(cfg), action)
+    }
+}
+
+pub struct Recovered {
+    //None if recovery gave up
+    pub tree: Option<ParseTree>,
+    //in input order
+    pub errors: Vec<SyntaxError>,
+}
+
+//an action the parser took, with the stack it took it on
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TraceStep {
+    //states before the action, bottom first
+    pub states: Vec<usize>,
+    //the lookahead, or the nonterminal just reduced for a goto
+    pub symbol: Symbol,
+    pub action: TableCell,
+}
+
+pub(crate) enum Step {
+    Shifted,
+    Accepted,
+    Error,
+}
+
+struct Parser<'a> {
+    table: &'a Table,
+    states: Vec<usize>,
+    //one tree per state above the first
+    trees: Vec<ParseTree>,
+    error_terminal: Option<Symbol>,
+    coverage: Option<&'a mut Coverage>,
+    trace: Option<&'a mut Vec<TraceStep>>,
+}
+
+impl Parser<'_> {
+    fn action(&self, state: usize, symbol: &Symbol) -> Option<TableCell> {
+        self.table.rows[state].cells.get(symbol).copied()
+    }
+
+    //the action on the symbol is being taken, and state entered if there is one
+    fn record(&mut self, symbol: &Symbol, entered: Option<usize>) {
+        let state = *self.states.last().unwrap();
+        let action = self.action(state, symbol).unwrap();
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceStep {
+                states: self.states.clone(),
+                symbol: *symbol,
+                action,
+            });
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.took(state, symbol);
+            if let Some(entered) = entered {
+                coverage.entered(entered);
+            }
+        }
+    }
+
+    //reduces as far as the token allows, then shifts or accepts it
+    fn step(&mut self, token: &Token) -> Step {
+        loop {
+            match self.action(*self.states.last().unwrap(), &token.symbol) {
+                Some(TableCell::Shift(state)) => {
+                    self.record(&token.symbol, Some(state));
+                    self.states.push(state);
+                    self.trees.push(ParseTree::Leaf(token.clone()));
+                    return Step::Shifted;
+                }
+                Some(TableCell::Reduce(production)) => {
+                    self.record(&token.symbol, None);
+                    if let Some(coverage) = &mut self.coverage {
+                        coverage.reduced(production);
+                    }
+                    let cfg = &self.table.cfg;
+                    let production_ref = cfg.production(production);
+                    let len = production_ref.rhs_len();
+                    self.states.truncate(self.states.len() - len);
+                    let children = self.trees.split_off(self.trees.len() - len);
+                    let lhs = Symbol::Nonterminal(production_ref.nonterminal);
+                    match self.action(*self.states.last().unwrap(), &lhs) {
+                        Some(TableCell::Goto(state)) => {
+                            self.record(&lhs, Some(state));
+                            self.states.push(state);
+                        }
+                        _ => panic!("table has no goto after reducing {}", production),
+                    }
+                    self.trees.push(ParseTree::Node(production, children));
+                }
+                Some(TableCell::Accept()) => {
+                    self.record(&token.symbol, None);
+                    return Step::Accepted;
+                }
+                _ => return Step::Error,
+            }
+        }
+    }
+
+    //whether the symbol can be shifted or accepted from the states, only following reductions
+    fn fits(&self, states: &[usize], symbol: &Symbol) -> bool {
+        simulate(self.table, states.to_vec(), std::iter::once(symbol)) == 1
+    }
+
+    fn expected(&self) -> Vec<Symbol> {
+        self.table.expected(*self.states.last().unwrap())
+    }
+
+    //the tree for the S' production matching what's left on the stack, with <eof> at the end
+    fn finish(&mut self, eof: &Token) -> ParseTree {
+        let cfg = &self.table.cfg;
+        let production = cfg.productions[0]
+            .iter()
+            .find(|x| x.rhs_len() == self.trees.len() + 1)
+            .expect("accepted without a matching start rule");
+        let id = cfg.production_id(production);
+        if let Some(coverage) = &mut self.coverage {
+            coverage.reduced(id);
+            coverage.accepted += 1;
+        }
+        let mut children = std::mem::take(&mut self.trees);
+        children.push(ParseTree::Leaf(eof.clone()));
+        ParseTree::Node(id, children)
+    }
+}
+
+//reduces as far as the symbol allows on a stack of states, then shifts or accepts it
+//on an error the stack is left after any reductions, its top is the state that found the error
+pub(crate) fn advance(table: &Table, states: &mut Vec<usize>, symbol: &Symbol) -> Step {
+    loop {
+        match table.rows[*states.last().unwrap()].cells.get(symbol) {
+            Some(TableCell::Shift(state)) => {
+                states.push(*state);
+                return Step::Shifted;
+            }
+            Some(TableCell::Reduce(production)) => {
+                let production = table.cfg.production(*production);
+                states.truncate(states.len() - production.rhs_len());
+                let lhs = Symbol::Nonterminal(production.nonterminal);
+                match table.rows[*states.last().unwrap()].cells.get(&lhs) {
+                    Some(TableCell::Goto(state)) => states.push(*state),
+                    _ => return Step::Error,
+                }
+            }
+            Some(TableCell::Accept()) => return Step::Accepted,
+            _ => return Step::Error,
+        }
+    }
+}
+
+//number of symbols the states get through, an accepted <eof> counts
+fn simulate<'a, I: Iterator<Item = &'a Symbol>>(
+    table: &Table,
+    mut states: Vec<usize>,
+    symbols: I,
+) -> usize {
+    let mut count = 0;
+    for symbol in symbols {
+        match advance(table, &mut states, symbol) {
+            Step::Shifted => count += 1,
+            Step::Accepted => return count + 1,
+            Step::Error => return count,
+        }
+    }
+    count
+}
+
+//tokens shouldn't include the end of input, it's added here
+//stops at the first error
+pub fn parse(table: &Table, tokens: &[Token]) -> Result<ParseTree, ParseError> {
+    run_stopping(table, tokens, None, None)
+}
+
+//like parse, adding the productions, states and actions it used to the coverage
+//the coverage has to be made for the same table
+pub fn parse_covering(
+    table: &Table,
+    tokens: &[Token],
+    coverage: &mut Coverage,
+) -> Result<ParseTree, ParseError> {
+    run_stopping(table, tokens, Some(coverage), None)
+}
+
+//like parse, adding every shift, reduce, goto and accept to the trace in the order they happen
+pub fn parse_traced(
+    table: &Table,
+    tokens: &[Token],
+    trace: &mut Vec<TraceStep>,
+) -> Result<ParseTree, ParseError> {
+    run_stopping(table, tokens, None, Some(trace))
+}
+
+fn run_stopping(
+    table: &Table,
+    tokens: &[Token],
+    coverage: Option<&mut Coverage>,
+    trace: Option<&mut Vec<TraceStep>>,
+) -> Result<ParseTree, ParseError> {
+    let recovery = Recovery {
+        repair: false,
+        max_errors: 0,
+        ..Recovery::default()
+    };
+    let recovered = run(table, tokens, &recovery, coverage, trace);
+    match recovered.tree {
+        Some(tree) => Ok(tree),
+        None => Err(recovered.errors[0].error.clone()),
+    }
+}
+
+//parses through syntax errors, reporting each one with how it was recovered from
+pub fn parse_recovering(table: &Table, tokens: &[Token], recovery: &Recovery) -> Recovered {
+    run(table, tokens, recovery, None, None)
+}
+
+fn run(
+    table: &Table,
+    tokens: &[Token],
+    recovery: &Recovery,
+    mut coverage: Option<&mut Coverage>,
+    trace: Option<&mut Vec<TraceStep>>,
+) -> Recovered {
+    if let Some(coverage) = &mut coverage {
+        coverage.inputs += 1;
+        coverage.entered(0);
+    }
+    let mut tokens = tokens.to_vec();
+    tokens.push(Token::eof_after(&tokens));
+    let error_terminal = table
+        .cfg
+        .terminal_symbols
+        .get(ERROR_SYMBOL)
+        .map(Symbol::Terminal);
+    let mut parser = Parser {
+        table,
+        states: vec![0],
+        trees: Vec::new(),
+        error_terminal,
+        coverage,
+        trace,
+    };
+    let mut errors: Vec<SyntaxError> = Vec::new();
+    let mut pos = 0;
+    loop {
+        let token = tokens[pos].clone();
+        match parser.step(&token) {
+            Step::Shifted => {
+                pos += 1;
+                continue;
+            }
+            Step::Accepted => {
+                let tree = parser.finish(&token);
+                return Recovered {
+                    tree: Some(tree),
+                    errors,
+                };
+            }
+            Step::Error => {}
+        }
+        let error = ParseError::Unexpected(token.clone(), parser.expected());
+        if errors.len() >= recovery.max_errors {
+            errors.push(SyntaxError {
+                error,
+                action: RecoveryAction::GaveUp(),
+            });
+            return Recovered { tree: None, errors };
+        }
+        let action = match recovery.repair {
+            true => repair(&mut parser, &tokens, &mut pos, recovery.check),
+            false => None,
+        };
+        let action = action
+            .or_else(|| error_rule(&mut parser, &tokens, &mut pos))
+            .or_else(|| panic_mode(&mut parser, &tokens, &mut pos, &recovery.sync));
+        match action {
+            Some(action) => errors.push(SyntaxError { error, action }),
+            None => {
+                errors.push(SyntaxError {
+                    error,
+                    action: RecoveryAction::GaveUp(),
+                });
+                return Recovered { tree: None, errors };
+            }
+        }
+    }
+}
+
+//tries every single token edit at pos and keeps the one that parses furthest, preferring insertions,
+//then deletions, then replacements, parser is left ready to continue at pos
+fn repair(
+    parser: &mut Parser,
+    tokens: &[Token],
+    pos: &mut usize,
+    check: usize,
+) -> Option<RecoveryAction> {
+    let token = &tokens[*pos];
+    let rest: Vec<Symbol> = tokens[*pos..].iter().map(|x| x.symbol).collect();
+    let candidates = parser.expected();
+    //an edit has to get through the edited token and check more, or to the end of input
+    let needed = |len: usize| 1 + check.max(1).min(len);
+    let mut best: Option<(usize, RecoveryAction)> = None;
+    let mut consider = |parsed: usize, needed: usize, action: RecoveryAction| {
+        if parsed >= needed && best.as_ref().map(|x| parsed > x.0).unwrap_or(true) {
+            best = Some((parsed, action));
+        }
+    };
+    for symbol in &candidates {
+        let edited = std::iter::once(symbol).chain(&rest);
+        let parsed = simulate(parser.table, parser.states.clone(), edited);
+        consider(
+            parsed,
+            needed(rest.len()),
+            RecoveryAction::Inserted(*symbol),
+        );
+    }
+    if token.symbol != Symbol::EOF() {
+        let parsed = simulate(parser.table, parser.states.clone(), rest[1..].iter());
+        consider(
+            parsed + 1,
+            needed(rest.len() - 1),
+            RecoveryAction::Deleted(token.clone()),
+        );
+        for symbol in &candidates {
+            if *symbol == Symbol::EOF() {
+                continue;
+            }
+            let edited = std::iter::once(symbol).chain(&rest[1..]);
+            let parsed = simulate(parser.table, parser.states.clone(), edited);
+            consider(
+                parsed,
+                needed(rest.len() - 1),
+                RecoveryAction::Replaced(token.clone(), *symbol),
+            );
+        }
+    }
+    let (_, action) = best?;
+    match &action {
+        RecoveryAction::Inserted(symbol) => {
+            let inserted = Token {
+                symbol: *symbol,
+                text: String::new(),
+                ..token.clone()
+            };
+            if let Step::Accepted = parser.step(&inserted) {
+                unreachable!("<eof> is never inserted before the end of input");
+            }
+        }
+        RecoveryAction::Deleted(_) => *pos += 1,
+        RecoveryAction::Replaced(token, symbol) => {
+            let replaced = Token {
+                symbol: *symbol,
+                ..token.clone()
+            };
+            parser.step(&replaced);
+            *pos += 1;
+        }
+        _ => {}
+    }
+    Some(action)
+}
+
+//pops to the nearest state that can shift <error>, shifts it, then skips tokens until one fits
+fn error_rule(parser: &mut Parser, tokens: &[Token], pos: &mut usize) -> Option<RecoveryAction> {
+    let error = parser.error_terminal?;
+    let depth = parser
+        .states
+        .iter()
+        .rposition(|x| matches!(parser.action(*x, &error), Some(TableCell::Shift(_))))?;
+    let state = match parser.action(parser.states[depth], &error) {
+        Some(TableCell::Shift(state)) => state,
+        _ => unreachable!(),
+    };
+    let mut states = parser.states[..=depth].to_vec();
+    states.push(state);
+    let skip = tokens[*pos..]
+        .iter()
+        .position(|x| parser.fits(&states, &x.symbol))?;
+    let skipped = tokens[*pos..*pos + skip].to_vec();
+    let text: Vec<&str> = skipped.iter().map(|x| x.text.as_str()).collect();
+    let first = &tokens[*pos];
+    parser.states = states;
+    parser.trees.truncate(depth);
+    parser.trees.push(ParseTree::Leaf(Token {
+        symbol: error,
+        text: text.join(" "),
+        line: first.line,
+        column: first.column,
+    }));
+    *pos += skip;
+    Some(RecoveryAction::ErrorRule(skipped))
+}
+
+//skips to a sync token or the end of input, then pops until a state takes it, either directly or
+//after a nonterminal standing in for the skipped input, like E in id = + ;
+//the stand in is a leaf whose token has the nonterminal as its symbol
+//if no state takes it the sync token is skipped too and the search goes on
+fn panic_mode(
+    parser: &mut Parser,
+    tokens: &[Token],
+    pos: &mut usize,
+    sync: &[Symbol],
+) -> Option<RecoveryAction> {
+    let start = *pos;
+    loop {
+        let found = tokens[*pos..]
+            .iter()
+            .position(|x| x.symbol == Symbol::EOF() || sync.contains(&x.symbol))?;
+        *pos += found;
+        let symbol = tokens[*pos].symbol;
+        for depth in (0..parser.states.len()).rev() {
+            let mut states = parser.states[..=depth].to_vec();
+            let stand_in = match parser.fits(&states, &symbol) {
+                true => None,
+                false => {
+                    let mut gotos: Vec<(&Symbol, &TableCell)> = parser.table.rows[states[depth]]
+                        .cells
+                        .iter()
+                        .filter(|(_, cell)| matches!(cell, TableCell::Goto(_)))
+                        .collect();
+                    gotos.sort();
+                    let fitting = gotos.into_iter().find(|(_, cell)| match cell {
+                        TableCell::Goto(state) => {
+                            let mut states = states.clone();
+                            states.push(*state);
+                            parser.fits(&states, &symbol)
+                        }
+                        _ => false,
+                    });
+                    match fitting {
+                        Some((nonterminal, TableCell::Goto(state))) => {
+                            states.push(*state);
+                            Some(*nonterminal)
+                        }
+                        _ => continue,
+                    }
+                }
+            };
+            let skipped = tokens[start..*pos].to_vec();
+            parser.states = states;
+            parser.trees.truncate(depth);
+            if let Some(nonterminal) = stand_in {
+                let texts: Vec<&str> = skipped.iter().map(|x| x.text.as_str()).collect();
+                let first = &tokens[start];
+                parser.trees.push(ParseTree::Leaf(Token {
+                    symbol: nonterminal,
+                    text: texts.join(" "),
+                    line: first.line,
+                    column: first.column,
+                }));
+            }
+            return Some(RecoveryAction::Skipped(skipped));
+        }
+        if symbol == Symbol::EOF() {
+            return None;
+        }
+        *pos += 1;
+    }
+}
@@ -1,3 +1,20 @@
+pub mod ambiguity;
+pub mod counterexample;
+pub mod coverage;
+pub mod differential;
+pub mod dot;
+pub mod earley;
+pub mod error_messages;
+pub mod glr;
 pub mod grammar;
+pub mod grammar_diff;
+pub mod ll1_generator;
 pub mod lr1_generator;
+pub mod lr_parser;
 pub mod parse_table;
+pub mod parse_tree;
+pub mod repl;
+pub mod report;
+pub mod table_cache;
+pub mod table_compression;
+pub mod table_serialization;
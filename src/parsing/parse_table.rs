@@ -1,22 +1,90 @@
 use super::grammar::*;
 use std::collections::HashMap;
 
+#[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
 pub enum TableCell {
     Shift(usize),
+    //index of the production, counting through CFG::productions in order
     Reduce(usize),
     Goto(usize),
     Accept(),
 }
 
+impl TableCell {
+    //shift, go to state 4 or reduce E -> E .+ .id, for reports and traces
+    pub fn describe(&self, cfg: &CFG) -> String {
+        match self {
+            TableCell::Shift(state) => format!("shift, go to state {}", state),
+            TableCell::Reduce(id) => format!("reduce {}", cfg.production_str(cfg.production(*id))),
+            TableCell::Goto(state) => format!("go to state {}", state),
+            TableCell::Accept() => String::from("accept"),
+        }
+    }
+}
+
 pub enum TableErr {
-    Conflict(TableCell, TableCell),
+    //existing cell, cell that couldn't be added, state, symbol
+    Conflict(TableCell, TableCell, usize, Symbol),
 }
 
 pub struct TableRow {
     pub cells: HashMap<Symbol, TableCell>,
+    //actions that conflict with the one in cells, only filled in by lr1_generator::generate_glr
+    pub extra: HashMap<Symbol, Vec<TableCell>>,
+}
+
+impl TableRow {
+    pub fn new(cells: HashMap<Symbol, TableCell>) -> TableRow {
+        TableRow {
+            cells,
+            extra: HashMap::new(),
+        }
+    }
+    //every action on the symbol, the one in cells first
+    pub fn actions(&self, symbol: &Symbol) -> Vec<TableCell> {
+        let mut actions: Vec<TableCell> = self.cells.get(symbol).copied().into_iter().collect();
+        if let Some(extra) = self.extra.get(symbol) {
+            actions.extend(extra);
+        }
+        actions
+    }
 }
 
-pub struct Table<'a> {
+pub struct Table {
     pub rows: Vec<TableRow>,
-    pub cfg: &'a CFG<'a>,
+    //the grammar the table was generated from
+    pub cfg: CFG,
+}
+
+impl Table {
+    //terminals and <eof> with an action in the state, sorted, what an error message there should expect
+    //<error> is left out since it never comes from the input
+    pub fn expected(&self, state: usize) -> Vec<Symbol> {
+        let error = self
+            .cfg
+            .terminal_symbols
+            .get(builder::ERROR_SYMBOL)
+            .map(Symbol::Terminal);
+        let mut expected: Vec<Symbol> = self.rows[state]
+            .cells
+            .keys()
+            .filter(|x| matches!(x, Symbol::Terminal(_) | Symbol::EOF()))
+            .filter(|x| Some(**x) != error)
+            .copied()
+            .collect();
+        expected.sort();
+        expected
+    }
+    //state, symbol and every action for cells with more than one action, sorted
+    pub fn conflicts(&self) -> Vec<(usize, Symbol, Vec<TableCell>)> {
+        let mut conflicts = Vec::new();
+        for (state, row) in self.rows.iter().enumerate() {
+            let mut symbols: Vec<&Symbol> = row.extra.keys().collect();
+            symbols.sort();
+            for symbol in symbols {
+                conflicts.push((state, *symbol, row.actions(symbol)));
+            }
+        }
+        conflicts
+    }
 }
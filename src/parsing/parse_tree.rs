@@ -0,0 +1,244 @@
+//input and output types shared by the parser runtimes
+
+use super::grammar::builder::ERROR_SYMBOL;
+use super::grammar::{Symbol, CFG};
+use std::fmt;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Token {
+    //Terminal or EOF, or a Nonterminal for input lr_parser skipped while recovering from an error
+    pub symbol: Symbol,
+    pub text: String,
+    //1 based
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Token {
+    //the end of input, placed just after the last token
+    pub fn eof_after(tokens: &[Token]) -> Token {
+        let (line, column) = match tokens.last() {
+            Some(t) => (t.line, t.column + t.text.chars().count()),
+            None => (1, 1),
+        };
+        Token {
+            symbol: Symbol::EOF(),
+            text: String::new(),
+            line,
+            column,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseTree {
+    Leaf(Token),
+    //production id and one child per rhs symbol, <empty> has no child
+    //the root is always the start rule S' -> S <eof>
+    Node(usize, Vec<ParseTree>),
+}
+
+//the default drop recurses once per level, which overflows on deep trees
+impl Drop for ParseTree {
+    fn drop(&mut self) {
+        let mut stack = match self {
+            ParseTree::Node(_, children) => std::mem::take(children),
+            ParseTree::Leaf(_) => return,
+        };
+        while let Some(mut tree) = stack.pop() {
+            if let ParseTree::Node(_, children) = &mut tree {
+                stack.append(children);
+            }
+        }
+    }
+}
+
+impl ParseTree {
+    //(E (T (F id))), leaves are written with their text
+    pub fn to_sexpr(&self, cfg: &CFG) -> String {
+        let mut out = String::new();
+        self.write_sexpr(cfg, &mut out);
+        out
+    }
+
+    fn write_sexpr(&self, cfg: &CFG, out: &mut String) {
+        match self {
+            ParseTree::Leaf(token) => out.push_str(&token.text),
+            ParseTree::Node(production, children) => {
+                out.push('(');
+                out.push_str(&cfg.nonterminal_symbols[cfg.production(*production).nonterminal]);
+                for child in children {
+                    //the end of input has no text
+                    if let ParseTree::Leaf(Token {
+                        symbol: Symbol::EOF(),
+                        ..
+                    }) = child
+                    {
+                        continue;
+                    }
+                    out.push(' ');
+                    child.write_sexpr(cfg, out);
+                }
+                out.push(')');
+            }
+        }
+    }
+
+    //one node per line, children indented under their parent, leaves as their symbol and text if that
+    //isn't just the symbol's name
+    pub fn to_indented(&self, cfg: &CFG) -> String {
+        let mut out = String::new();
+        self.write_indented(cfg, 0, &mut out);
+        out
+    }
+
+    fn write_indented(&self, cfg: &CFG, depth: usize, out: &mut String) {
+        out.push_str(&"  ".repeat(depth));
+        match self {
+            ParseTree::Leaf(token) => {
+                let name = cfg.symbol_str(&token.symbol);
+                out.push_str(name);
+                //id for .id says nothing new
+                let same =
+                    token.text == name || Some(token.text.as_str()) == name.strip_prefix('.');
+                if !token.text.is_empty() && !same {
+                    out.push_str(&format!(" \"{}\"", token.text));
+                }
+                out.push('\n');
+            }
+            ParseTree::Node(production, children) => {
+                out.push_str(&cfg.nonterminal_symbols[cfg.production(*production).nonterminal]);
+                out.push('\n');
+                for child in children {
+                    child.write_indented(cfg, depth + 1, out);
+                }
+            }
+        }
+    }
+
+    //tokens at the leaves, in input order
+    pub fn tokens(&self) -> Vec<&Token> {
+        let mut tokens = Vec::new();
+        let mut stack = vec![self];
+        while let Some(tree) = stack.pop() {
+            match tree {
+                ParseTree::Leaf(token) => tokens.push(token),
+                ParseTree::Node(_, children) => stack.extend(children.iter().rev()),
+            }
+        }
+        tokens
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum ParseError {
+    //text, line and column of input that isn't a terminal
+    UnknownToken(String, usize, usize),
+    //token that was found and the symbols that would have been accepted, sorted
+    Unexpected(Token, Vec<Symbol>),
+}
+
+impl ParseError {
+    pub fn message(&self, cfg: &CFG) -> String {
+        match self {
+            ParseError::UnknownToken(text, line, column) => {
+                format!("unknown token {} at {}:{}", text, line, column)
+            }
+            ParseError::Unexpected(token, expected) if expected.is_empty() => format!(
+                "unexpected {} at {}:{}",
+                cfg.describe(&token.symbol),
+                token.line,
+                token.column
+            ),
+            ParseError::Unexpected(token, expected) => format!(
+                "expected {}, found {} at {}:{}",
+                cfg.describe_list(expected),
+                cfg.describe(&token.symbol),
+                token.line,
+                token.column
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnknownToken(text, line, column) => {
+                write!(f, "unknown token {} at {}:{}", text, line, column)
+            }
+            ParseError::Unexpected(token, expected) => write!(
+                f,
+                "unexpected {:?} at {}:{}, expected {:?}",
+                token.symbol, token.line, token.column, expected
+            ),
+        }
+    }
+}
+
+//splits the input on whitespace and looks every word up as a terminal name
+//a word that isn't a terminal also matches the terminal with a . in front, so "id + id" works for .id and .+
+pub fn tokenize(cfg: &CFG, input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    for (line_index, line) in input.lines().enumerate() {
+        let mut column = 0;
+        let mut chars = line.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            column += 1;
+            if c.is_whitespace() {
+                continue;
+            }
+            let start_column = column;
+            let mut end = start + c.len_utf8();
+            while let Some((i, c)) = chars.peek().copied() {
+                if c.is_whitespace() {
+                    break;
+                }
+                end = i + c.len_utf8();
+                column += 1;
+                chars.next();
+            }
+            let text = &line[start..end];
+            let id = cfg
+                .terminal_symbols
+                .get(text)
+                .or_else(|| cfg.terminal_symbols.get(&format!(".{}", text)))
+                .filter(|_| text != ERROR_SYMBOL);
+            match id {
+                Some(id) => tokens.push(Token {
+                    symbol: Symbol::Terminal(id),
+                    text: String::from(text),
+                    line: line_index + 1,
+                    column: start_column,
+                }),
+                None => {
+                    return Err(ParseError::UnknownToken(
+                        String::from(text),
+                        line_index + 1,
+                        start_column,
+                    ))
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+//tokens for a sentence of terminals on one line, with the terminal names as their text
+pub fn symbol_tokens(cfg: &CFG, sentence: &[Symbol]) -> Vec<Token> {
+    let mut column = 1;
+    sentence
+        .iter()
+        .map(|symbol| {
+            let text = String::from(cfg.symbol_str(symbol));
+            let token = Token {
+                symbol: *symbol,
+                line: 1,
+                column,
+                text,
+            };
+            column += token.text.chars().count() + 1;
+            token
+        })
+        .collect()
+}
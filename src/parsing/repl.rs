@@ -0,0 +1,145 @@
+//the session behind lmaker repl: each line of input is tokenized and parsed, showing the tokens,
+//every action the lr(1) parser takes with the state stack it takes it on, and the tree
+//lines starting with : are commands, :reload reads the grammar file again without leaving
+
+use super::grammar::CFG;
+use super::lr1_generator;
+use super::lr_parser::{self, TraceStep};
+use super::parse_table::{Table, TableErr};
+use super::parse_tree::{tokenize, Token};
+use super::report;
+use std::path::{Path, PathBuf};
+
+pub const HELP: &str = "type input to parse it, or a command:
+  :reload, :r     read the grammar file again
+  :grammar        print the numbered productions
+  :help           this list
+  :quit, :q       leave";
+
+pub struct Repl {
+    pub path: PathBuf,
+    pub table: Table,
+}
+
+impl Repl {
+    pub fn load(path: &Path) -> Result<Repl, String> {
+        Ok(Repl {
+            path: path.to_path_buf(),
+            table: load_table(path)?,
+        })
+    }
+
+    //a grammar that doesn't load leaves the previous one in place
+    pub fn reload(&mut self) -> String {
+        match load_table(&self.path) {
+            Ok(table) => {
+                self.table = table;
+                format!("reloaded {}, {}\n", self.path.display(), self.summary())
+            }
+            Err(e) => format!("{}\nstill using the previous grammar\n", e),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} productions, {} states",
+            self.table.cfg.production_count(),
+            self.table.rows.len()
+        )
+    }
+
+    //what to print for a line, None to leave
+    pub fn eval(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        let out = match line {
+            "" => String::new(),
+            ":quit" | ":q" => return None,
+            ":reload" | ":r" => self.reload(),
+            ":grammar" => report::grammar_section(&self.table.cfg),
+            ":help" => format!("{}\n", HELP),
+            _ if line.starts_with(':') => {
+                format!("unknown command {}, :help lists them\n", line)
+            }
+            _ => explain(&self.table, line),
+        };
+        Some(out)
+    }
+}
+
+fn load_table(path: &Path) -> Result<Table, String> {
+    let cfg = CFG::load(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    lr1_generator::generate(&cfg).map_err(|e| match e {
+        TableErr::Conflict(_, _, state, symbol) => format!(
+            "{} isn't lr(1), conflict in state {} on {}, lmaker conflicts shows an example",
+            path.display(),
+            state,
+            cfg.symbol_str(&symbol)
+        ),
+    })
+}
+
+//the tokens, each action with the stack before it, then the tree or the syntax error
+pub fn explain(table: &Table, input: &str) -> String {
+    let cfg = &table.cfg;
+    let tokens = match tokenize(cfg, input) {
+        Ok(tokens) => tokens,
+        Err(e) => return format!("error: {}\n", e.message(cfg)),
+    };
+    let mut out = format!("tokens: {}\n", tokens_str(cfg, &tokens));
+    let mut trace = Vec::new();
+    let result = lr_parser::parse_traced(table, &tokens, &mut trace);
+    out.push_str(&trace_str(cfg, &trace));
+    match result {
+        Ok(tree) => {
+            out.push_str("tree:\n");
+            out.push_str(&tree.to_indented(cfg));
+        }
+        Err(e) => out.push_str(&format!("error: {}\n", e.message(cfg))),
+    }
+    out
+}
+
+//.id "x" .+ .id "y", the text is left out when it's just the terminal's name
+fn tokens_str(cfg: &CFG, tokens: &[Token]) -> String {
+    let words: Vec<String> = tokens
+        .iter()
+        .map(|token| {
+            let name = cfg.symbol_str(&token.symbol);
+            match token.text == name || Some(token.text.as_str()) == name.strip_prefix('.') {
+                true => String::from(name),
+                false => format!("{} \"{}\"", name, token.text),
+            }
+        })
+        .collect();
+    match words.is_empty() {
+        true => String::from("none"),
+        false => words.join(" "),
+    }
+}
+
+//one line per step, the stack, the symbol and the action, in columns
+pub fn trace_str(cfg: &CFG, trace: &[TraceStep]) -> String {
+    let stacks: Vec<String> = trace
+        .iter()
+        .map(|step| {
+            let states: Vec<String> = step.states.iter().map(|x| x.to_string()).collect();
+            states.join(" ")
+        })
+        .collect();
+    let stack_width = stacks.iter().map(|x| x.len()).max().unwrap_or(0);
+    let symbol_width = trace
+        .iter()
+        .map(|x| cfg.symbol_str(&x.symbol).chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for (step, stack) in trace.iter().zip(&stacks) {
+        out.push_str(&format!(
+            "  {:<stack_width$}  {:<symbol_width$}  {}\n",
+            stack,
+            cfg.symbol_str(&step.symbol),
+            step.action.describe(cfg),
+        ));
+    }
+    out
+}
@@ -0,0 +1,125 @@
+//plain text description of a grammar's lr(1) table, like bison's .output file
+//everything is sorted, so the report only changes where the table does and can be diffed in review
+//
+//sections: the numbered productions, first and follow sets, conflicts, then each state with
+//its kernel items, closure items, actions and gotos
+
+use super::dot::item_str;
+use super::grammar::{Symbol, CFG};
+use super::lr1_generator;
+use std::collections::HashSet;
+
+pub fn report(cfg: &CFG) -> String {
+    let automaton = lr1_generator::automaton(cfg);
+    //conflicting actions are kept so every state can be listed
+    let table = lr1_generator::generate_glr(cfg);
+    let mut out = grammar_section(cfg);
+    out.push('\n');
+    out.push_str(&sets_section(cfg));
+    out.push_str("\nConflicts\n\n");
+    let conflicts = table.conflicts();
+    if conflicts.is_empty() {
+        out.push_str("    none\n");
+    }
+    for (state, symbol, cells) in &conflicts {
+        let cells: Vec<String> = cells.iter().map(|x| x.describe(cfg)).collect();
+        //there are no precedence declarations, so nothing is resolved
+        out.push_str(&format!(
+            "    state {} on {}: {}, unresolved\n",
+            state,
+            cfg.symbol_str(symbol),
+            cells.join(" / ")
+        ));
+    }
+    for (id, state) in automaton.states.iter().enumerate() {
+        out.push_str(&format!("\n\nState {}\n\n", id));
+        //kernel items are the ones the state was made from, the rest come from the closure
+        let (kernel, closure): (Vec<_>, Vec<_>) = state
+            .items
+            .iter()
+            .partition(|x| x.dot > 0 || (id == 0 && x.production == 0));
+        for item in &kernel {
+            out.push_str(&format!("    {}\n", item_str(cfg, item)));
+        }
+        if !closure.is_empty() {
+            out.push('\n');
+        }
+        for item in &closure {
+            out.push_str(&format!("    {}\n", item_str(cfg, item)));
+        }
+        let row = &table.rows[id];
+        let mut symbols: Vec<&Symbol> = row.cells.keys().collect();
+        symbols.sort();
+        let (actions, gotos): (Vec<&Symbol>, Vec<&Symbol>) = symbols
+            .into_iter()
+            .partition(|x| !matches!(x, Symbol::Nonterminal(_)));
+        for list in [actions, gotos] {
+            if list.is_empty() {
+                continue;
+            }
+            out.push('\n');
+            let width = list.iter().map(|x| cfg.symbol_str(x).len()).max().unwrap();
+            for symbol in list {
+                let cells: Vec<String> = row
+                    .actions(symbol)
+                    .iter()
+                    .map(|x| x.describe(cfg))
+                    .collect();
+                out.push_str(&format!(
+                    "    {:<width$}  {}\n",
+                    cfg.symbol_str(symbol),
+                    cells.join(" / "),
+                    width = width
+                ));
+            }
+        }
+    }
+    out
+}
+
+//productions numbered like TableCell::Reduce
+pub fn grammar_section(cfg: &CFG) -> String {
+    let mut out = String::from("Grammar\n\n");
+    for id in 0..cfg.production_count() {
+        out.push_str(&format!(
+            "    {:>3} {}\n",
+            id,
+            cfg.production_str(cfg.production(id))
+        ));
+    }
+    if !cfg.aliases.is_empty() {
+        out.push_str("\nAliases\n\n");
+        let mut aliases: Vec<(&usize, &String)> = cfg.aliases.iter().collect();
+        aliases.sort();
+        for (terminal, alias) in aliases {
+            out.push_str(&format!(
+                "    {} = \"{}\"\n",
+                &cfg.terminal_symbols[*terminal], alias
+            ));
+        }
+    }
+    out
+}
+
+pub fn sets_section(cfg: &CFG) -> String {
+    let firsts = cfg.generate_firsts();
+    let follows = cfg.generate_follows(&firsts);
+    let mut out = String::from("First sets\n\n");
+    write_sets(cfg, &firsts, &mut out);
+    out.push_str("\nFollow sets\n\n");
+    write_sets(cfg, &follows, &mut out);
+    out
+}
+
+fn write_sets(cfg: &CFG, sets: &[HashSet<Symbol>], out: &mut String) {
+    for (index, set) in sets.iter().enumerate() {
+        let mut symbols: Vec<&Symbol> = set.iter().collect();
+        symbols.sort();
+        let names: Vec<&str> = symbols.iter().map(|x| cfg.symbol_str(x)).collect();
+        out.push_str(&format!(
+            "    {}: {}\n",
+            &cfg.nonterminal_symbols[index],
+            names.join(" ")
+        ));
+    }
+}
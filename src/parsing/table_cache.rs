@@ -0,0 +1,126 @@
+//caches generated tables on disk, keyed by a hash of the grammar and the generator options
+//the key only depends on the parsed grammar, so formatting changes in the grammar file still hit the cache
+//the cache is best effort: unreadable or mismatched entries are regenerated and write failures are ignored
+
+use super::grammar::{Symbol, CFG};
+use super::parse_table::{Table, TableErr};
+use super::table_serialization::{SavedTable, FORMAT_VERSION};
+use crate::util::hash::Fnv64;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_CACHE_DIR: &str = ".lmaker-cache";
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum CacheStatus {
+    Hit,
+    Miss,
+    //caching was turned off for this lookup
+    Disabled,
+}
+
+pub struct TableCache {
+    pub dir: PathBuf,
+    pub enabled: bool,
+}
+
+impl TableCache {
+    pub fn new(dir: &Path) -> TableCache {
+        TableCache {
+            dir: dir.to_path_buf(),
+            enabled: true,
+        }
+    }
+
+    //hex key for the grammar, options describes anything else that changes the generated table
+    pub fn key(cfg: &CFG, options: &str) -> String {
+        let mut hasher = Fnv64::new();
+        hasher.write_usize(FORMAT_VERSION as usize);
+        hasher.write_str(options);
+        hasher.write_usize(cfg.terminal_symbols.len());
+        for t in &cfg.terminal_symbols {
+            hasher.write_str(t);
+        }
+        hasher.write_usize(cfg.nonterminal_symbols.len());
+        for nt in &cfg.nonterminal_symbols {
+            hasher.write_str(nt);
+        }
+        for production in cfg.productions.iter().flatten() {
+            hasher.write_usize(production.nonterminal);
+            hasher.write_usize(production.rhs.len());
+            for symbol in &production.rhs {
+                let (tag, x) = match symbol {
+                    Symbol::Terminal(x) => (0, *x),
+                    Symbol::Nonterminal(x) => (1, *x),
+                    Symbol::Empty() => (2, 0),
+                    Symbol::EOF() => (3, 0),
+                };
+                hasher.write(&[tag]);
+                hasher.write_usize(x);
+            }
+        }
+        format!("{:016x}", hasher.finish())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.lmt", key))
+    }
+
+    //None if there's no entry, it can't be read, or it was saved for a different grammar
+    pub fn load(&self, key: &str, cfg: &CFG) -> Option<SavedTable> {
+        let bytes = std::fs::read(self.path(key)).ok()?;
+        let saved = SavedTable::from_binary(&bytes).ok()?;
+        //guard against hash collisions
+        let same = saved
+            .terminal_symbols
+            .iter()
+            .eq(cfg.terminal_symbols.iter())
+            && saved
+                .nonterminal_symbols
+                .iter()
+                .eq(cfg.nonterminal_symbols.iter())
+            && saved.productions.len() == cfg.production_count()
+            && saved
+                .productions
+                .iter()
+                .zip(cfg.productions.iter().flatten())
+                .all(|(a, b)| a.nonterminal == b.nonterminal && a.rhs == b.rhs);
+        if same {
+            Some(saved)
+        } else {
+            None
+        }
+    }
+
+    pub fn store(&self, key: &str, table: &SavedTable) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        //write then rename so a concurrent build never reads half a file
+        let tmp = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        std::fs::write(&tmp, table.to_binary())?;
+        std::fs::rename(&tmp, self.path(key))
+    }
+
+    //loads the table from the cache, or runs generate and stores the result
+    pub fn get_or_generate<F>(
+        &self,
+        cfg: &CFG,
+        options: &str,
+        generate: F,
+    ) -> Result<(Table, CacheStatus), TableErr>
+    where
+        F: FnOnce() -> Result<Table, TableErr>,
+    {
+        if !self.enabled {
+            return generate().map(|t| (t, CacheStatus::Disabled));
+        }
+        let key = TableCache::key(cfg, options);
+        if let Some(saved) = self.load(&key, cfg) {
+            //aliases aren't part of the key, so they come from the grammar rather than the cache
+            let mut table = saved.table();
+            table.cfg = cfg.clone();
+            return Ok((table, CacheStatus::Hit));
+        }
+        let table = generate()?;
+        let _ = self.store(&key, &SavedTable::from_table(&table));
+        Ok((table, CacheStatus::Miss))
+    }
+}
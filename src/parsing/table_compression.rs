@@ -0,0 +1,281 @@
+//compact encodings of a parse table for emitted parsers
+//1. default reductions: the most common reduction in a state replaces its explicit entries
+//2. row deduplication: states with identical action (or goto) rows share one row
+//3. comb vectors: distinct rows are overlapped into one array (row displacement), a check array
+//   records which row owns each slot
+//lookup for (state, column): slot = base[row[state]] + column, hit if check[slot] == row[state]
+
+use super::grammar::Symbol;
+use super::parse_table::{Table, TableCell};
+use std::collections::HashMap;
+
+pub struct CompressionOptions {
+    //replaces the most common reduction in each state with a default,
+    //error entries in that state become the reduction, the error is found before the next shift
+    pub default_reductions: bool,
+}
+
+impl Default for CompressionOptions {
+    fn default() -> Self {
+        CompressionOptions {
+            default_reductions: true,
+        }
+    }
+}
+
+//one comb vector, used for both the action and goto tables
+pub struct PackedTable {
+    //state -> row id, states with equal rows share an id
+    pub row: Vec<usize>,
+    //row id -> offset into next/check
+    pub base: Vec<usize>,
+    pub next: Vec<Option<TableCell>>,
+    //row id that owns the slot
+    pub check: Vec<Option<usize>>,
+}
+
+impl PackedTable {
+    fn get(&self, state: usize, column: usize) -> Option<TableCell> {
+        let row = self.row[state];
+        let slot = self.base[row] + column;
+        match self.check.get(slot) {
+            Some(Some(owner)) if *owner == row => self.next[slot],
+            _ => None,
+        }
+    }
+
+    //rows are (column, cell) lists for each state
+    fn pack(rows: Vec<Vec<(usize, TableCell)>>) -> PackedTable {
+        //dedup rows
+        let mut row_ids: HashMap<Vec<(usize, TableCell)>, usize> = HashMap::new();
+        let mut distinct: Vec<Vec<(usize, TableCell)>> = Vec::new();
+        let mut row = Vec::with_capacity(rows.len());
+        for r in rows {
+            let id = *row_ids.entry(r.clone()).or_insert_with(|| {
+                distinct.push(r);
+                distinct.len() - 1
+            });
+            row.push(id);
+        }
+        //first fit, placing the densest rows first since they're hardest to fit
+        let mut order: Vec<usize> = (0..distinct.len()).collect();
+        order.sort_by_key(|x| (std::cmp::Reverse(distinct[*x].len()), *x));
+        let mut base = vec![0; distinct.len()];
+        let mut next: Vec<Option<TableCell>> = Vec::new();
+        let mut check: Vec<Option<usize>> = Vec::new();
+        for id in order {
+            let cells = &distinct[id];
+            if cells.is_empty() {
+                continue;
+            }
+            let mut offset = 0;
+            while !cells
+                .iter()
+                .all(|(col, _)| check.get(offset + col).map(|x| x.is_none()) != Some(false))
+            {
+                offset += 1;
+            }
+            base[id] = offset;
+            for (col, cell) in cells {
+                let slot = offset + col;
+                if slot >= next.len() {
+                    next.resize(slot + 1, None);
+                    check.resize(slot + 1, None);
+                }
+                next[slot] = Some(*cell);
+                check[slot] = Some(id);
+            }
+        }
+        PackedTable {
+            row,
+            base,
+            next,
+            check,
+        }
+    }
+
+    //next as encode_cell values
+    pub fn encoded_next(&self) -> Vec<usize> {
+        self.next.iter().map(|x| encode_cell(*x)).collect()
+    }
+
+    //check with 0 for free slots, otherwise owning row id + 1
+    pub fn encoded_check(&self) -> Vec<usize> {
+        self.check
+            .iter()
+            .map(|x| x.map(|r| r + 1).unwrap_or(0))
+            .collect()
+    }
+
+    fn distinct_rows(&self) -> usize {
+        self.base.len()
+    }
+
+    //entries needed to store this table: row map, bases, and next/check pairs
+    fn size(&self) -> usize {
+        self.row.len() + self.base.len() + self.next.len() * 2
+    }
+}
+
+pub struct CompressedTable {
+    //columns 0..terminal_count are terminals, terminal_count is <eof>
+    pub terminal_count: usize,
+    pub nonterminal_count: usize,
+    //production index used when a state has no explicit action
+    pub default_reductions: Vec<Option<usize>>,
+    pub actions: PackedTable,
+    pub gotos: PackedTable,
+}
+
+pub struct CompressionStats {
+    pub states: usize,
+    //states * (terminals + <eof> + nonterminals)
+    pub dense_entries: usize,
+    //cells actually present in the table
+    pub filled_entries: usize,
+    pub compressed_entries: usize,
+    pub distinct_action_rows: usize,
+    pub distinct_goto_rows: usize,
+    pub default_reductions: usize,
+}
+
+impl CompressionStats {
+    //compressed size as a fraction of the dense size
+    pub fn ratio(&self) -> f64 {
+        if self.dense_entries == 0 {
+            return 1.0;
+        }
+        self.compressed_entries as f64 / self.dense_entries as f64
+    }
+}
+
+pub fn compress(table: &Table, options: &CompressionOptions) -> CompressedTable {
+    let terminal_count = table.cfg.terminal_symbols.len();
+    let nonterminal_count = table.cfg.nonterminal_symbols.len();
+    let mut action_rows = Vec::with_capacity(table.rows.len());
+    let mut goto_rows = Vec::with_capacity(table.rows.len());
+    let mut default_reductions = Vec::with_capacity(table.rows.len());
+    for row in &table.rows {
+        let mut actions = Vec::new();
+        let mut gotos = Vec::new();
+        for (symbol, cell) in &row.cells {
+            match symbol {
+                Symbol::Terminal(x) => actions.push((*x, *cell)),
+                Symbol::EOF() => actions.push((terminal_count, *cell)),
+                Symbol::Nonterminal(x) => gotos.push((*x, *cell)),
+                Symbol::Empty() => (),
+            }
+        }
+        let default = if options.default_reductions {
+            most_common_reduction(&actions)
+        } else {
+            None
+        };
+        if let Some(production) = default {
+            actions.retain(|(_, cell)| *cell != TableCell::Reduce(production));
+        }
+        actions.sort_by_key(|x| x.0);
+        gotos.sort_by_key(|x| x.0);
+        action_rows.push(actions);
+        goto_rows.push(gotos);
+        default_reductions.push(default);
+    }
+    CompressedTable {
+        terminal_count,
+        nonterminal_count,
+        default_reductions,
+        actions: PackedTable::pack(action_rows),
+        gotos: PackedTable::pack(goto_rows),
+    }
+}
+
+//ties go to the lowest production index so compression is deterministic
+fn most_common_reduction(actions: &[(usize, TableCell)]) -> Option<usize> {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for (_, cell) in actions {
+        if let TableCell::Reduce(p) = cell {
+            *counts.entry(*p).or_insert(0) += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(p, count)| (*count, std::cmp::Reverse(*p)))
+        .map(|(p, _)| p)
+}
+
+impl CompressedTable {
+    //same as looking up the symbol in the dense row, except error entries
+    //in states with a default reduction return that reduction
+    pub fn get(&self, state: usize, symbol: &Symbol) -> Option<TableCell> {
+        match symbol {
+            Symbol::Terminal(x) => self.action(state, *x),
+            Symbol::EOF() => self.action(state, self.terminal_count),
+            Symbol::Nonterminal(x) => self.gotos.get(state, *x),
+            Symbol::Empty() => None,
+        }
+    }
+
+    //column is a terminal index, or terminal_count for <eof>
+    pub fn action(&self, state: usize, column: usize) -> Option<TableCell> {
+        self.actions
+            .get(state, column)
+            .or_else(|| self.default_reductions[state].map(TableCell::Reduce))
+    }
+
+    pub fn goto(&self, state: usize, nonterminal: usize) -> Option<usize> {
+        match self.gotos.get(state, nonterminal) {
+            Some(TableCell::Goto(x)) => Some(x),
+            _ => None,
+        }
+    }
+
+    //default_reductions with 0 for none, otherwise production index + 1
+    pub fn encoded_default_reductions(&self) -> Vec<usize> {
+        self.default_reductions
+            .iter()
+            .map(|x| x.map(|p| p + 1).unwrap_or(0))
+            .collect()
+    }
+
+    pub fn stats(&self, table: &Table) -> CompressionStats {
+        let states = table.rows.len();
+        CompressionStats {
+            states,
+            dense_entries: states * (self.terminal_count + 1 + self.nonterminal_count),
+            filled_entries: table.rows.iter().map(|x| x.cells.len()).sum(),
+            compressed_entries: self.default_reductions.len()
+                + self.actions.size()
+                + self.gotos.size(),
+            distinct_action_rows: self.actions.distinct_rows(),
+            distinct_goto_rows: self.gotos.distinct_rows(),
+            default_reductions: self
+                .default_reductions
+                .iter()
+                .filter(|x| x.is_some())
+                .count(),
+        }
+    }
+}
+
+//integer form of a cell for emitting into generated source
+//0 is an empty cell, otherwise the low 2 bits are the kind and the rest is the target
+//  shift: 4s+1, reduce: 4p+2, goto: 4g+3, accept: 4
+pub fn encode_cell(cell: Option<TableCell>) -> usize {
+    match cell {
+        None => 0,
+        Some(TableCell::Shift(x)) => 4 * x + 1,
+        Some(TableCell::Reduce(x)) => 4 * x + 2,
+        Some(TableCell::Goto(x)) => 4 * x + 3,
+        Some(TableCell::Accept()) => 4,
+    }
+}
+
+pub fn decode_cell(x: usize) -> Option<TableCell> {
+    match (x % 4, x / 4) {
+        (0, 0) => None,
+        (0, _) => Some(TableCell::Accept()),
+        (1, s) => Some(TableCell::Shift(s)),
+        (2, p) => Some(TableCell::Reduce(p)),
+        (_, g) => Some(TableCell::Goto(g)),
+    }
+}
@@ -0,0 +1,796 @@
+//owned, versioned save format for parse tables, as json (for inspection) or binary (for shipping)
+//both formats hold the grammar symbols, productions and every table cell, and load back into a Table
+//
+//json layout:
+//  { "format": "lmaker-table", "version": 1,
+//    "terminals": [".+", ...], "nonterminals": ["S'", ...],
+//    "productions": [{"lhs": 0, "rhs": ["n1", "eof"]}, ...],
+//    "rows": [{"t0": "s4", "n1": "g2", "eof": "acc"}, ...],
+//    "aliases": {"t3": "a number", ...},
+//    "extra": [{"row": 5, "symbol": "t0", "cells": ["r3"]}, ...] }
+//  symbols are t<index>, n<index>, eof or empty. cells are s<state>, r<production>, g<state> or acc
+//  aliases are new in version 2 and extra in version 3, both optional
+//
+//binary layout (integers are unsigned leb128 unless noted):
+//  magic "LMKT", version (u32 little endian), terminal count, terminal strings,
+//  nonterminal count, nonterminal strings, production count, productions (lhs, rhs length, symbols),
+//  row count, rows (cell count, (symbol, cell) pairs), alias count, aliases (terminal, string),
+//  extra count, extra (row, symbol, cell count, cells)
+//  the aliases are left out before version 2 and extra before version 3
+//  strings are a length followed by utf-8 bytes, symbols and cells are a tag byte followed by the index
+
+use super::grammar::{CFGProduction, Symbol, SymbolTable, CFG};
+use super::parse_table::{Table, TableCell, TableRow};
+use crate::util::json::{Json, JsonError};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+pub const FORMAT_VERSION: u32 = 3;
+const JSON_FORMAT_NAME: &str = "lmaker-table";
+const BINARY_MAGIC: &[u8; 4] = b"LMKT";
+
+pub enum TableFormatError {
+    Json(JsonError),
+    //the data doesn't start with the right magic number or format name
+    NotATable,
+    //saved by a newer lmaker
+    UnsupportedVersion(u32),
+    //binary data ended early
+    Truncated,
+    //description of what is wrong
+    Invalid(String),
+}
+
+impl fmt::Display for TableFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableFormatError::Json(e) => write!(f, "{}", e),
+            TableFormatError::NotATable => write!(f, "not an lmaker parse table"),
+            TableFormatError::UnsupportedVersion(v) => write!(
+                f,
+                "table format version {} is newer than supported version {}",
+                v, FORMAT_VERSION
+            ),
+            TableFormatError::Truncated => write!(f, "table data is truncated"),
+            TableFormatError::Invalid(msg) => write!(f, "invalid table: {}", msg),
+        }
+    }
+}
+
+impl fmt::Debug for TableFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct SavedProduction {
+    pub nonterminal: usize,
+    pub rhs: Vec<Symbol>,
+}
+
+//owned copy of a table and the grammar it was generated from
+#[derive(PartialEq, Eq, Debug)]
+pub struct SavedTable {
+    pub terminal_symbols: Vec<String>,
+    pub nonterminal_symbols: Vec<String>,
+    //in production id order
+    pub productions: Vec<SavedProduction>,
+    //cells of each row, sorted by symbol
+    pub rows: Vec<Vec<(Symbol, TableCell)>>,
+    //terminal id and alias, sorted by terminal
+    pub aliases: Vec<(usize, String)>,
+    //TableRow::extra of each row, sorted by row and symbol. only glr tables have any
+    pub extra: Vec<(usize, Symbol, Vec<TableCell>)>,
+}
+
+impl SavedTable {
+    pub fn from_table(table: &Table) -> SavedTable {
+        let cfg = &table.cfg;
+        SavedTable {
+            terminal_symbols: cfg.terminal_symbols.iter().map(|x| x.to_string()).collect(),
+            nonterminal_symbols: cfg
+                .nonterminal_symbols
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+            productions: cfg
+                .productions
+                .iter()
+                .flatten()
+                .map(|p| SavedProduction {
+                    nonterminal: p.nonterminal,
+                    rhs: p.rhs.clone(),
+                })
+                .collect(),
+            rows: table
+                .rows
+                .iter()
+                .map(|row| {
+                    let mut cells: Vec<(Symbol, TableCell)> =
+                        row.cells.iter().map(|(s, c)| (*s, *c)).collect();
+                    cells.sort_by_key(|x| x.0);
+                    cells
+                })
+                .collect(),
+            aliases: {
+                let mut aliases: Vec<(usize, String)> =
+                    cfg.aliases.iter().map(|(t, a)| (*t, a.clone())).collect();
+                aliases.sort();
+                aliases
+            },
+            extra: {
+                let mut extra: Vec<(usize, Symbol, Vec<TableCell>)> = table
+                    .rows
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(state, row)| {
+                        row.extra
+                            .iter()
+                            .map(move |(s, cells)| (state, *s, cells.clone()))
+                    })
+                    .collect();
+                extra.sort_by_key(|x| (x.0, x.1));
+                extra
+            },
+        }
+    }
+
+    pub fn cfg(&self) -> CFG {
+        let mut productions: Vec<Vec<CFGProduction>> = self
+            .nonterminal_symbols
+            .iter()
+            .map(|_| Vec::new())
+            .collect();
+        for p in &self.productions {
+            productions[p.nonterminal].push(CFGProduction {
+                nonterminal: p.nonterminal,
+                rhs: p.rhs.clone(),
+            });
+        }
+        CFG {
+            productions,
+            nonterminal_symbols: self
+                .nonterminal_symbols
+                .iter()
+                .map(|x| x.as_str())
+                .collect::<SymbolTable>(),
+            terminal_symbols: self
+                .terminal_symbols
+                .iter()
+                .map(|x| x.as_str())
+                .collect::<SymbolTable>(),
+            aliases: self.aliases.iter().cloned().collect(),
+        }
+    }
+
+    pub fn table(&self) -> Table {
+        let mut rows: Vec<TableRow> = self
+            .rows
+            .iter()
+            .map(|cells| {
+                TableRow::new(
+                    cells
+                        .iter()
+                        .copied()
+                        .collect::<HashMap<Symbol, TableCell>>(),
+                )
+            })
+            .collect();
+        for (state, symbol, cells) in &self.extra {
+            rows[*state].extra.insert(*symbol, cells.clone());
+        }
+        Table {
+            rows,
+            cfg: self.cfg(),
+        }
+    }
+
+    //checks every index is in range and the stack checks of check_stack, so a loaded table can't make
+    //the runtime panic
+    fn validate(&self) -> Result<(), TableFormatError> {
+        let symbol_ok = |s: &Symbol| match s {
+            Symbol::Terminal(x) => *x < self.terminal_symbols.len(),
+            Symbol::Nonterminal(x) => *x < self.nonterminal_symbols.len(),
+            _ => true,
+        };
+        if self.nonterminal_symbols.is_empty() || self.productions.is_empty() {
+            return Err(invalid("missing start rule"));
+        }
+        //SymbolTable keeps the first of two equal names, which would shift every id after it
+        let unique = |names: &[String]| names.iter().collect::<HashSet<_>>().len() == names.len();
+        if !unique(&self.terminal_symbols) || !unique(&self.nonterminal_symbols) {
+            return Err(invalid("duplicate symbol name"));
+        }
+        //productions have to be grouped by nonterminal for ids to survive a round trip
+        let mut last = 0;
+        for p in &self.productions {
+            if p.nonterminal >= self.nonterminal_symbols.len() || p.nonterminal < last {
+                return Err(invalid("productions out of order"));
+            }
+            last = p.nonterminal;
+            if !p.rhs.iter().all(symbol_ok) {
+                return Err(invalid("production symbol out of range"));
+            }
+        }
+        let cell_ok = |c: &TableCell| match c {
+            TableCell::Shift(x) | TableCell::Goto(x) => *x < self.rows.len(),
+            TableCell::Reduce(x) => *x < self.productions.len(),
+            TableCell::Accept() => true,
+        };
+        for cells in &self.rows {
+            for (symbol, cell) in cells {
+                if !symbol_ok(symbol) || !cell_ok(cell) {
+                    return Err(invalid("table cell out of range"));
+                }
+            }
+        }
+        for (state, symbol, cells) in &self.extra {
+            if *state >= self.rows.len() || !symbol_ok(symbol) || !cells.iter().all(cell_ok) {
+                return Err(invalid("extra cell out of range"));
+            }
+        }
+        if self
+            .aliases
+            .iter()
+            .any(|(t, _)| *t >= self.terminal_symbols.len())
+        {
+            return Err(invalid("alias for a terminal out of range"));
+        }
+        if self.rows.is_empty() {
+            return Err(invalid("table has no rows"));
+        }
+        //an accepted parse is built from the one symbol under <eof>
+        let start: Vec<&SavedProduction> = self
+            .productions
+            .iter()
+            .filter(|p| p.nonterminal == 0)
+            .collect();
+        match start.as_slice() {
+            [p] if matches!(p.rhs.as_slice(), [Symbol::Nonterminal(x), Symbol::EOF()] if *x != 0) =>
+                {}
+            _ => return Err(invalid("start rule isn't S' -> X <eof>")),
+        }
+        self.check_stack()
+    }
+
+    //the runtime pops a state for each symbol of a reduced production and then needs a goto on its
+    //lhs, and accepts with a single symbol on the stack. the states that can be under the top of the
+    //stack are the ones a path of shifts and gotos from state 0 goes through, so those are checked
+    fn check_stack(&self) -> Result<(), TableFormatError> {
+        let mut next: Vec<Vec<usize>> = vec![Vec::new(); self.rows.len()];
+        for (state, cells) in self.rows.iter().enumerate() {
+            for (_, cell) in cells {
+                if let TableCell::Shift(x) | TableCell::Goto(x) = cell {
+                    next[state].push(*x);
+                }
+            }
+        }
+        let mut reachable = vec![false; self.rows.len()];
+        let mut stack = vec![0];
+        reachable[0] = true;
+        while let Some(state) = stack.pop() {
+            for x in &next[state] {
+                if !reachable[*x] {
+                    reachable[*x] = true;
+                    stack.push(*x);
+                }
+            }
+        }
+        let mut from: Vec<HashSet<usize>> = vec![HashSet::new(); self.rows.len()];
+        for (state, targets) in next.iter().enumerate().filter(|(x, _)| reachable[*x]) {
+            for x in targets {
+                from[*x].insert(state);
+            }
+        }
+        for (state, cells) in self.rows.iter().enumerate().filter(|(x, _)| reachable[*x]) {
+            for (_, cell) in cells {
+                match cell {
+                    TableCell::Reduce(p) => {
+                        let production = &self.productions[*p];
+                        let length = production.rhs.iter().filter(|x| **x != Symbol::Empty());
+                        let mut below = HashSet::from([state]);
+                        for _ in length {
+                            if below.contains(&0) {
+                                return Err(invalid("reduce pops past the bottom of the stack"));
+                            }
+                            below = below
+                                .iter()
+                                .flat_map(|x| from[*x].iter().copied())
+                                .collect();
+                        }
+                        let lhs = Symbol::Nonterminal(production.nonterminal);
+                        let has_goto = |x: &usize| {
+                            self.rows[*x]
+                                .iter()
+                                .any(|(s, c)| *s == lhs && matches!(c, TableCell::Goto(_)))
+                        };
+                        if !below.iter().all(has_goto) {
+                            return Err(invalid("no goto after a reduce"));
+                        }
+                    }
+                    TableCell::Accept()
+                        if from[state] != HashSet::from([0]) || !from[0].is_empty() =>
+                    {
+                        return Err(invalid("accept without a single symbol on the stack"));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        let strings =
+            |v: &Vec<String>| Json::Array(v.iter().map(|x| Json::String(x.clone())).collect());
+        let mut root = BTreeMap::new();
+        root.insert(
+            String::from("format"),
+            Json::String(String::from(JSON_FORMAT_NAME)),
+        );
+        root.insert(String::from("version"), Json::Number(FORMAT_VERSION as f64));
+        root.insert(String::from("terminals"), strings(&self.terminal_symbols));
+        root.insert(
+            String::from("nonterminals"),
+            strings(&self.nonterminal_symbols),
+        );
+        let productions = self
+            .productions
+            .iter()
+            .map(|p| {
+                let mut prod = BTreeMap::new();
+                prod.insert(String::from("lhs"), Json::Number(p.nonterminal as f64));
+                prod.insert(
+                    String::from("rhs"),
+                    Json::Array(
+                        p.rhs
+                            .iter()
+                            .map(|s| Json::String(symbol_to_string(s)))
+                            .collect(),
+                    ),
+                );
+                Json::Object(prod)
+            })
+            .collect();
+        root.insert(String::from("productions"), Json::Array(productions));
+        let rows = self
+            .rows
+            .iter()
+            .map(|cells| {
+                Json::Object(
+                    cells
+                        .iter()
+                        .map(|(s, c)| (symbol_to_string(s), Json::String(cell_to_string(c))))
+                        .collect(),
+                )
+            })
+            .collect();
+        root.insert(String::from("rows"), Json::Array(rows));
+        let aliases = self
+            .aliases
+            .iter()
+            .map(|(t, alias)| {
+                (
+                    symbol_to_string(&Symbol::Terminal(*t)),
+                    Json::String(alias.clone()),
+                )
+            })
+            .collect();
+        root.insert(String::from("aliases"), Json::Object(aliases));
+        let extra = self
+            .extra
+            .iter()
+            .map(|(state, symbol, cells)| {
+                let mut entry = BTreeMap::new();
+                entry.insert(String::from("row"), Json::Number(*state as f64));
+                entry.insert(
+                    String::from("symbol"),
+                    Json::String(symbol_to_string(symbol)),
+                );
+                entry.insert(
+                    String::from("cells"),
+                    Json::Array(
+                        cells
+                            .iter()
+                            .map(|c| Json::String(cell_to_string(c)))
+                            .collect(),
+                    ),
+                );
+                Json::Object(entry)
+            })
+            .collect();
+        root.insert(String::from("extra"), Json::Array(extra));
+        Json::Object(root).to_string_pretty()
+    }
+
+    pub fn from_json(input: &str) -> Result<SavedTable, TableFormatError> {
+        let root = Json::parse(input).map_err(TableFormatError::Json)?;
+        if root.get("format").and_then(|x| x.as_str()) != Some(JSON_FORMAT_NAME) {
+            return Err(TableFormatError::NotATable);
+        }
+        let version = root
+            .get("version")
+            .and_then(|x| x.as_usize())
+            .ok_or_else(|| invalid("missing version"))? as u32;
+        if version > FORMAT_VERSION {
+            return Err(TableFormatError::UnsupportedVersion(version));
+        }
+        let strings = |key: &str| -> Result<Vec<String>, TableFormatError> {
+            root.get(key)
+                .and_then(|x| x.as_array())
+                .ok_or_else(|| invalid(&format!("missing {}", key)))?
+                .iter()
+                .map(|x| {
+                    x.as_str()
+                        .map(String::from)
+                        .ok_or_else(|| invalid(&format!("{} must be strings", key)))
+                })
+                .collect()
+        };
+        let terminal_symbols = strings("terminals")?;
+        let nonterminal_symbols = strings("nonterminals")?;
+        let mut productions = Vec::new();
+        for p in root
+            .get("productions")
+            .and_then(|x| x.as_array())
+            .ok_or_else(|| invalid("missing productions"))?
+        {
+            let nonterminal = p
+                .get("lhs")
+                .and_then(|x| x.as_usize())
+                .ok_or_else(|| invalid("production missing lhs"))?;
+            let rhs = p
+                .get("rhs")
+                .and_then(|x| x.as_array())
+                .ok_or_else(|| invalid("production missing rhs"))?
+                .iter()
+                .map(|s| s.as_str().and_then(symbol_from_string))
+                .collect::<Option<Vec<Symbol>>>()
+                .ok_or_else(|| invalid("bad symbol in production"))?;
+            productions.push(SavedProduction { nonterminal, rhs });
+        }
+        let mut rows = Vec::new();
+        for row in root
+            .get("rows")
+            .and_then(|x| x.as_array())
+            .ok_or_else(|| invalid("missing rows"))?
+        {
+            let mut cells = Vec::new();
+            for (symbol, cell) in row
+                .as_object()
+                .ok_or_else(|| invalid("rows must be objects"))?
+            {
+                let symbol =
+                    symbol_from_string(symbol).ok_or_else(|| invalid("bad symbol in row"))?;
+                let cell = cell
+                    .as_str()
+                    .and_then(cell_from_string)
+                    .ok_or_else(|| invalid("bad cell in row"))?;
+                cells.push((symbol, cell));
+            }
+            cells.sort_by_key(|x| x.0);
+            rows.push(cells);
+        }
+        let mut aliases = Vec::new();
+        if let Some(object) = root.get("aliases") {
+            for (symbol, alias) in object
+                .as_object()
+                .ok_or_else(|| invalid("aliases must be an object"))?
+            {
+                let terminal = match symbol_from_string(symbol) {
+                    Some(Symbol::Terminal(t)) => t,
+                    _ => return Err(invalid("bad symbol in aliases")),
+                };
+                let alias = alias
+                    .as_str()
+                    .ok_or_else(|| invalid("aliases must be strings"))?;
+                aliases.push((terminal, String::from(alias)));
+            }
+            aliases.sort();
+        }
+        let mut extra = Vec::new();
+        if let Some(array) = root.get("extra") {
+            for entry in array
+                .as_array()
+                .ok_or_else(|| invalid("extra must be an array"))?
+            {
+                let state = entry
+                    .get("row")
+                    .and_then(|x| x.as_usize())
+                    .ok_or_else(|| invalid("extra missing row"))?;
+                let symbol = entry
+                    .get("symbol")
+                    .and_then(|x| x.as_str())
+                    .and_then(symbol_from_string)
+                    .ok_or_else(|| invalid("bad symbol in extra"))?;
+                let cells = entry
+                    .get("cells")
+                    .and_then(|x| x.as_array())
+                    .ok_or_else(|| invalid("extra missing cells"))?
+                    .iter()
+                    .map(|c| c.as_str().and_then(cell_from_string))
+                    .collect::<Option<Vec<TableCell>>>()
+                    .ok_or_else(|| invalid("bad cell in extra"))?;
+                extra.push((state, symbol, cells));
+            }
+            extra.sort_by_key(|x| (x.0, x.1));
+        }
+        let table = SavedTable {
+            terminal_symbols,
+            nonterminal_symbols,
+            productions,
+            rows,
+            aliases,
+            extra,
+        };
+        table.validate()?;
+        Ok(table)
+    }
+
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(BINARY_MAGIC);
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        write_varint(&mut out, self.terminal_symbols.len());
+        for s in &self.terminal_symbols {
+            write_string(&mut out, s);
+        }
+        write_varint(&mut out, self.nonterminal_symbols.len());
+        for s in &self.nonterminal_symbols {
+            write_string(&mut out, s);
+        }
+        write_varint(&mut out, self.productions.len());
+        for p in &self.productions {
+            write_varint(&mut out, p.nonterminal);
+            write_varint(&mut out, p.rhs.len());
+            for s in &p.rhs {
+                write_symbol(&mut out, s);
+            }
+        }
+        write_varint(&mut out, self.rows.len());
+        for cells in &self.rows {
+            write_varint(&mut out, cells.len());
+            for (symbol, cell) in cells {
+                write_symbol(&mut out, symbol);
+                write_cell(&mut out, cell);
+            }
+        }
+        write_varint(&mut out, self.aliases.len());
+        for (terminal, alias) in &self.aliases {
+            write_varint(&mut out, *terminal);
+            write_string(&mut out, alias);
+        }
+        write_varint(&mut out, self.extra.len());
+        for (state, symbol, cells) in &self.extra {
+            write_varint(&mut out, *state);
+            write_symbol(&mut out, symbol);
+            write_varint(&mut out, cells.len());
+            for cell in cells {
+                write_cell(&mut out, cell);
+            }
+        }
+        out
+    }
+
+    pub fn from_binary(bytes: &[u8]) -> Result<SavedTable, TableFormatError> {
+        if bytes.len() < 8 || &bytes[0..4] != BINARY_MAGIC {
+            return Err(TableFormatError::NotATable);
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version > FORMAT_VERSION {
+            return Err(TableFormatError::UnsupportedVersion(version));
+        }
+        let mut reader = Reader { bytes, pos: 8 };
+        let mut terminal_symbols = Vec::new();
+        for _ in 0..reader.varint()? {
+            terminal_symbols.push(reader.string()?);
+        }
+        let mut nonterminal_symbols = Vec::new();
+        for _ in 0..reader.varint()? {
+            nonterminal_symbols.push(reader.string()?);
+        }
+        let mut productions = Vec::new();
+        for _ in 0..reader.varint()? {
+            let nonterminal = reader.varint()?;
+            let mut rhs = Vec::new();
+            for _ in 0..reader.varint()? {
+                rhs.push(reader.symbol()?);
+            }
+            productions.push(SavedProduction { nonterminal, rhs });
+        }
+        let mut rows = Vec::new();
+        for _ in 0..reader.varint()? {
+            let mut cells = Vec::new();
+            for _ in 0..reader.varint()? {
+                let symbol = reader.symbol()?;
+                cells.push((symbol, reader.cell()?));
+            }
+            rows.push(cells);
+        }
+        let mut aliases = Vec::new();
+        if version >= 2 {
+            for _ in 0..reader.varint()? {
+                let terminal = reader.varint()?;
+                aliases.push((terminal, reader.string()?));
+            }
+        }
+        let mut extra = Vec::new();
+        if version >= 3 {
+            for _ in 0..reader.varint()? {
+                let state = reader.varint()?;
+                let symbol = reader.symbol()?;
+                let mut cells = Vec::new();
+                for _ in 0..reader.varint()? {
+                    cells.push(reader.cell()?);
+                }
+                extra.push((state, symbol, cells));
+            }
+        }
+        if reader.pos != bytes.len() {
+            return Err(invalid("trailing bytes"));
+        }
+        let table = SavedTable {
+            terminal_symbols,
+            nonterminal_symbols,
+            productions,
+            rows,
+            aliases,
+            extra,
+        };
+        table.validate()?;
+        Ok(table)
+    }
+}
+
+fn invalid(msg: &str) -> TableFormatError {
+    TableFormatError::Invalid(String::from(msg))
+}
+
+fn symbol_to_string(symbol: &Symbol) -> String {
+    match symbol {
+        Symbol::Terminal(x) => format!("t{}", x),
+        Symbol::Nonterminal(x) => format!("n{}", x),
+        Symbol::Empty() => String::from("empty"),
+        Symbol::EOF() => String::from("eof"),
+    }
+}
+
+fn symbol_from_string(s: &str) -> Option<Symbol> {
+    match s {
+        "eof" => Some(Symbol::EOF()),
+        "empty" => Some(Symbol::Empty()),
+        _ if s.starts_with('t') => s[1..].parse().ok().map(Symbol::Terminal),
+        _ if s.starts_with('n') => s[1..].parse().ok().map(Symbol::Nonterminal),
+        _ => None,
+    }
+}
+
+fn cell_to_string(cell: &TableCell) -> String {
+    match cell {
+        TableCell::Shift(x) => format!("s{}", x),
+        TableCell::Reduce(x) => format!("r{}", x),
+        TableCell::Goto(x) => format!("g{}", x),
+        TableCell::Accept() => String::from("acc"),
+    }
+}
+
+fn cell_from_string(s: &str) -> Option<TableCell> {
+    if s == "acc" {
+        return Some(TableCell::Accept());
+    }
+    let x = s.get(1..)?.parse().ok()?;
+    match s.as_bytes()[0] {
+        b's' => Some(TableCell::Shift(x)),
+        b'r' => Some(TableCell::Reduce(x)),
+        b'g' => Some(TableCell::Goto(x)),
+        _ => None,
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut x: usize) {
+    loop {
+        let byte = (x & 0x7f) as u8;
+        x >>= 7;
+        if x == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    write_varint(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_symbol(out: &mut Vec<u8>, symbol: &Symbol) {
+    let (tag, x) = match symbol {
+        Symbol::Terminal(x) => (0, *x),
+        Symbol::Nonterminal(x) => (1, *x),
+        Symbol::Empty() => (2, 0),
+        Symbol::EOF() => (3, 0),
+    };
+    out.push(tag);
+    write_varint(out, x);
+}
+
+fn write_cell(out: &mut Vec<u8>, cell: &TableCell) {
+    let (tag, x) = match cell {
+        TableCell::Shift(x) => (0, *x),
+        TableCell::Reduce(x) => (1, *x),
+        TableCell::Goto(x) => (2, *x),
+        TableCell::Accept() => (3, 0),
+    };
+    out.push(tag);
+    write_varint(out, x);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, TableFormatError> {
+        let b = *self
+            .bytes
+            .get(self.pos)
+            .ok_or(TableFormatError::Truncated)?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn varint(&mut self) -> Result<usize, TableFormatError> {
+        let mut x: usize = 0;
+        let mut shift = 0;
+        loop {
+            let b = self.byte()?;
+            if shift >= usize::BITS {
+                return Err(invalid("integer too large"));
+            }
+            x |= ((b & 0x7f) as usize) << shift;
+            if b & 0x80 == 0 {
+                return Ok(x);
+            }
+            shift += 7;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, TableFormatError> {
+        let len = self.varint()?;
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|x| *x <= self.bytes.len())
+            .ok_or(TableFormatError::Truncated)?;
+        let s = std::str::from_utf8(&self.bytes[self.pos..end])
+            .map_err(|_| invalid("string is not utf-8"))?;
+        self.pos = end;
+        Ok(String::from(s))
+    }
+
+    fn symbol(&mut self) -> Result<Symbol, TableFormatError> {
+        let tag = self.byte()?;
+        let x = self.varint()?;
+        match tag {
+            0 => Ok(Symbol::Terminal(x)),
+            1 => Ok(Symbol::Nonterminal(x)),
+            2 => Ok(Symbol::Empty()),
+            3 => Ok(Symbol::EOF()),
+            _ => Err(invalid("bad symbol tag")),
+        }
+    }
+    fn cell(&mut self) -> Result<TableCell, TableFormatError> {
+        let tag = self.byte()?;
+        let x = self.varint()?;
+        match tag {
+            0 => Ok(TableCell::Shift(x)),
+            1 => Ok(TableCell::Reduce(x)),
+            2 => Ok(TableCell::Goto(x)),
+            3 => Ok(TableCell::Accept()),
+            _ => Err(invalid("bad cell tag")),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::cfg;
+use crate::parsing::ambiguity::*;
+use crate::parsing::grammar::CFG;
+
+#[allow(dead_code)]
+fn sentence(cfg: &CFG, ambiguous: &Ambiguous) -> String {
+    let words: Vec<&str> = ambiguous
+        .sentence
+        .iter()
+        .map(|x| cfg.symbol_str(x))
+        .collect();
+    words.join(" ")
+}
+
+#[test]
+fn finds_shortest() {
+    let cfg = cfg(&["E -> E .+ E", "E -> .id"]);
+    let check = AmbiguityCheck::default();
+    let report = self::check(&cfg, &check);
+    assert!(report.conflicts);
+    assert_eq!(1, report.ambiguous.len());
+    assert_eq!(".id .+ .id .+ .id", sentence(&cfg, &report.ambiguous[0]));
+    assert_eq!(Some(2), report.ambiguous[0].count);
+    assert_eq!(2, report.ambiguous[0].trees.len());
+    assert_eq!(
+        "the grammar is ambiguous, .id .+ .id .+ .id has 2 parse trees
+  (S' (E (E (E .id) .+ (E .id)) .+ (E .id)))
+  (S' (E (E .id) .+ (E (E .id) .+ (E .id))))",
+        report.message(&cfg, &check)
+    );
+
+    //more reports come out shortest first
+    let check = AmbiguityCheck {
+        max_reports: 3,
+        ..AmbiguityCheck::default()
+    };
+    let report = self::check(&cfg, &check);
+    let lengths: Vec<usize> = report.ambiguous.iter().map(|x| x.sentence.len()).collect();
+    assert_eq!(vec![5, 7, 9], lengths);
+}
+
+#[test]
+fn cycles_and_empties() {
+    let cfg = cfg(&["E -> E", "E -> .id"]);
+    let report = check(&cfg, &AmbiguityCheck::default());
+    assert_eq!(None, report.ambiguous[0].count);
+    assert!(report
+        .message(&cfg, &AmbiguityCheck::default())
+        .starts_with("the grammar is ambiguous, .id has infinitely many parse trees"));
+
+    let cfg = self::cfg(&["S -> A B", "A -> <empty>", "B -> <empty>", "S -> <empty>"]);
+    let report = check(&cfg, &AmbiguityCheck::default());
+    assert_eq!(0, report.ambiguous[0].sentence.len());
+}
+
+#[test]
+fn not_lr1_but_unambiguous() {
+    //needs two tokens of lookahead, but every sentence has one tree
+    let cfg = cfg(&["S -> A .x .y", "S -> B .x .z", "A -> .a", "B -> .a"]);
+    let check = AmbiguityCheck::default();
+    let report = self::check(&cfg, &check);
+    assert!(report.conflicts);
+    assert!(report.ambiguous.is_empty());
+    assert!(report.complete);
+    assert_eq!(2, report.sentences);
+    assert_eq!(
+        "the grammar isn't lr(1), but no sentence up to 10 terminals is ambiguous",
+        report.message(&cfg, &check)
+    );
+
+    //the dangling else needs 9 terminals to show
+    let cfg = self::cfg(&[
+        "S -> .if E .then S",
+        "S -> .if E .then S .else S",
+        "S -> .x",
+        "E -> .b",
+    ]);
+    let short = AmbiguityCheck {
+        max_length: 8,
+        ..AmbiguityCheck::default()
+    };
+    assert!(self::check(&cfg, &short).ambiguous.is_empty());
+    assert_eq!(1, self::check(&cfg, &check).ambiguous.len());
+
+    //a limit makes the result incomplete
+    let limited = AmbiguityCheck {
+        max_parses: 5,
+        ..AmbiguityCheck::default()
+    };
+    let report = self::check(&cfg, &limited);
+    assert!(!report.complete);
+    assert!(report
+        .message(&cfg, &limited)
+        .starts_with("the grammar isn't lr(1), but none of the "));
+
+    let cfg = self::cfg(&["E -> E .+ .id", "E -> .id"]);
+    let report = self::check(&cfg, &check);
+    assert!(!report.conflicts && report.ambiguous.is_empty());
+    assert_eq!(
+        "the grammar is lr(1), so it isn't ambiguous",
+        report.message(&cfg, &check)
+    );
+}
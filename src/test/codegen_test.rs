@@ -0,0 +1,277 @@
+use crate::codegen::code_generator::*;
+use crate::codegen::code_generator_csharp::CodeGeneratorCSharp;
+use crate::codegen::code_generator_recursive_descent::CodeGeneratorRecursiveDescent;
+use crate::codegen::file_system::*;
+use crate::parsing::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[allow(dead_code)]
+fn get_gstr() -> Vec<String> {
+    let gstr = ["E -> E .+ .id", "E -> .id"];
+    gstr.iter().map(|&x| String::from(x)).collect()
+}
+
+#[allow(dead_code)]
+fn get_cfg(grammar_strings: &[String]) -> grammar::CFG {
+    grammar::CFG::from_strings(grammar_strings).unwrap()
+}
+
+#[allow(dead_code)]
+fn generate(fs: &mut dyn FileSystem) -> Result<GeneratedFiles, CodegenError> {
+    let gstr = get_gstr();
+    let cfg = get_cfg(&gstr);
+    let name = String::from("Calc");
+    let data = CodeGeneratorData {
+        table: parse_table::Table {
+            rows: Vec::new(),
+            cfg: cfg.clone(),
+        },
+        project_name: name.clone(),
+        messages: BTreeMap::new(),
+    };
+    CodeGeneratorCSharp::default().generate(&data, fs)
+}
+
+#[test]
+fn csharp_in_memory() {
+    let mut fs = MemoryFileSystem::new();
+    let files = generate(&mut fs).unwrap();
+    assert_eq!(
+        vec![
+            PathBuf::from("Calc.csproj"),
+            PathBuf::from("Program.cs"),
+            PathBuf::from("Parser.cs")
+        ],
+        files.files
+    );
+    assert_eq!(3, fs.files.len());
+    assert!(fs
+        .get_str("Calc.csproj")
+        .unwrap()
+        .starts_with("<Project Sdk=\"Microsoft.NET.Sdk\">"));
+    assert!(fs.get_str("Program.cs").unwrap().contains("namespace Calc"));
+}
+
+#[test]
+fn csharp_expected() {
+    let mut gstr = get_gstr();
+    gstr.push(String::from(".id = identifier"));
+    let cfg = get_cfg(&gstr);
+    let data = CodeGeneratorData {
+        table: lr1_generator::generate(&cfg).ok().unwrap(),
+        project_name: String::from("Calc"),
+        messages: BTreeMap::new(),
+    };
+    let mut fs = MemoryFileSystem::new();
+    CodeGeneratorCSharp::default()
+        .generate(&data, &mut fs)
+        .unwrap();
+    //state 0 only takes an identifier, the state after E takes + or the end
+    let parser = fs.get_str("Parser.cs").unwrap();
+    assert!(parser.contains(
+        "public static readonly string[] Expected = { \"identifier\", \"`.+` or end of input\", "
+    ));
+}
+
+//the generated Action and Goto functions run over tokens, state of the first error
+#[allow(dead_code)]
+fn compressed_error_state(
+    table: &parse_table::Table,
+    options: &table_compression::CompressionOptions,
+    input: &str,
+) -> Option<usize> {
+    let compressed = table_compression::compress(table, options);
+    let tokens = parse_tree::tokenize(&table.cfg, input).unwrap();
+    let mut states = vec![0];
+    let symbols = tokens.iter().map(|x| x.symbol);
+    for symbol in symbols.chain(std::iter::once(grammar::Symbol::EOF())) {
+        loop {
+            let state = *states.last().unwrap();
+            match compressed.get(state, &symbol) {
+                Some(parse_table::TableCell::Shift(next)) => {
+                    states.push(next);
+                    break;
+                }
+                Some(parse_table::TableCell::Reduce(production)) => {
+                    let production = table.cfg.production(production);
+                    states.truncate(states.len() - production.rhs_len());
+                    let top = *states.last().unwrap();
+                    states.push(compressed.goto(top, production.nonterminal).unwrap());
+                }
+                Some(_) => return None,
+                None => return Some(state),
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn csharp_error_states() {
+    let cfg = get_cfg(&[String::from("S -> .id .= E .;"), String::from("E -> .id")]);
+    let table = lr1_generator::generate(&cfg).ok().unwrap();
+    let tokens = parse_tree::tokenize(&cfg, "id = id id").unwrap();
+    let state = error_messages::error_state(&table, &tokens);
+    assert!(state.is_some());
+    assert_eq!(
+        state,
+        compressed_error_state(&table, &COMPRESSION, "id = id id")
+    );
+    //with default reductions E -> .id is reduced before the error is found
+    let defaults = table_compression::CompressionOptions::default();
+    assert_ne!(
+        state,
+        compressed_error_state(&table, &defaults, "id = id id")
+    );
+}
+
+#[test]
+fn csharp_messages() {
+    let cfg = get_cfg(&get_gstr());
+    let table = lr1_generator::generate(&cfg).ok().unwrap();
+    let mut messages = BTreeMap::new();
+    messages.insert(0, String::from("a \"quoted\" message"));
+    let states = table.rows.len();
+    let data = CodeGeneratorData {
+        table,
+        project_name: String::from("Calc"),
+        messages,
+    };
+    let mut fs = MemoryFileSystem::new();
+    CodeGeneratorCSharp::default()
+        .generate(&data, &mut fs)
+        .unwrap();
+    let parser = fs.get_str("Parser.cs").unwrap();
+    let nulls = vec!["null"; states - 1].join(", ");
+    assert!(parser.contains(&format!(
+        "public static readonly string[] Messages = {{ \"a \\\"quoted\\\" message\", {} }};",
+        nulls
+    )));
+}
+
+#[test]
+fn dry_run_reports_files() {
+    let mut fs = DryRunFileSystem::default();
+    let files = generate(&mut fs).unwrap();
+    assert_eq!(3, files.files.len());
+}
+
+#[test]
+fn rejects_escaping_paths() {
+    let mut fs = MemoryFileSystem::new();
+    assert!(fs.write_file(Path::new("../x.cs"), b"").is_err());
+    assert!(fs.write_file(Path::new(""), b"").is_err());
+    assert!(fs.write_file(Path::new("a/./b.cs"), b"").is_ok());
+}
+
+#[test]
+fn zip_archive() {
+    let mut fs = ZipFileSystem::new(Vec::new());
+    generate(&mut fs).unwrap();
+    let bytes = fs.finish().unwrap();
+    //local header signature, then end of central directory with 3 entries
+    assert_eq!(&[0x50, 0x4b, 0x03, 0x04], &bytes[0..4]);
+    let end = &bytes[bytes.len() - 22..];
+    assert_eq!(&[0x50, 0x4b, 0x05, 0x06], &end[0..4]);
+    assert_eq!(3, u16::from_le_bytes([end[10], end[11]]));
+    assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+}
+
+#[test]
+fn directory() {
+    let root = std::env::temp_dir().join(format!("lmaker_codegen_{}", std::process::id()));
+    let mut fs = DirectoryFileSystem::new(&root);
+    fs.write_file(Path::new("sub/file.txt"), b"hi").unwrap();
+    assert_eq!(
+        b"hi".to_vec(),
+        std::fs::read(root.join("sub/file.txt")).unwrap()
+    );
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[allow(dead_code)]
+fn generate_rd(gstr: &[&str]) -> Result<MemoryFileSystem, CodegenError> {
+    let gstr: Vec<String> = gstr.iter().map(|&x| String::from(x)).collect();
+    let cfg = get_cfg(&gstr);
+    let data = CodeGeneratorData {
+        table: parse_table::Table {
+            rows: Vec::new(),
+            cfg,
+        },
+        project_name: String::from("Calc"),
+        messages: BTreeMap::new(),
+    };
+    let mut fs = MemoryFileSystem::new();
+    CodeGeneratorRecursiveDescent::default().generate(&data, &mut fs)?;
+    Ok(fs)
+}
+
+#[test]
+fn recursive_descent() {
+    let fs = generate_rd(&["E -> .id E'", "E' -> .+ .id E'", "E' -> <empty>"]).unwrap();
+    assert_eq!(3, fs.files.len());
+    let parser = fs.get_str("Parser.cs").unwrap();
+    assert!(parser.contains("\tpublic Node Parse()\n"));
+    assert!(parser.contains("\tpublic Node ParseE()\n"));
+    //E' -> .+ .id E' on .+, E' -> <empty> on the end of input
+    assert!(parser.contains(
+        "\t\t\t\t//E' -> .+ .id E'\n\t\t\t\tcase TokenKind._2B:\n\t\t\t\t\tnode.Children.Add(Expect(TokenKind._2B));\n\t\t\t\t\tnode.Children.Add(Expect(TokenKind.id));\n\t\t\t\t\tnode.Children.Add(ParseE_27());\n\t\t\t\t\treturn node;\n"
+    ));
+    assert!(parser.contains(
+        "\t\t\t\t//E' -> <empty>\n\t\t\t\tcase TokenKind.EndOfInput:\n\t\t\t\t\treturn node;\n"
+    ));
+    assert!(parser.contains("\t\t\t\t\tthrow Error(TokenKind._2B, TokenKind.EndOfInput);\n"));
+    assert!(parser.contains("{ \".+\", TokenKind._2B },\n"));
+    assert!(parser.contains("{ \"+\", TokenKind._2B },\n"));
+    assert!(parser
+        .contains("static readonly string[] Names = { \"end of input\", \"`.id`\", \"`.+`\" };"));
+    assert!(fs
+        .get_str("Program.cs")
+        .unwrap()
+        .contains("Parser.KindOf(word)"));
+}
+
+#[test]
+fn recursive_descent_names() {
+    let fs = generate_rd(&[
+        "S -> .if A' A_27 r .EndOfInput",
+        "A' -> .int",
+        "A_27 -> .class",
+        "r -> .a_27 .a'",
+    ])
+    .unwrap();
+    let parser = fs.get_str("Parser.cs").unwrap();
+    //keywords are escaped
+    assert!(parser.contains("\t\t@if,\n"));
+    assert!(parser.contains("\t\t@int,\n"));
+    assert!(parser.contains("case TokenKind.@if:\n"));
+    assert!(parser.contains("{ \".class\", TokenKind.@class },\n"));
+    //and names that come out the same get a number
+    assert!(parser.contains("\t\tEndOfInput,\n\t\t@if,\n\t\tEndOfInput_2,\n"));
+    assert!(parser.contains("\t\ta_27,\n\t\ta_27_2,\n"));
+    assert!(parser.contains("\tpublic Node ParseA_27()\n"));
+    assert!(parser.contains("\tpublic Node ParseA_27_2()\n"));
+    //a method can't have the class name
+    assert!(parser.contains("\tpublic Node Parser_2()\n"));
+}
+
+#[test]
+fn recursive_descent_needs_ll1() {
+    match generate_rd(&["E -> E .+ .id", "E -> .id"]) {
+        Err(CodegenError::NotLL1(conflicts)) => {
+            assert_eq!(2, conflicts.len());
+            assert!(conflicts[0].starts_with("E is left recursive"));
+            assert!(conflicts[1].starts_with("FIRST/FIRST conflict in E on .id"));
+        }
+        _ => panic!("should refuse"),
+    }
+    let message = generate_rd(&["S -> .a", "S -> .a .b"])
+        .err()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        "grammar is not LL(1)\n  FIRST/FIRST conflict in S on .a: `S -> .a` and `S -> .a .b` can both start with it",
+        message
+    );
+}
@@ -0,0 +1,90 @@
+use super::cfg;
+use crate::parsing::counterexample::*;
+use crate::parsing::grammar::{Symbol, CFG};
+use crate::parsing::parse_table::TableCell;
+
+#[allow(dead_code)]
+fn form(cfg: &CFG, form: &[Symbol], position: usize) -> String {
+    form_str(cfg, form, position)
+}
+
+#[test]
+fn ambiguous_expression() {
+    let cfg = cfg(&["E -> E .+ E", "E -> .id"]);
+    let examples = counterexamples(&cfg);
+    assert_eq!(1, examples.len());
+    let example = &examples[0];
+    assert_eq!((TableCell::Shift(3), TableCell::Reduce(1)), example.actions);
+    match &example.example {
+        Some(Counterexample::Unifying(symbols, shift, reduce)) => {
+            assert_eq!("E .+ E • .+ E", form(&cfg, symbols, example.position));
+            assert_ne!(shift, reduce);
+            //shifting nests to the right, reducing to the left
+            assert_eq!(None, shift.children[0].production);
+            assert_eq!(Some(1), reduce.children[0].production);
+        }
+        _ => panic!("should be ambiguous"),
+    }
+    assert_eq!(
+        "conflict in state 4 on `.+` between shift and reduce E -> E .+ E
+  ambiguous example: E .+ E • .+ E
+  shift derivation
+    E -> E .+ E
+      E
+      .+
+      E -> E .+ E
+        E
+        • .+
+        E
+  reduce E -> E .+ E derivation
+    E -> E .+ E
+      E -> E .+ E
+        E
+        .+
+        E
+      • .+
+      E
+",
+        example.message(&cfg)
+    );
+}
+
+#[test]
+fn dangling_else() {
+    let cfg = cfg(&[
+        "S -> .if E .then S",
+        "S -> .if E .then S .else S",
+        "S -> .x",
+        "E -> .b",
+    ]);
+    let examples = counterexamples(&cfg);
+    assert_eq!(1, examples.len());
+    match &examples[0].example {
+        Some(Counterexample::Unifying(symbols, _, _)) => assert_eq!(
+            ".if E .then .if E .then S • .else S",
+            form(&cfg, symbols, examples[0].position)
+        ),
+        _ => panic!("should be ambiguous"),
+    }
+}
+
+#[test]
+fn needs_more_lookahead() {
+    //not ambiguous, the .y or .z after .x decides
+    let cfg = cfg(&["S -> A .x .y", "S -> B .x .z", "A -> .a", "B -> .a"]);
+    let examples = counterexamples(&cfg);
+    assert_eq!(1, examples.len());
+    match &examples[0].example {
+        Some(Counterexample::Nonunifying((a, _), (b, _))) => {
+            let position = examples[0].position;
+            assert_eq!(".a • .x .y", form(&cfg, a, position));
+            assert_eq!(".a • .x .z", form(&cfg, b, position));
+        }
+        _ => panic!("should be nonunifying"),
+    }
+    assert!(examples[0]
+        .message(&cfg)
+        .contains("  example for reduce A -> .a: .a • .x .y\n"));
+
+    assert!(counterexamples(&self::cfg(&["E -> E .+ .id", "E -> .id"])).is_empty());
+}
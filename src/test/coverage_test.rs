@@ -0,0 +1,100 @@
+use super::cfg;
+use crate::parsing::coverage::*;
+use crate::parsing::grammar::Symbol;
+use crate::parsing::lr1_generator;
+use crate::parsing::lr_parser;
+use crate::parsing::parse_table::{Table, TableCell};
+use crate::parsing::parse_tree::tokenize;
+
+#[allow(dead_code)]
+fn table(gstr: &[&str]) -> Table {
+    lr1_generator::generate(&cfg(gstr)).ok().unwrap()
+}
+
+#[allow(dead_code)]
+fn run(table: &Table, corpus: &[&str]) -> Coverage {
+    let mut coverage = Coverage::new(table);
+    for input in corpus {
+        let tokens = tokenize(&table.cfg, input).unwrap();
+        let covered = lr_parser::parse_covering(table, &tokens, &mut coverage);
+        assert_eq!(lr_parser::parse(table, &tokens), covered);
+    }
+    coverage
+}
+
+#[allow(dead_code)]
+const EXPRESSIONS: [&str; 6] = [
+    "E -> E .+ T",
+    "E -> T",
+    "T -> T .* F",
+    "T -> F",
+    "F -> .( E .)",
+    "F -> .id",
+];
+
+#[test]
+fn full_coverage() {
+    let table = table(&EXPRESSIONS);
+    let coverage = run(&table, &["id + id * id", "( id ) * ( id + id )", "id"]);
+    assert_eq!(3, coverage.inputs);
+    assert_eq!(3, coverage.accepted);
+    assert!(coverage.unused_productions().is_empty());
+    //the start rule is used once for each accepted input
+    assert_eq!(3, coverage.productions[0]);
+    assert_eq!(3, coverage.states[0]);
+}
+
+#[test]
+fn unused_productions() {
+    let table = table(&EXPRESSIONS);
+    let coverage = run(&table, &["id + id", "id +"]);
+    assert_eq!(2, coverage.inputs);
+    assert_eq!(1, coverage.accepted);
+    assert_eq!(vec![3, 5], coverage.unused_productions());
+    assert!(!coverage.unused_states().is_empty());
+    let report = coverage.report(&table, false);
+    assert!(report.starts_with("2 inputs, 1 accepted\nproductions reduced: 5 of 7 (71%)\n"));
+    assert!(
+        report.ends_with("\nproductions never reduced:\n    3 T -> T .* F\n    5 F -> .( E .)\n")
+    );
+    assert!(!report.contains("states never entered"));
+    assert!(coverage
+        .report(&table, true)
+        .contains("\nstates never entered: 1, "));
+}
+
+#[test]
+fn rejected_input_counts_until_the_error() {
+    let table = table(&["S -> .a .b", "S -> .a .c"]);
+    let coverage = run(&table, &["a a"]);
+    assert_eq!(0, coverage.accepted);
+    assert_eq!(vec![0, 1, 2], coverage.unused_productions());
+    //shifting .a is the only action taken
+    let a = Symbol::Terminal(table.cfg.terminal_symbols.get(".a").unwrap());
+    let taken: usize = coverage
+        .actions
+        .iter()
+        .map(|x| x.values().sum::<usize>())
+        .sum();
+    assert_eq!(1, taken);
+    assert_eq!(Some(&1), coverage.actions[0].get(&a));
+    let unused = coverage.unused_actions(&table);
+    assert!(!unused
+        .iter()
+        .any(|(state, symbol, _)| *state == 0 && *symbol == a));
+    assert!(unused
+        .iter()
+        .any(|(_, _, cell)| matches!(cell, TableCell::Accept())));
+}
+
+#[test]
+fn gotos_count_as_actions() {
+    let table = table(&["S -> A .x", "A -> .y"]);
+    let coverage = run(&table, &["y x"]);
+    assert!(coverage.unused_productions().is_empty());
+    assert!(coverage.unused_states().is_empty());
+    assert!(coverage.unused_actions(&table).is_empty());
+    assert!(coverage
+        .report(&table, true)
+        .contains("actions taken: 7 of 7 (100%)"));
+}
@@ -0,0 +1,111 @@
+use super::cfg;
+use crate::parsing::differential::*;
+use crate::parsing::grammar::CFG;
+use crate::parsing::lr1_generator;
+
+#[allow(dead_code)]
+fn mismatches(grammar: &[String], seed: u64) -> usize {
+    //minimize can remove every production of a nonterminal that's still used
+    let cfg = match CFG::from_strings(grammar) {
+        Ok(cfg) => cfg,
+        Err(_) => return 0,
+    };
+    compare(&cfg, &corpus(&cfg, 30, seed)).len()
+}
+
+#[test]
+fn random_grammars_agree() {
+    let mut compared = [0; 3];
+    for seed in 0..300 {
+        let grammar = random_grammar(seed);
+        let cfg = CFG::from_strings(&grammar).unwrap();
+        for (i, algorithm) in ALGORITHMS.iter().enumerate() {
+            compared[i] += algorithm.generate(&cfg).is_ok() as usize;
+        }
+        if mismatches(&grammar, seed) > 0 {
+            let small = minimize(&grammar, |x| mismatches(x, seed) > 0);
+            let cfg = CFG::from_strings(&small).unwrap();
+            let first = compare(&cfg, &corpus(&cfg, 30, seed)).remove(0);
+            panic!(
+                "seed {}, grammar {:?}\n{}",
+                seed,
+                small,
+                first.message(&cfg)
+            );
+        }
+    }
+    //enough of the grammars are conflict free for the check to mean something
+    assert!(compared.iter().all(|x| *x >= 50), "{:?}", compared);
+}
+
+#[test]
+fn lr1_not_lalr() {
+    let cfg = cfg(&[
+        "S -> .a A .d",
+        "S -> .b B .d",
+        "S -> .a B .e",
+        "S -> .b A .e",
+        "A -> .c",
+        "B -> .c",
+    ]);
+    assert!(lr1_generator::generate(&cfg).is_ok());
+    assert!(lr1_generator::generate_lalr(&cfg).is_err());
+    assert!(lr1_generator::generate_slr(&cfg).is_err());
+    assert!(compare(&cfg, &corpus(&cfg, 20, 0)).is_empty());
+}
+
+#[test]
+fn lalr_not_slr() {
+    let cfg = cfg(&["S -> L .= R", "S -> R", "L -> .* R", "L -> .id", "R -> L"]);
+    let lr1 = lr1_generator::generate(&cfg).ok().unwrap();
+    let lalr = lr1_generator::generate_lalr(&cfg).ok().unwrap();
+    assert!(lalr.rows.len() < lr1.rows.len());
+    assert!(lr1_generator::generate_slr(&cfg).is_err());
+    assert!(compare(&cfg, &corpus(&cfg, 30, 0)).is_empty());
+}
+
+#[test]
+fn slr_grammar() {
+    let cfg = cfg(&["E -> E .+ T", "E -> T", "T -> T .* .id", "T -> .id"]);
+    for algorithm in ALGORITHMS {
+        assert!(algorithm.generate(&cfg).is_ok(), "{}", algorithm.name());
+    }
+    let corpus = corpus(&cfg, 30, 1);
+    assert!(corpus.len() > 30);
+    assert!(compare(&cfg, &corpus).is_empty());
+}
+
+#[test]
+fn random_grammar_is_reproducible() {
+    assert_eq!(random_grammar(7), random_grammar(7));
+    let grammar = random_grammar(7);
+    assert!(grammar[0].starts_with("A -> "));
+    assert!(grammar.len() <= 9);
+}
+
+#[test]
+fn minimize_keeps_the_failure() {
+    //fails while the grammar has a conflict in lalr(1)
+    let grammar: Vec<String> = [
+        "S -> .a A .d",
+        "S -> .b B .d",
+        "S -> .a B .e",
+        "S -> .b A .e",
+        "S -> .f",
+        "A -> .c .c",
+        "B -> .c .c",
+        "A -> .g",
+    ]
+    .iter()
+    .map(|&x| String::from(x))
+    .collect();
+    let fails = |x: &[String]| {
+        CFG::from_strings(x).is_ok_and(|cfg| lr1_generator::generate_lalr(&cfg).is_err())
+    };
+    let small = minimize(&grammar, fails);
+    assert!(fails(&small));
+    assert_eq!(
+        vec!["S -> .a A", "S -> .a B", "A -> .c .c", "B -> .c .c"],
+        small
+    );
+}
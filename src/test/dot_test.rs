@@ -0,0 +1,97 @@
+use super::cfg;
+use crate::parsing::dot::*;
+use crate::parsing::grammar::{Symbol, CFG};
+use crate::parsing::lr1_generator;
+use crate::parsing::parse_table::TableCell;
+
+//LR(1) but not LALR(1), merging the two states after .c gives a reduce/reduce conflict
+#[allow(dead_code)]
+fn not_lalr() -> CFG {
+    cfg(&[
+        "S -> .a A .d",
+        "S -> .b B .d",
+        "S -> .a B .e",
+        "S -> .b A .e",
+        "A -> .c",
+        "B -> .c",
+    ])
+}
+
+#[test]
+fn automaton_matches_table() {
+    let cfg = cfg(&["E -> E .+ .id", "E -> .id"]);
+    let automaton = lr1_generator::automaton(&cfg);
+    let table = lr1_generator::generate(&cfg).ok().unwrap();
+    assert_eq!(table.rows.len(), automaton.states.len());
+    for (state, row) in automaton.states.iter().zip(&table.rows) {
+        for (symbol, to) in &state.transitions {
+            let cell = match symbol {
+                Symbol::Terminal(_) => TableCell::Shift(*to),
+                _ => TableCell::Goto(*to),
+            };
+            assert_eq!(Some(&cell), row.cells.get(symbol));
+        }
+    }
+    //the same grammar numbers its states the same way every time
+    assert_eq!(automaton, lr1_generator::automaton(&cfg));
+    assert_eq!(
+        vec![
+            "S' -> • E <eof>, <eof>",
+            "E -> • E .+ .id, .+ <eof>",
+            "E -> • .id, .+ <eof>"
+        ],
+        automaton.states[0]
+            .items
+            .iter()
+            .map(|x| item_str(&cfg, x))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn lalr_merges_cores() {
+    let cfg = not_lalr();
+    let lr1 = lr1_generator::automaton(&cfg);
+    assert!(lr1.conflicts(&cfg).is_empty());
+    let lalr = lr1.lalr();
+    assert_eq!(lr1.states.len() - 1, lalr.states.len());
+    let conflicts = lalr.conflicts(&cfg);
+    assert_eq!(2, conflicts.len());
+    let (state, _, cells) = &conflicts[0];
+    assert_eq!(vec![TableCell::Reduce(5), TableCell::Reduce(6)], *cells);
+    assert_eq!(
+        vec!["A -> .c •, .d .e", "B -> .c •, .d .e"],
+        lalr.states[*state]
+            .items
+            .iter()
+            .map(|x| item_str(&cfg, x))
+            .collect::<Vec<_>>()
+    );
+
+    //the conflicts of a glr table are the same as the automaton's
+    let table = lr1_generator::generate_glr(&self::cfg(&["E -> E .+ E", "E -> .id"]));
+    let automaton = lr1_generator::automaton(&table.cfg);
+    assert_eq!(table.conflicts(), automaton.conflicts(&table.cfg));
+}
+
+#[test]
+fn dot_output() {
+    let cfg = not_lalr();
+    let lalr = lr1_generator::automaton(&cfg).lalr();
+    let dot = automaton_dot(&cfg, &lalr, None);
+    assert!(dot.starts_with("digraph automaton {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("\ts0 [label=\"state 0\\lS' -> • S <eof>, <eof>\\l"));
+    assert!(dot.contains("\ts0 -> s1 [label=\".a\"];\n"));
+    assert!(dot.contains("conflict on .d: reduce A -> .c / reduce B -> .c\\l"));
+    assert_eq!(1, dot.matches("color=red").count());
+
+    //the way to the conflict and its neighbours
+    let conflict = lalr.conflicts(&cfg)[0].0;
+    let around = lalr.around(&[conflict], 1);
+    assert_eq!(vec![0, 1, 2, conflict], around);
+    let dot = automaton_dot(&cfg, &lalr, Some(&around));
+    assert_eq!(4, dot.matches("[label=\"state").count());
+    assert_eq!(4, dot.matches(" -> s").count());
+    assert!(!dot.contains("s3 "));
+}
@@ -0,0 +1,92 @@
+use super::cfg;
+use crate::parsing::earley::*;
+use crate::parsing::grammar::{Symbol, CFG};
+use crate::parsing::ll1_generator;
+use crate::parsing::parse_tree::*;
+
+#[allow(dead_code)]
+fn parse_str(cfg: &CFG, input: &str) -> Result<ParseTree, ParseError> {
+    parse(cfg, &tokenize(cfg, input)?)
+}
+
+#[allow(dead_code)]
+fn sexpr(cfg: &CFG, input: &str) -> String {
+    parse_str(cfg, input).unwrap().to_sexpr(cfg)
+}
+
+#[test]
+fn left_recursion() {
+    let cfg = cfg(&["E -> E .+ T", "E -> T", "T -> T .* .id", "T -> .id"]);
+    assert_eq!(
+        "(S' (E (E (T id)) + (T (T id) * id)))",
+        sexpr(&cfg, "id + id * id")
+    );
+    let tree = parse_str(&cfg, "id").unwrap();
+    assert_eq!(1, tree.tokens().len() - 1);
+}
+
+#[test]
+fn same_tree_as_ll1() {
+    let cfg = cfg(&[
+        "E -> T E'",
+        "E' -> .+ T E'",
+        "E' -> <empty>",
+        "T -> F T'",
+        "T' -> .* F T'",
+        "T' -> <empty>",
+        "F -> .( E .)",
+        "F -> .id",
+    ]);
+    let table = ll1_generator::generate(&cfg).unwrap();
+    for input in &["id", "id + id * ( id + id )", "( ( id ) ) * id"] {
+        let tokens = tokenize(&cfg, input).unwrap();
+        assert_eq!(table.parse(&tokens).unwrap(), parse(&cfg, &tokens).unwrap());
+    }
+}
+
+#[test]
+fn empties() {
+    //the usual problem case for earley, A is nullable and used several times in a row
+    let cfg = cfg(&["S -> A A A A", "A -> .a", "A -> E", "E -> <empty>"]);
+    assert_eq!("(S' (S (A (E)) (A (E)) (A (E)) (A (E))))", sexpr(&cfg, ""));
+    let tree = parse_str(&cfg, "a a").unwrap();
+    assert_eq!(3, tree.tokens().len());
+    assert!(parse_str(&cfg, "a a a a a").is_err());
+
+    //hidden left recursion and a language no LR(k) parser handles, even length palindromes
+    let cfg = self::cfg(&["S -> A S .x", "S -> .x", "A -> <empty>"]);
+    assert_eq!("(S' (S (A) (S x) x))", sexpr(&cfg, "x x"));
+    let cfg = self::cfg(&["S -> .a S .a", "S -> .b S .b", "S -> <empty>"]);
+    assert_eq!("(S' (S a (S b (S) b) a))", sexpr(&cfg, "a b b a"));
+    assert!(parse_str(&cfg, "a b a").is_err());
+}
+
+#[test]
+fn ambiguous_and_cyclic() {
+    let cfg = cfg(&["E -> E .+ E", "E -> .id"]);
+    let tree = parse_str(&cfg, "id + id + id").unwrap();
+    assert_eq!(6, tree.tokens().len());
+    //E -> E loops, the tree takes the way out
+    let cfg = self::cfg(&["E -> E", "E -> .id"]);
+    assert_eq!("(S' (E id))", sexpr(&cfg, "id"));
+}
+
+#[test]
+fn errors() {
+    let cfg = cfg(&["E -> E .+ .id", "E -> .id"]);
+    assert_eq!(
+        "expected `.+` or end of input, found `.id` at 1:9",
+        parse_str(&cfg, "id + id id").err().unwrap().message(&cfg)
+    );
+    match parse_str(&cfg, "id +") {
+        Err(ParseError::Unexpected(token, expected)) => {
+            assert_eq!(Symbol::EOF(), token.symbol);
+            assert_eq!(vec![Symbol::Terminal(1)], expected);
+        }
+        _ => panic!("should fail"),
+    }
+    assert!(matches!(
+        parse_str(&cfg, ""),
+        Err(ParseError::Unexpected(_, _))
+    ));
+}
@@ -0,0 +1,148 @@
+use crate::parsing::error_messages::*;
+use crate::parsing::grammar::CFG;
+use crate::parsing::lr1_generator;
+use crate::parsing::parse_table::Table;
+use crate::parsing::parse_tree::tokenize;
+
+#[allow(dead_code)]
+fn table(gstr: &[&str]) -> Table {
+    let gstr: Vec<String> = gstr.iter().map(|&x| String::from(x)).collect();
+    match lr1_generator::generate(&CFG::from_strings(&gstr).unwrap()) {
+        Ok(table) => table,
+        Err(_) => panic!("grammar should be LR(1)"),
+    }
+}
+
+#[allow(dead_code)]
+fn statements() -> Table {
+    table(&[
+        "P -> P S",
+        "P -> S",
+        "S -> .id .= E .;",
+        "E -> E .+ .id",
+        "E -> .id",
+    ])
+}
+
+#[allow(dead_code)]
+fn state_of(table: &Table, input: &str) -> Option<usize> {
+    error_state(table, &tokenize(&table.cfg, input).unwrap())
+}
+
+#[test]
+fn parse_file() {
+    let text = "# a comment\nid = ;\nid = + id\n\nexpected an expression\nafter `=`\n\n\n<eof>\n\nexpected a statement\n";
+    let messages = Messages::parse(text).unwrap();
+    assert_eq!(
+        vec![
+            MessageEntry {
+                sentences: vec![(2, String::from("id = ;")), (3, String::from("id = + id"))],
+                message: String::from("expected an expression\nafter `=`"),
+            },
+            MessageEntry {
+                sentences: vec![(9, String::from("<eof>"))],
+                message: String::from("expected a statement"),
+            },
+        ],
+        messages.entries
+    );
+    match Messages::parse("id = ;\n\nexpected an expression\n\nid\n# no message\n") {
+        Err(MessagesError::MissingMessage(line)) => assert_eq!(5, line),
+        _ => panic!("should fail"),
+    }
+}
+
+#[test]
+fn compile() {
+    let table = statements();
+    let messages = Messages::parse(
+        "id = ;\nid = + id\n\nexpected an expression\n\n<eof>\n\nexpected a statement\n",
+    )
+    .unwrap();
+    let compiled = messages.compile(&table);
+    let after_equals = state_of(&table, "id = ;").unwrap();
+    assert_eq!(Some(after_equals), state_of(&table, "id = + id"));
+    assert_eq!(
+        "expected an expression",
+        compiled.by_state[&after_equals].as_str()
+    );
+    assert_eq!(
+        "expected a statement",
+        compiled.by_state[&state_of(&table, "").unwrap()].as_str()
+    );
+    assert_eq!(2, compiled.by_state.len());
+    //everything else is missing a message, and nothing more is wrong
+    assert!(!compiled.problems.is_empty());
+    assert!(compiled
+        .problems
+        .iter()
+        .all(|x| matches!(x, MessageProblem::Missing(_, _))));
+}
+
+#[test]
+fn problems() {
+    let table = statements();
+    let text = "id = id ;\n\nparses fine\n\nid = ;\n\none message\n\nid = + ;\n\nanother message\n\nid = $\n\nbad token\n";
+    let problems = Messages::parse(text).unwrap().compile(&table).problems;
+    let problems: Vec<String> = problems
+        .iter()
+        .filter(|x| !matches!(x, MessageProblem::Missing(_, _)))
+        .map(|x| x.message(&table.cfg))
+        .collect();
+    assert_eq!(3, problems.len());
+    assert_eq!("line 1: the example parses without an error", problems[0]);
+    assert!(problems[1].starts_with("lines 5 and 9: both fail in state "));
+    assert!(problems[2].starts_with("line 13: "));
+
+    //a statement list that can now be empty makes the old example stale
+    let changed = self::table(&["P -> P S", "P -> <empty>", "S -> .id .= .id .;"]);
+    let problems = Messages::parse("<eof>\n\nexpected a statement\n")
+        .unwrap()
+        .compile(&changed)
+        .problems;
+    assert_eq!(MessageProblem::NoError(1), problems[0]);
+}
+
+#[test]
+fn every_error_state_is_found() {
+    let table = statements();
+    let sentences = error_sentences(&table);
+    //canonical LR(1) only finds errors after a shift or before reading anything,
+    //here the start, after `id`, `=`, `+`, the operand after `=` and `+`, and a complete statement list
+    assert_eq!(7, sentences.len());
+    assert!(sentences.windows(2).all(|x| x[0].0 < x[1].0));
+    for (state, sentence) in &sentences {
+        let input = sentence_str(&table.cfg, sentence);
+        let tokens = match input.as_str() {
+            "<eof>" => Vec::new(),
+            input => tokenize(&table.cfg, input).unwrap(),
+        };
+        assert_eq!(Some(*state), error_state(&table, &tokens), "{}", input);
+    }
+}
+
+#[test]
+fn list_and_update() {
+    let table = statements();
+    let listed = list(&table, &Messages::default());
+    let messages = Messages::parse(&listed).unwrap();
+    assert_eq!(7, messages.entries.len());
+    assert!(messages.entries.iter().all(|x| x.message == PLACEHOLDER));
+    assert!(listed.starts_with("<eof>\n## fails in state "));
+    assert!(listed.contains(", which expects `.id`\n\n<YOUR SYNTAX ERROR MESSAGE HERE>\n"));
+
+    //existing entries are kept first and their states aren't listed again
+    let existing = Messages::parse("id = ;\n\nexpected an expression\n").unwrap();
+    let updated = list(&table, &existing);
+    assert!(updated.starts_with("id = ;\n## fails in state "));
+    let messages = Messages::parse(&updated).unwrap();
+    assert_eq!(7, messages.entries.len());
+    assert_eq!("expected an expression", messages.entries[0].message);
+    assert_eq!(updated, list(&table, &messages));
+
+    //filling in the placeholders leaves nothing to report
+    let filled = updated.replace(PLACEHOLDER, "filled in");
+    let compiled = Messages::parse(&filled).unwrap().compile(&table);
+    assert!(compiled.problems.is_empty());
+    assert_eq!(7, compiled.by_state.len());
+}
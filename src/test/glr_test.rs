@@ -0,0 +1,157 @@
+use crate::parsing::glr::*;
+use crate::parsing::grammar::{Symbol, CFG};
+use crate::parsing::lr1_generator;
+use crate::parsing::parse_table::{Table, TableCell};
+use crate::parsing::parse_tree::*;
+
+#[allow(dead_code)]
+fn table(gstr: &[&str]) -> Table {
+    let gstr: Vec<String> = gstr.iter().map(|&x| String::from(x)).collect();
+    lr1_generator::generate_glr(&CFG::from_strings(&gstr).unwrap())
+}
+
+#[allow(dead_code)]
+fn parse_str(table: &Table, input: &str) -> Result<Forest, ParseError> {
+    parse(table, &tokenize(&table.cfg, input)?)
+}
+
+#[allow(dead_code)]
+fn sexprs(table: &Table, forest: &Forest) -> Vec<String> {
+    let mut trees: Vec<String> = forest
+        .trees(100)
+        .iter()
+        .map(|x| x.to_sexpr(&table.cfg))
+        .collect();
+    trees.sort();
+    trees
+}
+
+#[test]
+fn conflicts_kept() {
+    let table = table(&["E -> E .+ E", "E -> .id"]);
+    let conflicts = table.conflicts();
+    assert_eq!(1, conflicts.len());
+    let (_, symbol, actions) = &conflicts[0];
+    assert_eq!(Symbol::Terminal(0), *symbol);
+    assert_eq!(2, actions.len());
+    assert!(actions.iter().any(|x| matches!(x, TableCell::Shift(_))));
+    assert!(actions.contains(&TableCell::Reduce(1)));
+    //the deterministic generator refuses the same grammar
+    assert!(lr1_generator::generate(&table.cfg).is_err());
+}
+
+#[test]
+fn ambiguous() {
+    let table = table(&["E -> E .+ E", "E -> .id"]);
+    let forest = parse_str(&table, "id + id + id").unwrap();
+    assert!(forest.is_ambiguous());
+    assert_eq!(Some(2), forest.count_trees());
+    assert_eq!(
+        vec![
+            "(S' (E (E (E id) + (E id)) + (E id)))",
+            "(S' (E (E id) + (E (E id) + (E id))))",
+        ],
+        sexprs(&table, &forest)
+    );
+    //the whole E is the only ambiguous node
+    let ambiguities = forest.ambiguities();
+    assert_eq!(1, ambiguities.len());
+    let node = &forest.nodes[ambiguities[0]];
+    assert_eq!(
+        (Symbol::Nonterminal(1), 0, 5),
+        (node.symbol, node.start, node.end)
+    );
+    assert_eq!(1, forest.trees(1).len());
+
+    //6 operands make 42 trees, the catalan number, without 42 copies of the nodes
+    let forest = parse_str(&table, "id + id + id + id + id + id").unwrap();
+    assert_eq!(Some(42), forest.count_trees());
+    assert_eq!(10, forest.trees(10).len());
+}
+
+#[test]
+fn disambiguate() {
+    let table = table(&["E -> E .+ E", "E -> .id"]);
+    let forest = parse_str(&table, "id + id + id").unwrap();
+    //left associative, the first child is the longer one
+    let tree = forest.disambiguate(|node, alternatives| {
+        let end = |i: usize| forest.nodes[alternatives[i].children[0]].end;
+        assert_eq!(2, alternatives.len());
+        assert_eq!(Symbol::Nonterminal(1), node.symbol);
+        (0..alternatives.len()).max_by_key(|x| end(*x)).unwrap()
+    });
+    assert_eq!(
+        "(S' (E (E (E id) + (E id)) + (E id)))",
+        tree.to_sexpr(&table.cfg)
+    );
+    assert_eq!(6, tree.tokens().len());
+    assert!(forest.tree().is_none());
+
+    let forest = parse_str(&table, "id + id").unwrap();
+    assert!(!forest.is_ambiguous());
+    assert_eq!(
+        "(S' (E (E id) + (E id)))",
+        forest.tree().unwrap().to_sexpr(&table.cfg)
+    );
+}
+
+#[test]
+fn empty_and_cycles() {
+    //hidden left recursion, A derives nothing
+    let table = table(&["S -> A S .x", "S -> .x", "A -> <empty>"]);
+    let forest = parse_str(&table, "x x x").unwrap();
+    assert_eq!(Some(1), forest.count_trees());
+    assert_eq!(
+        "(S' (S (A) (S (A) (S x) x) x))",
+        forest.tree().unwrap().to_sexpr(&table.cfg)
+    );
+
+    //E -> E gives infinitely many trees
+    let table = self::table(&["E -> E", "E -> .id"]);
+    let forest = parse_str(&table, "id").unwrap();
+    assert_eq!(None, forest.count_trees());
+    //E over id is its own child, trees() leaves the loop out
+    assert_eq!(vec!["(S' (E id))"], sexprs(&table, &forest));
+    let tree = forest.disambiguate(|_, _| 0);
+    assert_eq!("(S' (E id))", tree.to_sexpr(&table.cfg));
+}
+
+#[test]
+fn errors() {
+    let table = table(&["E -> E .+ E", "E -> .id"]);
+    assert_eq!(
+        "expected `.+` or end of input, found `.id` at 1:9",
+        parse_str(&table, "id + id id")
+            .err()
+            .unwrap()
+            .message(&table.cfg)
+    );
+    match parse_str(&table, "id +") {
+        Err(ParseError::Unexpected(token, expected)) => {
+            assert_eq!(Symbol::EOF(), token.symbol);
+            assert_eq!(vec![Symbol::Terminal(1)], expected);
+        }
+        _ => panic!("should fail"),
+    }
+}
+
+#[test]
+fn viable_sentences_shortest_first() {
+    let table = table(&["S -> .a S .b", "S -> .c"]);
+    let mut found = Vec::new();
+    let complete = viable_sentences(&table, 5, 1000, |sentence, _| {
+        found.push(symbol_str(&table, sentence));
+        true
+    });
+    assert!(complete);
+    assert_eq!(vec![".c", ".a .c .b", ".a .a .c .b .b"], found);
+    //the bounds stop the search early
+    assert!(!viable_sentences(&table, 5, 3, |_, _| true));
+    assert!(!viable_sentences(&table, 5, 1000, |_, _| false));
+}
+
+#[allow(dead_code)]
+fn symbol_str(table: &Table, sentence: &[Symbol]) -> String {
+    let names: Vec<&str> = sentence.iter().map(|x| table.cfg.symbol_str(x)).collect();
+    names.join(" ")
+}
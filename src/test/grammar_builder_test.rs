@@ -0,0 +1,131 @@
+use crate::parsing::grammar::builder::*;
+use crate::parsing::*;
+
+#[allow(dead_code)]
+fn get_gstr() -> Vec<String> {
+    let gstr = [
+        "E -> E .+ T",
+        "E -> T",
+        "T -> T .* F",
+        "T -> F",
+        "F -> .( E .)",
+        "F -> .id",
+    ];
+    gstr.iter().map(|&x| String::from(x)).collect()
+}
+
+#[test]
+fn same_as_strings() {
+    let cfg = GrammarBuilder::new()
+        .terminals(vec![".+", ".*", ".(", ".)", ".id"])
+        .nonterminals(vec!["E", "T", "F"])
+        .rule("E", [nt("E"), t(".+"), nt("T")])
+        .rule("E", [nt("T")])
+        .rule("T", [nt("T"), t(".*"), nt("F")])
+        .rule("T", [nt("F")])
+        .rule("F", [t(".("), nt("E"), t(".)")])
+        .rule("F", [t(".id")])
+        .build()
+        .unwrap();
+    assert_eq!(grammar::CFG::from_strings(&get_gstr()).unwrap(), cfg);
+    assert!(lr1_generator::generate(&cfg).is_ok());
+}
+
+#[test]
+fn error_terminal() {
+    let cfg = GrammarBuilder::new()
+        .terminal(".;")
+        .nonterminal("S")
+        .rule("S", [error(), t(".;")])
+        .build()
+        .unwrap();
+    let gstr = vec![
+        String::from("S -> .; <error>"),
+        String::from("S -> <error> .;"),
+    ];
+    let from_strings = grammar::CFG::from_strings(&gstr).unwrap();
+    assert_eq!(from_strings.terminal_symbols, cfg.terminal_symbols);
+    assert_eq!(from_strings.productions[1][1], cfg.productions[1][0]);
+}
+
+#[test]
+fn names_are_not_escaped() {
+    let cfg = GrammarBuilder::new()
+        .terminal("if x")
+        .terminal("<empty>")
+        .nonterminal("A")
+        .nonterminal("B")
+        .start("B")
+        .rule("A", [t("if x")])
+        .rule("A", [])
+        .rule("B", [nt("A"), t("<empty>")])
+        .build()
+        .unwrap();
+    assert_eq!(Some(0), cfg.terminal_symbols.get("if x"));
+    assert_eq!(
+        vec![grammar::Symbol::Terminal(1)],
+        cfg.productions[2][0].rhs[1..].to_vec()
+    );
+    //start rule goes to B
+    assert_eq!(
+        grammar::Symbol::Nonterminal(2),
+        cfg.productions[0][0].rhs[0]
+    );
+    assert_eq!(vec![grammar::Symbol::Empty()], cfg.productions[1][1].rhs);
+}
+
+#[test]
+fn errors() {
+    let err = |b: GrammarBuilder| b.build().err().unwrap();
+    assert_eq!(
+        GrammarError::UnknownTerminal(String::from("x")),
+        err(GrammarBuilder::new().nonterminal("A").rule("A", [t("x")]))
+    );
+    assert_eq!(
+        GrammarError::UnknownNonterminal(String::from("B")),
+        err(GrammarBuilder::new().nonterminal("A").rule("A", [nt("B")]))
+    );
+    assert_eq!(
+        GrammarError::UnknownNonterminal(String::from("B")),
+        err(GrammarBuilder::new().nonterminal("A").rule("B", []))
+    );
+    assert_eq!(
+        GrammarError::DuplicateTerminal(String::from("x")),
+        err(GrammarBuilder::new().terminal("x").terminal("x"))
+    );
+    assert_eq!(
+        GrammarError::DuplicateNonterminal(String::from("A")),
+        err(GrammarBuilder::new().nonterminal("A").nonterminal("A"))
+    );
+    assert_eq!(
+        GrammarError::ReservedName(String::from(START_SYMBOL)),
+        err(GrammarBuilder::new().nonterminal(START_SYMBOL))
+    );
+    assert_eq!(
+        GrammarError::EmptyName(),
+        err(GrammarBuilder::new().terminal(""))
+    );
+    assert_eq!(
+        GrammarError::DuplicateRule(String::from("A"), 1),
+        err(GrammarBuilder::new()
+            .nonterminal("A")
+            .rule("A", [])
+            .rule("A", [empty()]))
+    );
+    assert_eq!(
+        GrammarError::NoRules(String::from("B")),
+        err(GrammarBuilder::new()
+            .nonterminals(vec!["A", "B"])
+            .rule("A", [nt("B")]))
+    );
+    assert_eq!(GrammarError::NoNonterminals(), err(GrammarBuilder::new()));
+    //the first error is the one reported
+    assert_eq!(
+        GrammarError::UnknownTerminal(String::from("x")),
+        err(GrammarBuilder::new()
+            .nonterminal("A")
+            .rule("A", [t("x")])
+            .terminal("y")
+            .terminal("y"))
+    );
+}
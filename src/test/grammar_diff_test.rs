@@ -0,0 +1,128 @@
+use super::cfg;
+use crate::parsing::grammar_diff::*;
+
+#[allow(dead_code)]
+fn words(sentences: &[Vec<String>]) -> Vec<String> {
+    sentences.iter().map(|x| x.join(" ")).collect()
+}
+
+#[test]
+fn same_grammar() {
+    let old = cfg(&["E -> E .+ .id", "E -> .id"]);
+    let diff = diff(&old, &old, &DiffOptions::default());
+    assert!(diff.is_empty());
+    assert!(diff.complete);
+    assert_eq!(
+        "same language for sentences up to 6 terminals\n",
+        diff.message(&DiffOptions::default())
+    );
+}
+
+#[test]
+fn matched_by_name() {
+    //the same grammar with its productions and symbols in another order, so every id differs
+    let old = cfg(&["S -> A B", "A -> .x", "B -> .y", ".x = \"ex\""]);
+    let new = cfg(&[
+        "S -> A B",
+        "B -> .y",
+        "A -> .x",
+        ".y = \"why\"",
+        ".x = \"ex\"",
+    ]);
+    let diff = diff(&old, &new, &DiffOptions::default());
+    assert!(diff.added_productions.is_empty());
+    assert!(diff.removed_productions.is_empty());
+    assert!(diff.changed_productions.is_empty());
+    assert!(diff.firsts.is_empty() && diff.follows.is_empty());
+    assert_eq!(
+        vec![(String::from(".y"), None, Some(String::from("why")))],
+        diff.changed_aliases
+    );
+    assert!(diff.only_old.is_empty() && diff.only_new.is_empty());
+}
+
+#[test]
+fn changed_operator() {
+    let old = cfg(&["E -> E .+ T", "E -> T", "T -> .id"]);
+    let new = cfg(&["E -> E .- T", "E -> T", "E -> .( E .)", "T -> .id"]);
+    let options = DiffOptions {
+        max_length: 3,
+        ..DiffOptions::default()
+    };
+    let diff = diff(&old, &new, &options);
+    assert_eq!(
+        vec![(String::from("E -> E .+ T"), String::from("E -> E .- T"))],
+        diff.changed_productions
+    );
+    assert_eq!(vec!["E -> .( E .)"], diff.added_productions);
+    assert_eq!(vec![".+"], diff.removed_terminals);
+    assert_eq!(vec![".(", ".)", ".-"], diff.added_terminals);
+    assert_eq!(
+        vec![SetChange {
+            nonterminal: String::from("E"),
+            added: vec![String::from(".(")],
+            removed: Vec::new(),
+        }],
+        diff.firsts
+    );
+    assert_eq!(
+        vec!["E", "T"],
+        diff.follows
+            .iter()
+            .map(|x| x.nonterminal.as_str())
+            .collect::<Vec<&str>>()
+    );
+    assert_eq!(vec![".id .+ .id"], words(&diff.only_old));
+    assert_eq!(vec![".( .id .)", ".id .- .id"], words(&diff.only_new));
+    assert_eq!(
+        "productions
+  + E -> .( E .)
+  ~ E -> E .+ T
+    E -> E .- T
+terminals
+  - .+
+  + .(
+  + .)
+  + .-
+first sets
+  E: added .(
+follow sets
+  E: added .) .-, removed .+
+  T: added .) .-, removed .+
+language, sentences up to 3 terminals
+  only old: .id .+ .id
+  only new: .( .id .)
+  only new: .id .- .id
+",
+        diff.message(&options)
+    );
+}
+
+#[test]
+fn conflicts_and_language() {
+    //E -> E .+ E is ambiguous but has the same language
+    let old = cfg(&["E -> E .+ .id", "E -> .id"]);
+    let new = cfg(&["E -> E .+ E", "E -> .id"]);
+    let options = DiffOptions::default();
+    let diff = diff(&old, &new, &options);
+    assert_eq!((0, 1), diff.conflicts);
+    assert!(diff.only_old.is_empty() && diff.only_new.is_empty());
+    assert!(diff
+        .message(&options)
+        .contains("conflicts\n  lr(1) -> 1 conflicts\n"));
+}
+
+#[test]
+fn limits() {
+    let old = cfg(&["S -> .a S", "S -> <empty>"]);
+    let new = cfg(&["S -> .a .a S", "S -> <empty>"]);
+    let options = DiffOptions {
+        max_length: 10,
+        max_parses: 5,
+        max_examples: 1,
+    };
+    let diff = diff(&old, &new, &options);
+    assert!(!diff.complete);
+    assert_eq!(vec![".a"], words(&diff.only_old));
+    assert!(diff.message(&options).contains("stopped after 5 parses"));
+}
@@ -0,0 +1,63 @@
+use super::cfg;
+use crate::codegen::grammar_docs::*;
+use crate::codegen::railroad;
+use crate::parsing::grammar;
+use std::path::PathBuf;
+
+//compares against src/test/snapshots/<name>, LMAKER_UPDATE_SNAPSHOTS=1 rewrites the file instead
+#[allow(dead_code)]
+fn snapshot(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src", "test", "snapshots", name]
+        .iter()
+        .collect();
+    if std::env::var_os("LMAKER_UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        expected == actual,
+        "{} doesn't match, run the tests with LMAKER_UPDATE_SNAPSHOTS=1 to update it",
+        path.display()
+    );
+}
+
+#[test]
+fn statements_page() {
+    let cfg = cfg(&[
+        "P -> P S",
+        "P -> <empty>",
+        "S -> .id .= E .;",
+        "S -> .print E .;",
+        "E -> E .+ T",
+        "E -> T",
+        "T -> .id",
+        "T -> .( E .)",
+        ".id = \"identifier\"",
+    ]);
+    snapshot("statements.html", &render(&cfg, "Statements"));
+}
+
+#[test]
+fn links_and_escaping() {
+    let mut cfg = cfg(&["A -> B .<", "B -> .& A", "B -> .c"]);
+    //grammar files can't have a nonterminal without productions, but a loaded table can
+    let c = cfg.nonterminal_symbols.intern("C");
+    cfg.productions.push(Vec::new());
+    cfg.productions[2][1].rhs = vec![grammar::Symbol::Nonterminal(c)];
+    let html = render(&cfg, "<A & B>");
+    assert!(html.contains("<title>&lt;A &amp; B&gt;</title>"));
+    //A is used by B, B by A, and C has no productions
+    assert!(html.contains("<p>Start symbol: <a href=\"#nt-1\"><code>A</code></a></p>"));
+    assert!(html.contains("<p>Used by: <a href=\"#nt-2\"><code>B</code></a></p>"));
+    assert!(html.contains("<section id=\"nt-3\">\n<h3><code>C</code></h3>\n<p>No productions.</p>"));
+    assert!(html.contains("<tr><td><code>.&lt;</code></td><td></td><td><a href=\"#nt-1\"><code>A</code></a></td></tr>"));
+    //no scripts or anything loaded from elsewhere
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("<link"));
+
+    let svg = railroad::diagram(&cfg, 2, &|x| format!("#nt-{}", x));
+    assert!(svg.starts_with("<svg class=\"railroad\""));
+    assert!(svg.contains("<text x=\"58\" y=\"26\">.&amp;</text>"));
+    assert!(svg.contains("<a href=\"#nt-1\"><rect class=\"nonterminal\""));
+}
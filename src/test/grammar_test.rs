@@ -1,8 +1,8 @@
-use crate::*;
+use crate::parsing::*;
 
 #[allow(dead_code)]
 fn get_gstr() -> Vec<String> {
-    let gstr = vec![
+    let gstr = [
         "E -> E .+ T",
         "E -> T",
         "T -> T .* F",
@@ -14,8 +14,8 @@ fn get_gstr() -> Vec<String> {
 }
 
 #[allow(dead_code)]
-fn get_cfg(grammar_strings: &Vec<String>) -> grammar::CFG {
-    grammar::CFG::from_strings(&grammar_strings)
+fn get_cfg(grammar_strings: &[String]) -> grammar::CFG {
+    grammar::CFG::from_strings(grammar_strings).unwrap()
 }
 
 #[test]
@@ -81,3 +81,136 @@ fn follow() {
     assert!(follow_f.contains(&grammar::Symbol::Terminal(1)));
     assert!(follow_f.contains(&grammar::Symbol::Terminal(3)));
 }
+
+#[allow(dead_code)]
+fn load_owned() -> grammar::CFG {
+    //the strings are dropped before the grammar is used
+    let text = get_gstr().join("\n");
+    grammar::CFG::from_strings(&grammar::grammar_lines(&text)).unwrap()
+}
+
+#[allow(dead_code)]
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+#[test]
+fn owned_cfg() {
+    let cfg = load_owned();
+    assert_send_sync(&cfg);
+    let moved = std::thread::spawn(move || cfg).join().unwrap();
+    assert_eq!("S'", &moved.nonterminal_symbols[0]);
+    assert_eq!(Some(1), moved.nonterminal_symbols.get("E"));
+    assert_eq!(Some(2), moved.terminal_symbols.get(".("));
+    assert_eq!(None, moved.terminal_symbols.get("E"));
+    assert_eq!(4, moved.nonterminal_symbols.len());
+    assert_eq!(
+        vec![".+", ".*", ".(", ".)", ".id"],
+        moved.terminal_symbols.iter().collect::<Vec<&str>>()
+    );
+    assert_eq!(moved, get_cfg(&get_gstr()));
+    //the table keeps its own copy of the grammar
+    let table = lr1_generator::generate(&moved).ok().unwrap();
+    drop(moved);
+    assert_send_sync(&table);
+    assert_eq!("E", table.cfg.symbol_str(&grammar::Symbol::Nonterminal(1)));
+}
+
+#[test]
+fn aliases() {
+    let gstr: Vec<String> = [
+        "E -> E .+ .id",
+        "E -> .id",
+        "E -> .( E .)",
+        ".id = \"identifier\"",
+        ".( = `(`",
+    ]
+    .iter()
+    .map(|&x| String::from(x))
+    .collect();
+    let cfg = get_cfg(&gstr);
+    //aliases don't change the ids of terminals in the productions
+    assert_eq!(Some(1), cfg.terminal_symbols.get(".id"));
+    assert_eq!(Some(2), cfg.terminal_symbols.get(".("));
+    assert_eq!("identifier", cfg.describe(&grammar::Symbol::Terminal(1)));
+    assert_eq!("`.+`", cfg.describe(&grammar::Symbol::Terminal(0)));
+    assert_eq!(
+        "`.+`, `(` or end of input",
+        cfg.describe_list(&[
+            grammar::Symbol::Terminal(0),
+            grammar::Symbol::Terminal(2),
+            grammar::Symbol::EOF()
+        ])
+    );
+    assert_eq!(
+        "identifier",
+        cfg.describe_list(&[grammar::Symbol::Terminal(1)])
+    );
+    let table = lr1_generator::generate(&cfg).ok().unwrap();
+    let tokens = parse_tree::tokenize(&cfg, "id id").unwrap();
+    assert_eq!(
+        "expected `.+` or end of input, found identifier at 1:4",
+        lr_parser::parse(&table, &tokens)
+            .err()
+            .unwrap()
+            .message(&cfg)
+    );
+}
+
+#[test]
+fn symbol_table() {
+    let mut symbols = grammar::SymbolTable::new();
+    assert!(symbols.is_empty());
+    assert_eq!(0, symbols.intern("a"));
+    assert_eq!(1, symbols.intern("b"));
+    assert_eq!(0, symbols.intern("a"));
+    assert_eq!(2, symbols.len());
+    assert_eq!("b", symbols.name(1));
+    assert_eq!(symbols, vec!["a", "b"].into_iter().collect());
+}
+
+#[test]
+fn malformed_grammars() {
+    use crate::parsing::grammar::builder::GrammarError;
+    let at = |line, e| GrammarError::AtLine(line, Box::new(e));
+    let load = |x: &[&str]| {
+        grammar::CFG::from_strings(&x.iter().map(|&x| String::from(x)).collect::<Vec<String>>())
+    };
+    assert_eq!(
+        Err(at(2, GrammarError::ExpectedArrow())),
+        load(&["E -> .id", "E .id"])
+    );
+    assert_eq!(Err(at(1, GrammarError::ExpectedArrow())), load(&["E"]));
+    assert_eq!(Err(GrammarError::NoNonterminals()), load(&[]));
+    assert_eq!(
+        Err(at(1, GrammarError::ReservedName(String::from("S'")))),
+        load(&["S' -> .id"])
+    );
+    //comments and blank lines still count for the line number
+    let path = std::env::temp_dir().join(format!("lmaker_grammar_{}.lm", std::process::id()));
+    std::fs::write(&path, "# expressions\n\nE -> E .+ .id\nE .id\n").unwrap();
+    assert_eq!(
+        "line 4: expected -> after the nonterminal",
+        grammar::CFG::load(&path).err().unwrap().to_string()
+    );
+    //and every nonterminal needs productions, Tt is probably meant to be T
+    std::fs::write(&path, "E -> E .+ T\nE -> T\n\nT -> .id .* Tt\nT -> .id\n").unwrap();
+    assert_eq!(
+        "line 4: nonterminal Tt has no rules",
+        grammar::CFG::load(&path).err().unwrap().to_string()
+    );
+    //an alias has to be for a terminal the productions use
+    std::fs::write(&path, "E -> .id\n.id = identifier\n.num = number\n").unwrap();
+    assert_eq!(
+        "line 3: unknown terminal .num",
+        grammar::CFG::load(&path).err().unwrap().to_string()
+    );
+    std::fs::write(&path, "# nothing yet\n").unwrap();
+    assert_eq!(
+        Err(GrammarError::NoNonterminals()),
+        grammar::CFG::load(&path)
+    );
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        grammar::CFG::load(&path),
+        Err(GrammarError::Unreadable(_))
+    ));
+}
@@ -0,0 +1,126 @@
+use super::cfg;
+use crate::parsing::grammar::{transform, Symbol, CFG};
+use crate::parsing::ll1_generator::*;
+use crate::parsing::parse_tree::*;
+
+//expression grammar with the left recursion already removed
+#[allow(dead_code)]
+fn expr() -> CFG {
+    cfg(&[
+        "E -> T E'",
+        "E' -> .+ T E'",
+        "E' -> <empty>",
+        "T -> F T'",
+        "T' -> .* F T'",
+        "T' -> <empty>",
+        "F -> .( E .)",
+        "F -> .id",
+    ])
+}
+
+#[allow(dead_code)]
+fn parse(table: &LL1Table, input: &str) -> Result<ParseTree, ParseError> {
+    table.parse(&tokenize(&table.cfg, input)?)
+}
+
+#[test]
+fn firsts_with_empties() {
+    let cfg = expr();
+    let firsts = cfg.generate_firsts();
+    let follows = cfg.generate_follows(&firsts);
+    let t = |name: &str| Symbol::Terminal(cfg.terminal_symbols.get(name).unwrap());
+    let nt = |name: &str| cfg.nonterminal_symbols.get(name).unwrap();
+    assert_eq!(vec![t(".("), t(".id")], sorted(&firsts[nt("E")]));
+    assert_eq!(vec![t(".+"), Symbol::Empty()], sorted(&firsts[nt("E'")]));
+    assert_eq!(vec![t(".)"), Symbol::EOF()], sorted(&follows[nt("E'")]));
+    assert_eq!(
+        vec![t(".+"), t(".)"), Symbol::EOF()],
+        sorted(&follows[nt("T")])
+    );
+}
+
+#[allow(dead_code)]
+fn sorted(set: &std::collections::HashSet<Symbol>) -> Vec<Symbol> {
+    let mut v: Vec<Symbol> = set.iter().copied().collect();
+    v.sort();
+    v
+}
+
+#[test]
+fn table() {
+    let table = generate(&expr()).unwrap();
+    let cfg = &table.cfg;
+    let e2 = cfg.nonterminal_symbols.get("E'").unwrap();
+    let plus = Symbol::Terminal(cfg.terminal_symbols.get(".+").unwrap());
+    let close = Symbol::Terminal(cfg.terminal_symbols.get(".)").unwrap());
+    assert_eq!(Some(&2), table.rows[e2].get(&plus));
+    assert_eq!(Some(&3), table.rows[e2].get(&close));
+    assert_eq!(Some(&3), table.rows[e2].get(&Symbol::EOF()));
+    assert_eq!(3, table.rows[e2].len());
+}
+
+#[test]
+fn driver() {
+    let table = generate(&expr()).unwrap();
+    let tree = parse(&table, "id + id * ( id )").unwrap();
+    assert_eq!(
+        "(S' (E (T (F id) (T')) (E' + (T (F id) (T' * (F ( (E (T (F id) (T')) (E')) )) (T'))) (E'))))",
+        tree.to_sexpr(&table.cfg)
+    );
+    assert_eq!(8, tree.tokens().len());
+    match parse(&table, "id +\n  * id") {
+        Err(ParseError::Unexpected(token, expected)) => {
+            assert_eq!((2, 3), (token.line, token.column));
+            assert_eq!(2, expected.len());
+        }
+        _ => panic!("should fail"),
+    }
+    assert_eq!(
+        "expected `.+`, `.*`, `.)` or end of input, found `.id` at 1:4",
+        parse(&table, "id id").err().unwrap().message(&table.cfg)
+    );
+    match parse(&table, "id + ") {
+        Err(ParseError::Unexpected(token, _)) => assert_eq!(Symbol::EOF(), token.symbol),
+        _ => panic!("should fail"),
+    }
+    assert!(matches!(
+        parse(&table, "id $ id"),
+        Err(ParseError::UnknownToken(_, 1, 4))
+    ));
+}
+
+#[test]
+fn deep_input() {
+    let table = generate(&expr()).unwrap();
+    let input = "( ".repeat(5000) + "id" + &" )".repeat(5000);
+    assert!(parse(&table, &input).is_ok());
+}
+
+#[test]
+fn conflicts() {
+    let left = cfg(&["E -> E .+ .id", "E -> .id"]);
+    let conflicts = generate(&left).err().unwrap();
+    assert_eq!(LL1Conflict::LeftRecursive(1), conflicts[0]);
+    assert!(conflicts
+        .iter()
+        .any(|c| matches!(c, LL1Conflict::FirstFirst(1, Symbol::Terminal(1), 1, 2))));
+    assert_eq!(
+        "FIRST/FIRST conflict in E on .id: `E -> E .+ .id` and `E -> .id` can both start with it",
+        conflict_message(&left, &conflicts[1])
+    );
+
+    let dangling = cfg(&["S -> .if S E", "S -> .x", "E -> .else S", "E -> <empty>"]);
+    let conflicts = generate(&dangling).err().unwrap();
+    assert_eq!(1, conflicts.len());
+    assert!(matches!(
+        conflicts[0],
+        LL1Conflict::FirstFollow(2, Symbol::Terminal(2), 3, 4)
+    ));
+    assert!(conflict_message(&dangling, &conflicts[0])
+        .starts_with("FIRST/FOLLOW conflict in E on .else"));
+
+    //transforming the original left recursive grammar makes it LL(1)
+    let t = transform::remove_left_recursion(&left).unwrap();
+    let table = generate(&t.cfg).unwrap();
+    assert!(parse(&table, "id + id + id").is_ok());
+}
@@ -1,8 +1,8 @@
-use crate::*;
+use crate::parsing::*;
 
 #[allow(dead_code)]
 fn get_gstr() -> Vec<String> {
-    let gstr = vec![
+    let gstr = [
         "S -> S .; A",
         "S -> A",
         "A -> E",
@@ -14,8 +14,8 @@ fn get_gstr() -> Vec<String> {
 }
 
 #[allow(dead_code)]
-fn get_cfg(grammar_strings: &Vec<String>) -> grammar::CFG {
-    grammar::CFG::from_strings(&grammar_strings)
+fn get_cfg(grammar_strings: &[String]) -> grammar::CFG {
+    grammar::CFG::from_strings(grammar_strings).unwrap()
 }
 
 #[test]
@@ -38,3 +38,84 @@ fn closure() {
     A -> E , <eof>
         */
 }
+
+//runs the table on a string of terminals, true if it's accepted
+#[allow(dead_code)]
+fn accepts(table: &parse_table::Table, input: &[grammar::Symbol]) -> bool {
+    let mut stack = vec![0];
+    let mut pos = 0;
+    loop {
+        let lookahead = input.get(pos).copied().unwrap_or(grammar::Symbol::EOF());
+        match table.rows[*stack.last().unwrap()].cells.get(&lookahead) {
+            Some(parse_table::TableCell::Shift(s)) => {
+                stack.push(*s);
+                pos += 1;
+            }
+            Some(parse_table::TableCell::Reduce(p)) => {
+                let production = table.cfg.production(*p);
+                stack.truncate(stack.len() - production.rhs.len());
+                let goto = &table.rows[*stack.last().unwrap()].cells
+                    [&grammar::Symbol::Nonterminal(production.nonterminal)];
+                match goto {
+                    parse_table::TableCell::Goto(s) => stack.push(*s),
+                    _ => return false,
+                }
+            }
+            Some(parse_table::TableCell::Accept()) => return true,
+            _ => return false,
+        }
+    }
+}
+
+#[test]
+fn table() {
+    //dragon book example 4.54, canonical lr(1) has 10 states
+    let gstr: Vec<String> = ["S -> C C", "C -> .c C", "C -> .d"]
+        .iter()
+        .map(|&x| String::from(x))
+        .collect();
+    let cfg = get_cfg(&gstr);
+    let table = match lr1_generator::generate(&cfg) {
+        Ok(t) => t,
+        Err(_) => panic!("conflict in lr(1) grammar"),
+    };
+    assert_eq!(10, table.rows.len());
+    let c = grammar::Symbol::Terminal(0);
+    let d = grammar::Symbol::Terminal(1);
+    assert!(accepts(&table, &[d, d]));
+    assert!(accepts(&table, &[c, d, c, c, d]));
+    assert!(!accepts(&table, &[d]));
+    assert!(!accepts(&table, &[c, d, c]));
+}
+
+#[test]
+fn table_with_lookahead() {
+    let gstr = get_gstr();
+    let cfg = get_cfg(&gstr);
+    let table = match lr1_generator::generate(&cfg) {
+        Ok(t) => t,
+        Err(_) => panic!("conflict in lr(1) grammar"),
+    };
+    //.; .id .:= .+
+    let semi = grammar::Symbol::Terminal(0);
+    let id = grammar::Symbol::Terminal(1);
+    let assign = grammar::Symbol::Terminal(2);
+    let plus = grammar::Symbol::Terminal(3);
+    assert!(accepts(&table, &[id, assign, id, plus, id, semi, id]));
+    assert!(!accepts(&table, &[id, plus, id, assign, id]));
+}
+
+#[test]
+fn conflict() {
+    let gstr: Vec<String> = ["E -> E .+ E", "E -> .id"]
+        .iter()
+        .map(|&x| String::from(x))
+        .collect();
+    let cfg = get_cfg(&gstr);
+    match lr1_generator::generate(&cfg) {
+        Err(parse_table::TableErr::Conflict(_, _, _, symbol)) => {
+            assert_eq!(".+", cfg.symbol_str(&symbol))
+        }
+        Ok(_) => panic!("ambiguous grammar generated a table"),
+    }
+}
@@ -0,0 +1,183 @@
+use crate::parsing::grammar::{builder, Symbol, CFG};
+use crate::parsing::lr_parser::*;
+use crate::parsing::parse_table::Table;
+use crate::parsing::parse_tree::*;
+use crate::parsing::{earley, lr1_generator};
+
+#[allow(dead_code)]
+fn table(gstr: &[&str]) -> Table {
+    let gstr: Vec<String> = gstr.iter().map(|&x| String::from(x)).collect();
+    match lr1_generator::generate(&CFG::from_strings(&gstr).unwrap()) {
+        Ok(table) => table,
+        Err(_) => panic!("grammar should be LR(1)"),
+    }
+}
+
+#[allow(dead_code)]
+fn statements(error_rule: bool) -> Table {
+    let mut gstr = vec![
+        "P -> P S",
+        "P -> S",
+        "S -> .id .= E .;",
+        "E -> E .+ .id",
+        "E -> .id",
+    ];
+    if error_rule {
+        gstr.push("S -> <error> .;");
+    }
+    table(&gstr)
+}
+
+#[allow(dead_code)]
+fn recover(table: &Table, input: &str, recovery: &Recovery) -> Recovered {
+    parse_recovering(table, &tokenize(&table.cfg, input).unwrap(), recovery)
+}
+
+#[allow(dead_code)]
+fn messages(table: &Table, recovered: &Recovered) -> Vec<String> {
+    recovered
+        .errors
+        .iter()
+        .map(|x| x.message(&table.cfg))
+        .collect()
+}
+
+#[test]
+fn same_tree_as_earley() {
+    let table = statements(false);
+    for input in &["id = id ;", "id = id + id ; id = id ;"] {
+        let tokens = tokenize(&table.cfg, input).unwrap();
+        assert_eq!(
+            earley::parse(&table.cfg, &tokens).unwrap(),
+            parse(&table, &tokens).unwrap()
+        );
+    }
+    let table = self::table(&["S -> A .x", "A -> <empty>"]);
+    let tree = parse(&table, &tokenize(&table.cfg, "x").unwrap()).unwrap();
+    assert_eq!("(S' (S (A) x))", tree.to_sexpr(&table.cfg));
+}
+
+#[test]
+fn first_error() {
+    let table = statements(false);
+    let tokens = tokenize(&table.cfg, "id = id id = id ;").unwrap();
+    assert_eq!(
+        "expected `.;` or `.+`, found `.id` at 1:9",
+        parse(&table, &tokens).err().unwrap().message(&table.cfg)
+    );
+}
+
+#[test]
+fn repairs() {
+    let table = statements(false);
+    let recovered = recover(
+        &table,
+        "id = id + id id = id ;\nid = = id ;\nid = id .+ ; id = id ;\nid = id",
+        &Recovery::default(),
+    );
+    assert_eq!(
+        vec![
+            "expected `.;` or `.+`, found `.id` at 1:14, inserted `.;`",
+            "expected `.id`, found `.=` at 2:6, deleted `=`",
+            "expected `.id`, found `.;` at 3:12, inserted `.id`",
+            "expected `.;` or `.+`, found end of input at 4:8, inserted `.;`",
+        ],
+        messages(&table, &recovered)
+    );
+    let tree = recovered.tree.unwrap();
+    //the inserted tokens have no text
+    assert_eq!(
+        "(S' (P (P (P (P (P (P (S id = (E (E id) + id) )) (S id = (E id) ;)) (S id = (E id) ;)) (S id = (E (E id) .+ ) ;)) (S id = (E id) ;)) (S id = (E id) )))",
+        tree.to_sexpr(&table.cfg)
+    );
+
+    //a replacement, `=` for `+`
+    let recovered = recover(&table, "id + id ; id = id ;", &Recovery::default());
+    assert_eq!(
+        vec!["expected `.=`, found `.+` at 1:4, read `+` as `.=`"],
+        messages(&table, &recovered)
+    );
+    assert!(recovered.tree.is_some());
+}
+
+#[test]
+fn error_rules() {
+    let table = statements(true);
+    let recovery = Recovery {
+        repair: false,
+        ..Recovery::default()
+    };
+    let recovered = recover(&table, "id = + + ; id = id ; id id ; id = id ;", &recovery);
+    assert_eq!(2, recovered.errors.len());
+    assert!(matches!(
+        &recovered.errors[0].action,
+        RecoveryAction::ErrorRule(tokens) if tokens.len() == 2
+    ));
+    assert_eq!(
+        "expected `.=`, found `.id` at 1:25, used an error rule for `id`",
+        recovered.errors[1].message(&table.cfg)
+    );
+    assert_eq!(
+        "(S' (P (P (P (P (S + + ;)) (S id = (E id) ;)) (S id ;)) (S id = (E id) ;)))",
+        recovered.tree.unwrap().to_sexpr(&table.cfg)
+    );
+    //the error terminal never comes from the input, or shows up as expected
+    assert!(tokenize(&table.cfg, builder::ERROR_SYMBOL).is_err());
+    let tokens = tokenize(&table.cfg, ";").unwrap();
+    match parse(&table, &tokens) {
+        Err(ParseError::Unexpected(_, expected)) => assert_eq!(1, expected.len()),
+        _ => panic!("should fail"),
+    }
+}
+
+#[test]
+fn panic_mode() {
+    let table = statements(false);
+    let semi = Symbol::Terminal(table.cfg.terminal_symbols.get(".;").unwrap());
+    let recovery = Recovery {
+        sync: vec![semi],
+        repair: false,
+        ..Recovery::default()
+    };
+    let recovered = recover(&table, "id = + + ; id = id ;", &recovery);
+    assert_eq!(
+        vec!["expected `.id`, found `.+` at 1:6, skipped `+ +`"],
+        messages(&table, &recovered)
+    );
+    //E stands in for what was skipped
+    assert_eq!(
+        "(S' (P (P (S id = + + ;)) (S id = (E id) ;)))",
+        recovered.tree.unwrap().to_sexpr(&table.cfg)
+    );
+
+    //without sync tokens the only place left to stop is the end of input, where P stands in for it all
+    let recovered = recover(
+        &table,
+        "id = + ; id = id ;",
+        &Recovery {
+            repair: false,
+            ..Recovery::default()
+        },
+    );
+    assert_eq!(
+        vec!["expected `.id`, found `.+` at 1:6, skipped `+ ; id = id ;`"],
+        messages(&table, &recovered)
+    );
+    assert_eq!(
+        "(S' + ; id = id ;)",
+        recovered.tree.unwrap().to_sexpr(&table.cfg)
+    );
+}
+
+#[test]
+fn max_errors() {
+    let table = statements(true);
+    let recovery = Recovery {
+        max_errors: 2,
+        ..Recovery::default()
+    };
+    let recovered = recover(&table, "; ; ; ; id = id ;", &recovery);
+    assert!(recovered.tree.is_none());
+    assert_eq!(3, recovered.errors.len());
+    assert_eq!(RecoveryAction::GaveUp(), recovered.errors[2].action);
+}
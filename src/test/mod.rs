@@ -1,2 +1,32 @@
+mod ambiguity_test;
+mod codegen_test;
+mod counterexample_test;
+mod coverage_test;
+mod differential_test;
+mod dot_test;
+mod earley_test;
+mod error_messages_test;
+mod glr_test;
+mod grammar_builder_test;
+mod grammar_diff_test;
+mod grammar_docs_test;
 mod grammar_test;
+mod ll1_generator_test;
 mod lr1_generator_test;
+mod lr_parser_test;
+mod repl_test;
+mod report_test;
+mod sentences_test;
+mod table_cache_test;
+mod table_compression_test;
+mod table_serialization_test;
+mod template_test;
+mod transform_test;
+
+use crate::parsing::grammar::CFG;
+
+//grammar from lines of a grammar file, for tests whose grammar is known to load
+pub fn cfg(gstr: &[&str]) -> CFG {
+    let gstr: Vec<String> = gstr.iter().map(|&x| String::from(x)).collect();
+    CFG::from_strings(&gstr).unwrap()
+}
@@ -0,0 +1,114 @@
+use super::cfg;
+use crate::parsing::grammar::Symbol;
+use crate::parsing::lr1_generator;
+use crate::parsing::lr_parser;
+use crate::parsing::parse_table::{Table, TableCell};
+use crate::parsing::parse_tree::tokenize;
+use crate::parsing::repl::*;
+use std::path::PathBuf;
+
+#[allow(dead_code)]
+fn table(gstr: &[&str]) -> Table {
+    lr1_generator::generate(&cfg(gstr)).ok().unwrap()
+}
+
+#[allow(dead_code)]
+fn grammar_file(name: &str, text: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("lmaker_repl_{}_{}.lm", name, std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn trace_steps() {
+    let table = table(&["S -> A .x", "A -> .y"]);
+    let tokens = tokenize(&table.cfg, "y x").unwrap();
+    let mut trace = Vec::new();
+    let tree = lr_parser::parse_traced(&table, &tokens, &mut trace).unwrap();
+    assert_eq!(lr_parser::parse(&table, &tokens).unwrap(), tree);
+    let actions: Vec<(usize, &TableCell)> =
+        trace.iter().map(|x| (x.states.len(), &x.action)).collect();
+    //shift .y, reduce A, goto, shift .x, reduce S, goto, accept
+    assert_eq!(7, actions.len());
+    assert!(matches!(actions[0], (1, TableCell::Shift(_))));
+    assert!(matches!(actions[1], (2, TableCell::Reduce(2))));
+    assert!(matches!(actions[2], (1, TableCell::Goto(_))));
+    assert!(matches!(actions[6], (2, TableCell::Accept())));
+    assert_eq!(Symbol::EOF(), trace[6].symbol);
+}
+
+#[test]
+fn explain_input() {
+    let table = table(&["S -> A .x", "A -> .y", ".y = \"why\""]);
+    assert_eq!(
+        "tokens: .y .x
+  0      .y     shift, go to state 1
+  0 1    .x     reduce A -> .y
+  0      A      go to state 3
+  0 3    .x     shift, go to state 4
+  0 3 4  <eof>  reduce S -> A .x
+  0      S      go to state 2
+  0 2    <eof>  accept
+tree:
+S'
+  S
+    A
+      .y
+    .x
+  <eof>
+",
+        explain(&table, "y .x")
+    );
+    let out = explain(&table, "y y");
+    assert!(out.starts_with("tokens: .y .y\n  0  .y  shift, go to state 1\nerror: "));
+    assert!(out.ends_with("error: expected `.x`, found why at 1:3\n"));
+    assert_eq!("error: unknown token z at 1:1\n", explain(&table, "z"));
+}
+
+#[test]
+fn commands_and_reload() {
+    let path = grammar_file("reload", "S -> .a\n");
+    let mut repl = Repl::load(&path).unwrap();
+    assert_eq!("2 productions, 3 states", repl.summary());
+    assert!(repl.eval("a").unwrap().contains("tree:\n"));
+    assert!(repl
+        .eval("b")
+        .unwrap()
+        .starts_with("error: unknown token b"));
+    assert!(repl.eval(":grammar").unwrap().contains("1 S -> .a\n"));
+    assert!(repl
+        .eval(":nope")
+        .unwrap()
+        .starts_with("unknown command :nope"));
+    assert_eq!(Some(String::new()), repl.eval("  "));
+
+    //editing the file and reloading picks up the new grammar
+    std::fs::write(&path, "S -> .a\nS -> .b S\n").unwrap();
+    assert!(repl.eval(":r").unwrap().starts_with("reloaded "));
+    assert!(repl.eval("b a").unwrap().contains("tree:\n"));
+
+    //a grammar with a conflict keeps the one before it
+    std::fs::write(&path, "S -> S S\nS -> .a\n").unwrap();
+    let out = repl.eval(":reload").unwrap();
+    assert!(out.contains("isn't lr(1), conflict in state "));
+    assert!(out.ends_with("still using the previous grammar\n"));
+    assert!(repl.eval("b a").unwrap().contains("tree:\n"));
+
+    //so does one that doesn't parse
+    std::fs::write(&path, "S -> .a\nE .id\n").unwrap();
+    let out = repl.eval(":r").unwrap();
+    assert!(out.contains(": line 2: expected -> after the nonterminal\n"));
+    assert!(out.ends_with("still using the previous grammar\n"));
+    assert!(repl.eval("b a").unwrap().contains("tree:\n"));
+    assert!(Repl::load(&path)
+        .err()
+        .unwrap()
+        .ends_with("line 2: expected -> after the nonterminal"));
+
+    assert_eq!(None, repl.eval(":q"));
+    std::fs::remove_file(&path).unwrap();
+    assert!(Repl::load(&path)
+        .err()
+        .unwrap()
+        .contains(": couldn't read the file: "));
+}
@@ -0,0 +1,106 @@
+use super::cfg;
+use crate::parsing::report::*;
+
+//the whole report, any change to the format shows up here
+const AMBIGUOUS: &str = r#"Grammar
+
+      0 S' -> E <eof>
+      1 E -> E .+ E
+      2 E -> .id
+
+Aliases
+
+    .id = "identifier"
+
+First sets
+
+    S': .id
+    E: .id
+
+Follow sets
+
+    S': <eof>
+    E: .+ <eof>
+
+Conflicts
+
+    state 4 on .+: shift, go to state 3 / reduce E -> E .+ E, unresolved
+
+
+State 0
+
+    S' -> • E <eof>, <eof>
+
+    E -> • E .+ E, .+ <eof>
+    E -> • .id, .+ <eof>
+
+    .id  shift, go to state 1
+
+    E  go to state 2
+
+
+State 1
+
+    E -> .id •, .+ <eof>
+
+    .+     reduce E -> .id
+    <eof>  reduce E -> .id
+
+
+State 2
+
+    S' -> E • <eof>, <eof>
+    E -> E • .+ E, .+ <eof>
+
+    .+     shift, go to state 3
+    <eof>  accept
+
+
+State 3
+
+    E -> E .+ • E, .+ <eof>
+
+    E -> • E .+ E, .+ <eof>
+    E -> • .id, .+ <eof>
+
+    .id  shift, go to state 1
+
+    E  go to state 4
+
+
+State 4
+
+    E -> E • .+ E, .+ <eof>
+    E -> E .+ E •, .+ <eof>
+
+    .+     shift, go to state 3 / reduce E -> E .+ E
+    <eof>  reduce E -> E .+ E
+"#;
+
+#[test]
+fn ambiguous_grammar() {
+    let cfg = cfg(&["E -> E .+ E", "E -> .id", ".id = \"identifier\""]);
+    assert_eq!(AMBIGUOUS, report(&cfg));
+}
+
+#[test]
+fn stable() {
+    let cfg = cfg(&[
+        "P -> P S",
+        "P -> S",
+        "S -> .id .= E .;",
+        "S -> <empty>",
+        "E -> E .+ .id",
+        "E -> .id",
+    ]);
+    assert_eq!(report(&cfg), report(&cfg.clone()));
+    let report = report(&cfg);
+    assert!(report.contains("      4 S -> <empty>\n"));
+    assert!(report.contains("\nConflicts\n\n    state 0 on "));
+    //the sections info prints are the start of the report
+    assert!(report.starts_with(&format!(
+        "{}\n{}",
+        grammar_section(&cfg),
+        sets_section(&cfg)
+    )));
+}
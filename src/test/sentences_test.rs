@@ -0,0 +1,132 @@
+use super::cfg;
+use crate::parsing::earley;
+use crate::parsing::grammar::sentences::*;
+use crate::parsing::grammar::{generate_sentences, Symbol, CFG};
+use crate::parsing::parse_tree::{tokenize, ParseTree};
+use std::collections::HashSet;
+
+#[allow(dead_code)]
+fn statements() -> CFG {
+    cfg(&[
+        "P -> P S",
+        "P -> S",
+        "S -> .id .= E .;",
+        "S -> .print E .;",
+        "S -> .{ P .}",
+        "E -> E .+ T",
+        "E -> T",
+        "T -> .id",
+        "T -> .( E .)",
+    ])
+}
+
+#[allow(dead_code)]
+fn texts(cfg: &CFG, sentences: &[Vec<Symbol>]) -> Vec<String> {
+    sentences.iter().map(|x| sentence_text(cfg, x)).collect()
+}
+
+//production ids used in the tree
+#[allow(dead_code)]
+fn productions(tree: &ParseTree, used: &mut HashSet<usize>) {
+    if let ParseTree::Node(production, children) = tree {
+        used.insert(*production);
+        for child in children {
+            productions(child, used);
+        }
+    }
+}
+
+#[test]
+fn random_sentences_parse() {
+    let cfg = statements();
+    let sentences = generate_sentences(&cfg, &SentenceOptions::default());
+    assert_eq!(10, sentences.len());
+    let mut used = HashSet::new();
+    for text in texts(&cfg, &sentences) {
+        //the text reads back as the same terminals
+        let tree = earley::parse(&cfg, &tokenize(&cfg, &text).unwrap()).unwrap();
+        productions(&tree, &mut used);
+    }
+    //coverage makes sure every production shows up
+    assert_eq!(cfg.production_count(), used.len());
+
+    //the same seed gives the same sentences, another seed different ones
+    assert_eq!(
+        sentences,
+        generate_sentences(&cfg, &SentenceOptions::default())
+    );
+    let other = SentenceOptions {
+        mode: SentenceMode::Random(7),
+        ..SentenceOptions::default()
+    };
+    assert_ne!(sentences, generate_sentences(&cfg, &other));
+}
+
+#[test]
+fn depth_limit() {
+    let cfg = statements();
+    //P, S, E, T is the shallowest tree, with id = id ; or print id ;
+    let options = SentenceOptions {
+        max_depth: 4,
+        count: 100,
+        ..SentenceOptions::default()
+    };
+    let mut sentences = texts(&cfg, &generate_sentences(&cfg, &options));
+    sentences.sort();
+    assert_eq!(vec!["id = id ;", "print id ;"], sentences);
+    let options = SentenceOptions {
+        max_depth: 3,
+        ..SentenceOptions::default()
+    };
+    assert!(generate_sentences(&cfg, &options).is_empty());
+}
+
+#[test]
+fn exhaustive() {
+    let cfg = cfg(&["E -> E .+ .id", "E -> .id", ".id = identifier"]);
+    let options = SentenceOptions {
+        mode: SentenceMode::Exhaustive(),
+        max_depth: 3,
+        ..SentenceOptions::default()
+    };
+    assert_eq!(
+        vec!["id", "id + id", "id + id + id"],
+        texts(&cfg, &generate_sentences(&cfg, &options))
+    );
+    let options = SentenceOptions {
+        mode: SentenceMode::Exhaustive(),
+        count: 4,
+        ..SentenceOptions::default()
+    };
+    assert_eq!(4, generate_sentences(&cfg, &options).len());
+
+    //ambiguous grammars don't repeat sentences, and empties are left out
+    let cfg = self::cfg(&["S -> S S", "S -> .a", "S -> <empty>"]);
+    let options = SentenceOptions {
+        mode: SentenceMode::Exhaustive(),
+        max_depth: 3,
+        count: 100,
+        ..SentenceOptions::default()
+    };
+    let sentences = generate_sentences(&cfg, &options);
+    let unique: HashSet<&Vec<Symbol>> = sentences.iter().collect();
+    assert_eq!(unique.len(), sentences.len());
+    assert_eq!(
+        vec!["a", "", "a a", "a a a", "a a a a"],
+        texts(&cfg, &sentences)
+    );
+}
+
+#[test]
+fn text() {
+    //a terminal that is also the name of another one keeps its dot
+    let cfg = cfg(&["S -> .x x", "x -> .y"]);
+    let cfg_with_clash = {
+        let mut cfg = cfg.clone();
+        cfg.terminal_symbols.intern("y");
+        cfg
+    };
+    let sentence = vec![Symbol::Terminal(0), Symbol::Terminal(1)];
+    assert_eq!("x y", sentence_text(&cfg, &sentence));
+    assert_eq!("x .y", sentence_text(&cfg_with_clash, &sentence));
+}
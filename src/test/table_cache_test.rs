@@ -0,0 +1,101 @@
+use crate::parsing::table_cache::*;
+use crate::parsing::*;
+use std::path::PathBuf;
+
+#[allow(dead_code)]
+fn cache_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("lmaker_cache_{}_{}", name, std::process::id()))
+}
+
+#[test]
+fn whitespace_change_hits_cache() {
+    let dir = cache_dir("whitespace");
+    let cache = TableCache::new(&dir);
+
+    let original = grammar::grammar_lines("E -> E .+ .id\nE -> .id\n");
+    let cfg = grammar::CFG::from_strings(&original).unwrap();
+    let (table, status) = cache
+        .get_or_generate(&cfg, "lr1", || lr1_generator::generate(&cfg))
+        .ok()
+        .unwrap();
+    assert_eq!(CacheStatus::Miss, status);
+
+    let reformatted = grammar::grammar_lines("# comment\n\n  E  ->   E .+\t.id   \n\n\nE -> .id");
+    let cfg2 = grammar::CFG::from_strings(&reformatted).unwrap();
+    let (cached, status) = cache
+        .get_or_generate(&cfg2, "lr1", || panic!("should be cached"))
+        .ok()
+        .unwrap();
+    assert_eq!(CacheStatus::Hit, status);
+    assert_eq!(table.rows.len(), cached.rows.len());
+    for (a, b) in table.rows.iter().zip(cached.rows.iter()) {
+        assert_eq!(a.cells, b.cells);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn changes_miss_cache() {
+    let dir = cache_dir("changes");
+    let mut cache = TableCache::new(&dir);
+    let a = grammar::grammar_lines("E -> E .+ .id\nE -> .id");
+    let b = grammar::grammar_lines("E -> E .- .id\nE -> .id");
+    let cfg_a = grammar::CFG::from_strings(&a).unwrap();
+    let cfg_b = grammar::CFG::from_strings(&b).unwrap();
+    assert_ne!(
+        TableCache::key(&cfg_a, "lr1"),
+        TableCache::key(&cfg_b, "lr1")
+    );
+    assert_ne!(
+        TableCache::key(&cfg_a, "lr1"),
+        TableCache::key(&cfg_a, "lalr")
+    );
+
+    let generate = || lr1_generator::generate(&cfg_a);
+    assert_eq!(
+        CacheStatus::Miss,
+        cache
+            .get_or_generate(&cfg_a, "lr1", generate)
+            .ok()
+            .unwrap()
+            .1
+    );
+    let generate = || lr1_generator::generate(&cfg_b);
+    assert_eq!(
+        CacheStatus::Miss,
+        cache
+            .get_or_generate(&cfg_b, "lr1", generate)
+            .ok()
+            .unwrap()
+            .1
+    );
+    //--no-cache
+    cache.enabled = false;
+    let generate = || lr1_generator::generate(&cfg_a);
+    assert_eq!(
+        CacheStatus::Disabled,
+        cache
+            .get_or_generate(&cfg_a, "lr1", generate)
+            .ok()
+            .unwrap()
+            .1
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn corrupt_entry_is_regenerated() {
+    let dir = cache_dir("corrupt");
+    let cache = TableCache::new(&dir);
+    let gstr = grammar::grammar_lines("E -> .id");
+    let cfg = grammar::CFG::from_strings(&gstr).unwrap();
+    let key = TableCache::key(&cfg, "lr1");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(format!("{}.lmt", key)), b"garbage").unwrap();
+    let generate = || lr1_generator::generate(&cfg);
+    assert_eq!(
+        CacheStatus::Miss,
+        cache.get_or_generate(&cfg, "lr1", generate).ok().unwrap().1
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
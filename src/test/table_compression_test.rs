@@ -0,0 +1,116 @@
+use crate::parsing::table_compression::*;
+use crate::parsing::*;
+
+#[allow(dead_code)]
+fn grammars() -> Vec<Vec<String>> {
+    let gstrs: Vec<Vec<&str>> = vec![
+        vec![
+            "E -> E .+ T",
+            "E -> T",
+            "T -> T .* F",
+            "T -> F",
+            "F -> .( E .)",
+            "F -> .id",
+        ],
+        vec![
+            "S -> S .; A",
+            "S -> A",
+            "A -> E",
+            "A -> .id .:= E",
+            "E -> E .+ .id",
+            "E -> .id",
+        ],
+        vec!["S -> C C", "C -> .c C", "C -> .d"],
+    ];
+    gstrs
+        .iter()
+        .map(|g| g.iter().map(|&x| String::from(x)).collect())
+        .collect()
+}
+
+#[allow(dead_code)]
+fn all_symbols(cfg: &grammar::CFG) -> Vec<grammar::Symbol> {
+    let mut symbols = vec![grammar::Symbol::EOF()];
+    for i in 0..cfg.terminal_symbols.len() {
+        symbols.push(grammar::Symbol::Terminal(i));
+    }
+    for i in 0..cfg.nonterminal_symbols.len() {
+        symbols.push(grammar::Symbol::Nonterminal(i));
+    }
+    symbols
+}
+
+#[test]
+fn exact_without_default_reductions() {
+    for gstr in grammars() {
+        let cfg = grammar::CFG::from_strings(&gstr).unwrap();
+        let table = lr1_generator::generate(&cfg).ok().unwrap();
+        let options = CompressionOptions {
+            default_reductions: false,
+        };
+        let compressed = compress(&table, &options);
+        for (state, row) in table.rows.iter().enumerate() {
+            for symbol in all_symbols(&cfg) {
+                assert_eq!(
+                    row.cells.get(&symbol).copied(),
+                    compressed.get(state, &symbol)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn matches_with_default_reductions() {
+    for gstr in grammars() {
+        let cfg = grammar::CFG::from_strings(&gstr).unwrap();
+        let table = lr1_generator::generate(&cfg).ok().unwrap();
+        let compressed = compress(&table, &CompressionOptions::default());
+        for (state, row) in table.rows.iter().enumerate() {
+            for symbol in all_symbols(&cfg) {
+                let lookup = compressed.get(state, &symbol);
+                match row.cells.get(&symbol) {
+                    Some(cell) => assert_eq!(Some(*cell), lookup),
+                    None if matches!(symbol, grammar::Symbol::Nonterminal(_)) => {
+                        assert_eq!(None, lookup)
+                    }
+                    //errors may only turn into the default reduction
+                    None => assert_eq!(
+                        compressed.default_reductions[state].map(parse_table::TableCell::Reduce),
+                        lookup
+                    ),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn stats() {
+    let gstr = &grammars()[0];
+    let cfg = grammar::CFG::from_strings(gstr).unwrap();
+    let table = lr1_generator::generate(&cfg).ok().unwrap();
+    let compressed = compress(&table, &CompressionOptions::default());
+    let stats = compressed.stats(&table);
+    assert_eq!(table.rows.len(), stats.states);
+    assert_eq!(table.rows.len() * 10, stats.dense_entries);
+    assert!(stats.compressed_entries < stats.dense_entries);
+    assert!(stats.distinct_action_rows < stats.states);
+    assert!(stats.default_reductions > 0);
+    assert!(stats.ratio() < 1.0);
+}
+
+#[test]
+fn encoding() {
+    for cell in [
+        None,
+        Some(parse_table::TableCell::Shift(0)),
+        Some(parse_table::TableCell::Shift(7)),
+        Some(parse_table::TableCell::Reduce(0)),
+        Some(parse_table::TableCell::Reduce(3)),
+        Some(parse_table::TableCell::Goto(5)),
+        Some(parse_table::TableCell::Accept()),
+    ] {
+        assert_eq!(cell, decode_cell(encode_cell(cell)));
+    }
+}
@@ -0,0 +1,219 @@
+use crate::parsing::table_serialization::*;
+use crate::parsing::*;
+use crate::util::json::{self, Json};
+
+#[allow(dead_code)]
+fn get_gstr() -> Vec<String> {
+    let gstr = [
+        "S -> S .; A",
+        "S -> A",
+        "A -> E",
+        "A -> .id .:= E",
+        "E -> E .+ .id",
+        "E -> .id",
+        ".id = \"identifier\"",
+    ];
+    gstr.iter().map(|&x| String::from(x)).collect()
+}
+
+#[allow(dead_code)]
+fn saved() -> SavedTable {
+    let gstr = get_gstr();
+    let cfg = grammar::CFG::from_strings(&gstr).unwrap();
+    let table = lr1_generator::generate(&cfg).ok().unwrap();
+    SavedTable::from_table(&table)
+}
+
+#[test]
+fn json_round_trip() {
+    let saved = saved();
+    let json = saved.to_json();
+    assert!(json.contains("\"format\": \"lmaker-table\""));
+    assert_eq!(saved, SavedTable::from_json(&json).unwrap());
+    //output is stable
+    assert_eq!(json, SavedTable::from_json(&json).unwrap().to_json());
+}
+
+#[test]
+fn binary_round_trip() {
+    let saved = saved();
+    let bytes = saved.to_binary();
+    assert_eq!(b"LMKT", &bytes[0..4]);
+    assert_eq!(saved, SavedTable::from_binary(&bytes).unwrap());
+}
+
+#[test]
+fn loaded_table_matches() {
+    let gstr = get_gstr();
+    let cfg = grammar::CFG::from_strings(&gstr).unwrap();
+    let table = lr1_generator::generate(&cfg).ok().unwrap();
+    let loaded = SavedTable::from_binary(&SavedTable::from_table(&table).to_binary()).unwrap();
+    let loaded_cfg = loaded.cfg();
+    let loaded_table = loaded.table();
+    assert_eq!(loaded_cfg, loaded_table.cfg);
+    assert_eq!(cfg, loaded_cfg);
+    assert_eq!(cfg.nonterminal_symbols, loaded_cfg.nonterminal_symbols);
+    assert_eq!(cfg.terminal_symbols, loaded_cfg.terminal_symbols);
+    assert_eq!(cfg.production_count(), loaded_cfg.production_count());
+    for id in 0..cfg.production_count() {
+        assert!(cfg.production(id) == loaded_cfg.production(id));
+    }
+    assert_eq!(table.rows.len(), loaded_table.rows.len());
+    for (a, b) in table.rows.iter().zip(loaded_table.rows.iter()) {
+        assert_eq!(a.cells, b.cells);
+    }
+}
+
+#[test]
+fn version_1_tables() {
+    let saved = saved();
+    assert_eq!(1, saved.aliases.len());
+    //version 1 ended after the rows, where the alias count is now
+    let mut bytes = SavedTable {
+        aliases: Vec::new(),
+        ..saved
+    }
+    .to_binary();
+    assert_eq!(Some(0), bytes.pop());
+    assert_eq!(Some(0), bytes.pop());
+    bytes[4] = 1;
+    let loaded = SavedTable::from_binary(&bytes).unwrap();
+    assert!(loaded.aliases.is_empty());
+    assert!(loaded.cfg().aliases.is_empty());
+}
+
+#[test]
+fn version_2_tables() {
+    let saved = saved();
+    //version 2 ended after the aliases, where the extra count is now
+    let mut bytes = saved.to_binary();
+    assert_eq!(Some(0), bytes.pop());
+    bytes[4] = 2;
+    assert_eq!(saved, SavedTable::from_binary(&bytes).unwrap());
+    let json = saved.to_json().replace("\"version\": 3", "\"version\": 2");
+    let json = json.replace(",\n  \"extra\": []", "");
+    assert!(!json.contains("extra"));
+    assert_eq!(saved, SavedTable::from_json(&json).unwrap());
+}
+
+#[test]
+fn glr_tables() {
+    let gstr: Vec<String> = ["E -> E .+ E", "E -> .id"]
+        .iter()
+        .map(|&x| String::from(x))
+        .collect();
+    let table = lr1_generator::generate_glr(&grammar::CFG::from_strings(&gstr).unwrap());
+    let saved = SavedTable::from_table(&table);
+    assert!(!saved.extra.is_empty());
+    assert_eq!(saved, SavedTable::from_json(&saved.to_json()).unwrap());
+    let loaded = SavedTable::from_binary(&saved.to_binary()).unwrap().table();
+    for (a, b) in table.rows.iter().zip(loaded.rows.iter()) {
+        assert_eq!(a.cells, b.cells);
+        assert_eq!(a.extra, b.extra);
+    }
+    let mut bad = SavedTable::from_table(&table);
+    bad.extra[0].0 = table.rows.len();
+    assert!(matches!(
+        SavedTable::from_binary(&bad.to_binary()),
+        Err(TableFormatError::Invalid(_))
+    ));
+}
+
+#[test]
+fn rejects_bad_input() {
+    let saved = saved();
+    let bytes = saved.to_binary();
+    assert!(matches!(
+        SavedTable::from_binary(&bytes[..bytes.len() - 1]),
+        Err(TableFormatError::Truncated)
+    ));
+    assert!(matches!(
+        SavedTable::from_binary(b"nope nope"),
+        Err(TableFormatError::NotATable)
+    ));
+    let mut newer = bytes.clone();
+    newer[4] = 99;
+    assert!(matches!(
+        SavedTable::from_binary(&newer),
+        Err(TableFormatError::UnsupportedVersion(99))
+    ));
+    let json = saved.to_json().replace("\"s", "\"s9");
+    assert!(matches!(
+        SavedTable::from_json(&json),
+        Err(TableFormatError::Invalid(_))
+    ));
+    let mut duplicate = self::saved();
+    duplicate.terminal_symbols[1] = duplicate.terminal_symbols[0].clone();
+    assert!(matches!(
+        SavedTable::from_binary(&duplicate.to_binary()),
+        Err(TableFormatError::Invalid(_))
+    ));
+    let mut duplicate = self::saved();
+    duplicate.nonterminal_symbols[2] = duplicate.nonterminal_symbols[1].clone();
+    assert!(matches!(
+        SavedTable::from_json(&duplicate.to_json()),
+        Err(TableFormatError::Invalid(_))
+    ));
+    assert!(matches!(
+        SavedTable::from_json("{\"format\": \"other\"}"),
+        Err(TableFormatError::NotATable)
+    ));
+}
+
+#[allow(dead_code)]
+fn is_invalid(saved: &SavedTable) -> bool {
+    matches!(
+        SavedTable::from_binary(&saved.to_binary()),
+        Err(TableFormatError::Invalid(_))
+    )
+}
+
+#[test]
+fn rejects_tables_the_runtime_would_panic_on() {
+    assert!(!is_invalid(&saved()));
+    let mut empty = saved();
+    empty.rows.clear();
+    assert!(is_invalid(&empty));
+    let mut start = saved();
+    start.productions[0].rhs.pop();
+    assert!(is_invalid(&start));
+    //S has no goto left to come back to after reducing it
+    let mut no_goto = saved();
+    let s = grammar::Symbol::Nonterminal(1);
+    for row in &mut no_goto.rows {
+        row.retain(|(symbol, _)| *symbol != s);
+    }
+    assert!(is_invalid(&no_goto));
+    //S -> S .; A reduced in the start state pops more than the stack has
+    let mut pops = saved();
+    pops.rows[0][0].1 = parse_table::TableCell::Reduce(1);
+    assert!(is_invalid(&pops));
+    let mut accept = saved();
+    accept.rows[0][0].1 = parse_table::TableCell::Accept();
+    assert!(is_invalid(&accept));
+}
+
+#[test]
+fn json_values() {
+    let value =
+        Json::parse(r#"{"a": [1, 2.5, -3], "b": "x\"é😀", "c": [true, false, null], "d": {}}"#)
+            .unwrap();
+    assert_eq!(
+        Some(1),
+        value.get("a").unwrap().as_array().unwrap()[0].as_usize()
+    );
+    assert_eq!(Some("x\"é😀"), value.get("b").unwrap().as_str());
+    assert_eq!(value, Json::parse(&value.to_string_pretty()).unwrap());
+    assert_eq!(
+        Some("😀\n"),
+        Json::parse(r#""\ud83d\ude00\n""#).unwrap().as_str()
+    );
+    assert!(Json::parse("[1, 2").is_err());
+    assert!(Json::parse("{\"a\" 1}").is_err());
+    assert!(Json::parse("1 2").is_err());
+    //deep nesting is an error instead of a stack overflow
+    let deep = |n: usize| format!("{}{}", "[".repeat(n), "]".repeat(n));
+    assert!(Json::parse(&deep(json::MAX_DEPTH)).is_ok());
+    let err = Json::parse(&deep(100_000)).unwrap_err();
+    assert_eq!("nested too deeply", err.message);
+}
@@ -0,0 +1,310 @@
+use crate::codegen::code_generator::*;
+use crate::codegen::code_generator_template::*;
+use crate::codegen::file_system::*;
+use crate::codegen::template::*;
+use crate::parsing::*;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[allow(dead_code)]
+fn get_gstr() -> Vec<String> {
+    let gstr = ["E -> E .+ .id", "E -> .id"];
+    gstr.iter().map(|&x| String::from(x)).collect()
+}
+
+#[allow(dead_code)]
+fn render(source: &str, context: &Value) -> String {
+    Template::parse(source).unwrap().render(context).unwrap()
+}
+
+#[test]
+fn variables_and_loops() {
+    let context = Value::object()
+        .with("name", Value::Str(String::from("x")))
+        .with(
+            "items",
+            Value::List(vec![
+                Value::object().with("v", Value::Int(1)),
+                Value::object().with("v", Value::Int(2)),
+            ]),
+        );
+    assert_eq!(
+        "x: 1, 2",
+        render(
+            "{{name}}: {{#each items}}{{v}}{{#if @last}}{{else}}, {{/if}}{{/each}}",
+            &context
+        )
+    );
+    assert_eq!(
+        "0x1",
+        render(
+            "{{#each items}}{{#if @first}}{{@index}}{{name}}{{v}}{{/if}}{{/each}}",
+            &context
+        )
+    );
+}
+
+#[test]
+fn standalone_tags_leave_no_lines() {
+    let context = Value::object().with("items", Value::List(vec![Value::Int(1), Value::Int(2)]));
+    let source = "start\n  {{#each items}}\n  item {{this}}\n  {{/each}}\nend\n";
+    assert_eq!("start\n  item 1\n  item 2\nend\n", render(source, &context));
+}
+
+#[test]
+fn errors() {
+    let context = Value::object();
+    assert!(Template::parse("{{#each x}}").is_err());
+    assert!(Template::parse("{{#if x}}{{/each}}").is_err());
+    assert!(Template::parse("{{/if}}").is_err());
+    assert!(Template::parse("{{x").is_err());
+    let err = Template::parse("\n\n{{missing}}")
+        .unwrap()
+        .render(&context)
+        .unwrap_err();
+    assert_eq!(3, err.line);
+
+    //lists and objects are for #each and #if, printing one is an error
+    let context = Value::object()
+        .with("list", Value::List(vec![Value::Int(1)]))
+        .with("object", Value::object());
+    for source in ["{{list}}", "{{object}}"] {
+        let err = Template::parse(source)
+            .unwrap()
+            .render(&context)
+            .unwrap_err();
+        assert!(err.message.ends_with("can't be printed"), "{}", err.message);
+    }
+    assert_eq!("yes", render("{{#if list}}yes{{/if}}", &context));
+}
+
+#[test]
+fn ident_and_quote() {
+    assert_eq!("id", ident(".id"));
+    assert_eq!("_2B", ident(".+"));
+    assert_eq!("S_27", ident("S'"));
+    assert_eq!("\"a\\\"b\"", quote("a\"b"));
+}
+
+#[test]
+fn generate_from_table() {
+    let gstr = get_gstr();
+    let cfg = grammar::CFG::from_strings(&gstr).unwrap();
+    let mut cells = HashMap::new();
+    cells.insert(
+        grammar::Symbol::Terminal(1),
+        parse_table::TableCell::Shift(2),
+    );
+    cells.insert(
+        grammar::Symbol::Nonterminal(1),
+        parse_table::TableCell::Goto(1),
+    );
+    let name = String::from("calc");
+    let data = CodeGeneratorData {
+        table: parse_table::Table {
+            rows: vec![parse_table::TableRow::new(cells)],
+            cfg: cfg.clone(),
+        },
+        project_name: name.clone(),
+        messages: BTreeMap::new(),
+    };
+    let mut generator = CodeGeneratorTemplate::new();
+    generator
+        .add(
+            "{{project_name}}.txt",
+            "{{#each productions}}\n{{index}}: {{lhs}} ->{{#each rhs}} {{name}}{{/each}}\n{{/each}}\n{{#each states}}\n{{#each cells}}\n{{index}} {{symbol_ident}} {{action}} {{target}}\n{{/each}}\n{{/each}}\n",
+        )
+        .unwrap();
+    let mut fs = MemoryFileSystem::new();
+    let files = generator.generate(&data, &mut fs).unwrap();
+    assert_eq!(vec![PathBuf::from("calc.txt")], files.files);
+    assert_eq!(
+        "0: S' -> E <eof>\n1: E -> E .+ .id\n2: E -> .id\n0 id shift 2\n0 E goto 1\n",
+        fs.get_str("calc.txt").unwrap()
+    );
+}
+
+#[allow(dead_code)]
+fn render_table(table: parse_table::Table, template: &str) -> String {
+    let data = CodeGeneratorData {
+        table,
+        project_name: String::from("calc"),
+        messages: BTreeMap::new(),
+    };
+    let mut generator = CodeGeneratorTemplate::new();
+    generator.add("out.txt", template).unwrap();
+    let mut fs = MemoryFileSystem::new();
+    generator.generate(&data, &mut fs).unwrap();
+    String::from(fs.get_str("out.txt").unwrap())
+}
+
+#[test]
+fn empty_productions() {
+    let gstr: Vec<String> = ["A -> .a A", "A -> <empty>"]
+        .iter()
+        .map(|&x| String::from(x))
+        .collect();
+    let table = lr1_generator::generate(&grammar::CFG::from_strings(&gstr).unwrap())
+        .ok()
+        .unwrap();
+    //an epsilon reduction pops nothing, and <empty> isn't a symbol to push or pop
+    assert_eq!(
+        "S' 2: A <eof>\nA 2: .a A\nA 0:\n",
+        render_table(
+            table,
+            "{{#each productions}}\n{{lhs}} {{length}}:{{#each rhs}} {{name}}{{/each}}\n{{/each}}\n"
+        )
+    );
+}
+
+#[test]
+fn unique_idents() {
+    let gstr: Vec<String> = ["S -> .+ ._2B A' A_27", "A' -> .x", "A_27 -> .y"]
+        .iter()
+        .map(|&x| String::from(x))
+        .collect();
+    let cfg = grammar::CFG::from_strings(&gstr).unwrap();
+    let table = parse_table::Table {
+        rows: Vec::new(),
+        cfg,
+    };
+    //.+ and ._2B are both _2B, A' and A_27 are both A_27
+    assert_eq!(
+        "_2B _2B_2 x y \n S_27 S A_27 A_27_2\n",
+        render_table(
+            table,
+            "{{#each terminals}}{{ident}} {{/each}}\n{{#each nonterminals}} {{ident}}{{/each}}\n"
+        )
+    );
+}
+
+//runs the compressed arrays from the model the way a generated parser would, and gives the
+//message of the state the first error is found in
+#[allow(dead_code)]
+fn message_of_error(gstr: &[&str], messages: &str, input: &str) -> String {
+    let gstr: Vec<String> = gstr.iter().map(|&x| String::from(x)).collect();
+    let cfg = grammar::CFG::from_strings(&gstr).unwrap();
+    let table = lr1_generator::generate(&cfg).ok().unwrap();
+    let tokens = parse_tree::tokenize(&cfg, input).unwrap();
+    let messages = error_messages::Messages::parse(messages).unwrap();
+    let data = CodeGeneratorData {
+        messages: messages.compile(&table).by_state,
+        table,
+        project_name: String::from("calc"),
+    };
+    let mut generator = CodeGeneratorTemplate::new();
+    let arrays = [
+        "default_reductions",
+        "action_row",
+        "action_base",
+        "action_next",
+        "action_check",
+        "goto_row",
+        "goto_base",
+        "goto_next",
+        "goto_check",
+    ];
+    let mut template = String::new();
+    for name in arrays {
+        template.push_str(&format!(
+            "{{{{#each compressed.{}}}}} {{{{this}}}}{{{{/each}}}}\n",
+            name
+        ));
+    }
+    template.push_str("{{#each states}}{{message}}\n{{/each}}");
+    generator.add("out.txt", &template).unwrap();
+    let mut fs = MemoryFileSystem::new();
+    generator.generate(&data, &mut fs).unwrap();
+    let out = String::from(fs.get_str("out.txt").unwrap());
+    let lines: Vec<&str> = out.lines().collect();
+    let ints: Vec<Vec<usize>> = lines[..arrays.len()]
+        .iter()
+        .map(|x| x.split_whitespace().map(|x| x.parse().unwrap()).collect())
+        .collect();
+    let (default, row, base, next, check) = (&ints[0], &ints[1], &ints[2], &ints[3], &ints[4]);
+    let (goto_row, goto_base, goto_next, goto_check) = (&ints[5], &ints[6], &ints[7], &ints[8]);
+    let action = |state: usize, column: usize| {
+        let slot = base[row[state]] + column;
+        match check.get(slot) {
+            Some(x) if *x == row[state] + 1 => next[slot],
+            _ if default[state] == 0 => 0,
+            _ => 4 * (default[state] - 1) + 2,
+        }
+    };
+    let goto = |state: usize, nonterminal: usize| {
+        let slot = goto_base[goto_row[state]] + nonterminal;
+        assert_eq!(goto_row[state] + 1, goto_check[slot]);
+        goto_next[slot] / 4
+    };
+    let cfg = &data.table.cfg;
+    let columns = tokens.iter().map(|x| match x.symbol {
+        grammar::Symbol::Terminal(t) => t,
+        _ => cfg.terminal_symbols.len(),
+    });
+    let mut states = vec![0];
+    for column in columns.chain(std::iter::once(cfg.terminal_symbols.len())) {
+        loop {
+            let state = *states.last().unwrap();
+            match table_compression::decode_cell(action(state, column)) {
+                Some(parse_table::TableCell::Shift(x)) => {
+                    states.push(x);
+                    break;
+                }
+                Some(parse_table::TableCell::Reduce(p)) => {
+                    let production = cfg.production(p);
+                    states.truncate(states.len() - production.rhs_len());
+                    let top = *states.last().unwrap();
+                    states.push(goto(top, production.nonterminal));
+                }
+                Some(_) => panic!("the input parses"),
+                None => return String::from(lines[arrays.len() + state]),
+            }
+        }
+    }
+    panic!("the input parses")
+}
+
+#[test]
+fn compressed_error_messages() {
+    let gstr = ["S -> .id .= E .;", "E -> .id"];
+    let messages = ".id .= .id .id\n\nexpected `;` after the expression\n";
+    assert_eq!(
+        "expected `;` after the expression",
+        message_of_error(&gstr, messages, "id = id id")
+    );
+}
+
+#[test]
+fn from_directory() {
+    let root = std::env::temp_dir().join(format!("lmaker_templates_{}", std::process::id()));
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(
+        root.join("src/{{project_name}}.py.tmpl"),
+        "# {{project_name}}",
+    )
+    .unwrap();
+    std::fs::write(root.join("README"), "not a template").unwrap();
+    let mut generator = CodeGeneratorTemplate::from_directory(&root);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let gstr = get_gstr();
+    let cfg = grammar::CFG::from_strings(&gstr).unwrap();
+    let name = String::from("calc");
+    let data = CodeGeneratorData {
+        table: parse_table::Table {
+            rows: Vec::new(),
+            cfg: cfg.clone(),
+        },
+        project_name: name.clone(),
+        messages: BTreeMap::new(),
+    };
+    let mut fs = MemoryFileSystem::new();
+    let files = generator
+        .as_mut()
+        .unwrap()
+        .generate(&data, &mut fs)
+        .unwrap();
+    assert_eq!(vec![PathBuf::from("src/calc.py")], files.files);
+    assert_eq!("# calc", fs.get_str("src/calc.py").unwrap());
+}
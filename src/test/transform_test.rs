@@ -0,0 +1,282 @@
+use super::cfg;
+use crate::parsing::grammar::transform::*;
+use crate::parsing::grammar::{Symbol, CFG};
+use std::collections::HashSet;
+
+#[allow(dead_code)]
+fn expr() -> CFG {
+    cfg(&[
+        "E -> E .+ T",
+        "E -> T",
+        "T -> T .* F",
+        "T -> F",
+        "F -> .( E .)",
+        "F -> .id",
+    ])
+}
+
+//every sentence of at most max_len terminals the grammar accepts, spelled with terminal names
+//computed bottom up, so it works for left recursive grammars too
+#[allow(dead_code)]
+fn sentences(cfg: &CFG, max_len: usize) -> HashSet<Vec<String>> {
+    let mut langs: Vec<HashSet<Vec<usize>>> = vec![HashSet::new(); cfg.productions.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for prod in cfg.productions.iter().flatten() {
+            let mut strings: HashSet<Vec<usize>> = HashSet::new();
+            strings.insert(Vec::new());
+            for symbol in &prod.rhs {
+                strings = match symbol {
+                    Symbol::Terminal(t) => strings
+                        .into_iter()
+                        .filter(|s| s.len() < max_len)
+                        .map(|mut s| {
+                            s.push(*t);
+                            s
+                        })
+                        .collect(),
+                    Symbol::Nonterminal(n) => {
+                        let mut next = HashSet::new();
+                        for s in &strings {
+                            for tail in &langs[*n] {
+                                if s.len() + tail.len() <= max_len {
+                                    next.insert(s.iter().chain(tail).copied().collect());
+                                }
+                            }
+                        }
+                        next
+                    }
+                    _ => strings,
+                };
+            }
+            for s in strings {
+                changed |= langs[prod.nonterminal].insert(s);
+            }
+        }
+    }
+    langs[0]
+        .iter()
+        .map(|s| {
+            s.iter()
+                .map(|t| String::from(cfg.terminal_symbols.name(*t)))
+                .collect()
+        })
+        .collect()
+}
+
+#[allow(dead_code)]
+fn assert_same_language(a: &CFG, b: &CFG) {
+    let sa = sentences(a, 6);
+    assert!(!sa.is_empty());
+    assert_eq!(sa, sentences(b, 6));
+}
+
+#[allow(dead_code)]
+fn assert_origins_valid(original: &CFG, t: &Transformed) {
+    assert_eq!(t.cfg.production_count(), t.origins.len());
+    for ids in &t.origins {
+        assert!(ids.iter().all(|x| *x < original.production_count()));
+    }
+    //the start rule is left alone
+    assert_eq!(&[0], t.origin(0));
+}
+
+#[allow(dead_code)]
+fn has_rhs(cfg: &CFG, nonterminal: &str, rhs: &[&str]) -> Option<usize> {
+    let nt = cfg.nonterminal_symbols.get(nonterminal)?;
+    (0..cfg.production_count()).find(|id| {
+        let p = cfg.production(*id);
+        p.nonterminal == nt
+            && p.rhs
+                .iter()
+                .map(|x| cfg.symbol_str(x))
+                .collect::<Vec<&str>>()
+                == rhs
+    })
+}
+
+#[test]
+fn direct_left_recursion() {
+    let original = expr();
+    assert_eq!(Some(1), left_recursive(&original));
+    let t = remove_left_recursion(&original).unwrap();
+    assert_eq!(None, left_recursive(&t.cfg));
+    assert_same_language(&original, &t.cfg);
+    assert_origins_valid(&original, &t);
+    //E -> T E', E' -> .+ T E' | <empty>
+    let id = has_rhs(&t.cfg, "E", &["T", "E'"]).unwrap();
+    assert_eq!(&[2], t.origin(id));
+    let id = has_rhs(&t.cfg, "E'", &[".+", "T", "E'"]).unwrap();
+    assert_eq!(&[1], t.origin(id));
+    let id = has_rhs(&t.cfg, "E'", &["<empty>"]).unwrap();
+    assert!(t.origin(id).is_empty());
+}
+
+#[test]
+fn indirect_left_recursion() {
+    let original = cfg(&[
+        "S -> A .a",
+        "S -> .b",
+        "A -> A .c",
+        "A -> S .d",
+        "A -> <empty>",
+    ]);
+    assert!(left_recursive(&original).is_some());
+    let t = remove_left_recursion(&original).unwrap();
+    assert_eq!(None, left_recursive(&t.cfg));
+    assert_same_language(&original, &t.cfg);
+    assert_origins_valid(&original, &t);
+    //A -> .b .d A' came from substituting S -> .b into A -> S .d
+    let id = has_rhs(&t.cfg, "A", &[".b", ".d", "A'"]).unwrap();
+    assert_eq!(&[2, 4], t.origin(id));
+}
+
+#[test]
+fn left_recursion_errors() {
+    assert!(matches!(
+        remove_left_recursion(&cfg(&["S -> A", "A -> S", "A -> .a"])),
+        Err(TransformError::Cycle(_))
+    ));
+    assert!(matches!(
+        remove_left_recursion(&cfg(&["S -> A S .x", "S -> .y", "A -> <empty>"])),
+        Err(TransformError::HiddenLeftRecursion(_))
+    ));
+    assert!(matches!(
+        remove_left_recursion(&cfg(&["S -> .x A", "A -> A .a"])),
+        Err(TransformError::NonTerminating(_))
+    ));
+    //removing epsilons first fixes hidden left recursion
+    let original = cfg(&["S -> A S .x", "S -> .y", "A -> <empty>"]);
+    let t = remove_epsilons(&original);
+    let next = remove_left_recursion(&t.cfg).unwrap();
+    let t = t.then(next);
+    assert_eq!(None, left_recursive(&t.cfg));
+    assert_same_language(&original, &t.cfg);
+    assert_origins_valid(&original, &t);
+}
+
+#[test]
+fn left_factoring() {
+    let original = cfg(&[
+        "S -> .if E .then S",
+        "S -> .if E .then S .else S",
+        "S -> .a",
+        "E -> .b",
+        "E -> .b .c",
+        "E -> .b .c",
+    ]);
+    let t = left_factor(&original);
+    assert_same_language(&original, &t.cfg);
+    assert_origins_valid(&original, &t);
+    for prods in &t.cfg.productions {
+        let firsts: HashSet<Symbol> = prods.iter().map(|p| p.rhs[0]).collect();
+        assert_eq!(prods.len(), firsts.len());
+    }
+    //S' is taken by the start rule
+    let id = has_rhs(&t.cfg, "S", &[".if", "E", ".then", "S", "S''"]).unwrap();
+    assert_eq!(&[1, 2], t.origin(id));
+    assert!(has_rhs(&t.cfg, "S''", &[".else", "S"]).is_some());
+    assert!(has_rhs(&t.cfg, "S''", &["<empty>"]).is_some());
+    //the duplicate rule is merged into one
+    let id = has_rhs(&t.cfg, "E'", &[".c"]).unwrap();
+    assert_eq!(&[5, 6], t.origin(id));
+}
+
+#[test]
+fn epsilons() {
+    let original = cfg(&[
+        "S -> A B",
+        "A -> .a A",
+        "A -> <empty>",
+        "B -> .b",
+        "B -> C",
+        "C -> <empty>",
+    ]);
+    let t = remove_epsilons(&original);
+    assert!(sentences(&original, 6).contains(&Vec::new()));
+    assert_same_language(&original, &t.cfg);
+    assert_origins_valid(&original, &t);
+    assert!(t.cfg.nullable().iter().all(|x| !x));
+    assert!(t
+        .cfg
+        .productions
+        .iter()
+        .flatten()
+        .all(|p| !p.rhs.contains(&Symbol::Empty())));
+    //C only derived <empty>, so it's gone
+    assert_eq!(None, t.cfg.nonterminal_symbols.get("C"));
+    //the start rule is still production 0, the empty input is accepted by the one after it
+    assert_eq!(Some(0), has_rhs(&t.cfg, "S'", &["S", "<eof>"]));
+    assert_eq!(Some(1), has_rhs(&t.cfg, "S'", &["<eof>"]));
+}
+
+#[test]
+fn unit_productions() {
+    let original = expr();
+    let t = remove_unit_productions(&original);
+    assert_same_language(&original, &t.cfg);
+    assert_origins_valid(&original, &t);
+    assert!(t
+        .cfg
+        .productions
+        .iter()
+        .flatten()
+        .all(|p| !matches!(p.rhs.as_slice(), [Symbol::Nonterminal(_)])));
+    //E -> .id came through E -> T and T -> F
+    let id = has_rhs(&t.cfg, "E", &[".id"]).unwrap();
+    assert_eq!(&[2, 4, 6], t.origin(id));
+}
+
+#[test]
+fn useless_symbols() {
+    let original = cfg(&[
+        "S -> A .a",
+        "S -> .b",
+        "A -> A .c",
+        "B -> .d",
+        "S -> D",
+        "D -> .e",
+    ]);
+    let t = remove_useless_symbols(&original).unwrap();
+    assert_same_language(&original, &t.cfg);
+    assert_origins_valid(&original, &t);
+    assert_eq!(
+        vec!["S'", "S", "D"],
+        t.cfg.nonterminal_symbols.iter().collect::<Vec<&str>>()
+    );
+    assert_eq!(
+        vec![".b", ".e"],
+        t.cfg.terminal_symbols.iter().collect::<Vec<&str>>()
+    );
+    assert!(matches!(
+        remove_useless_symbols(&cfg(&["S -> S .a"])),
+        Err(TransformError::EmptyLanguage())
+    ));
+}
+
+#[test]
+fn chained() {
+    let original = cfg(&[
+        "S -> S .; A",
+        "S -> A",
+        "S -> <empty>",
+        "A -> E",
+        "A -> .id .:= E",
+        "E -> E .+ .id",
+        "E -> .id",
+        "U -> .u",
+    ]);
+    let t = remove_epsilons(&original);
+    let next = remove_unit_productions(&t.cfg);
+    let t = t.then(next);
+    let next = remove_useless_symbols(&t.cfg).unwrap();
+    let t = t.then(next);
+    let next = remove_left_recursion(&t.cfg).unwrap();
+    let t = t.then(next);
+    let next = left_factor(&t.cfg);
+    let t = t.then(next);
+    assert_eq!(None, left_recursive(&t.cfg));
+    assert_same_language(&original, &t.cfg);
+    assert_origins_valid(&original, &t);
+}
@@ -0,0 +1,36 @@
+//stable 64 bit fnv-1a hash, used where a hash is saved to disk (std's hasher may change between releases)
+
+pub struct Fnv64 {
+    state: u64,
+}
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Fnv64 {
+            state: 0xcbf2_9ce4_8422_2325,
+        }
+    }
+}
+
+impl Fnv64 {
+    pub fn new() -> Fnv64 {
+        Fnv64::default()
+    }
+    pub fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.state ^= *b as u64;
+            self.state = self.state.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    //length prefixed so ["ab", "c"] and ["a", "bc"] hash differently
+    pub fn write_str(&mut self, s: &str) {
+        self.write_usize(s.len());
+        self.write(s.as_bytes());
+    }
+    pub fn write_usize(&mut self, x: usize) {
+        self.write(&(x as u64).to_le_bytes());
+    }
+    pub fn finish(&self) -> u64 {
+        self.state
+    }
+}
@@ -0,0 +1,3 @@
+pub mod hash;
+pub mod json;
+pub mod rng;